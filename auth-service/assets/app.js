@@ -39,6 +39,7 @@ const loginErrAlter = document.getElementById("login-err-alert");
 loginButton.addEventListener("click", (e) => {
     e.preventDefault();
 
+    const tenant = loginForm.tenant.value;
     const email = loginForm.email.value;
     const password = loginForm.password.value;
 
@@ -47,7 +48,7 @@ loginButton.addEventListener("click", (e) => {
         headers: {
             'Content-Type': 'application/json',
         },
-        body: JSON.stringify({ email, password }),
+        body: JSON.stringify({ tenant, email, password }),
     }).then(response => {
         if (response.status === 206) {
             TwoFAForm.email.value = email;
@@ -88,6 +89,7 @@ const signupErrAlter = document.getElementById("signup-err-alert");
 signupButton.addEventListener("click", (e) => {
     e.preventDefault();
 
+    const tenant = signupForm.tenant.value;
     const email = signupForm.email.value;
     const password = signupForm.password.value;
     const requires2FA = signupForm.twoFA.checked;
@@ -97,7 +99,7 @@ signupButton.addEventListener("click", (e) => {
         headers: {
             'Content-Type': 'application/json',
         },
-        body: JSON.stringify({ email, password, requires2FA }),
+        body: JSON.stringify({ tenant, email, password, requires2FA }),
     }).then(response => {
         if (response.ok) {
             signupForm.email.value = "";
@@ -113,6 +115,10 @@ signupButton.addEventListener("click", (e) => {
                 let error_msg = data.error;
                 if (error_msg !== undefined && error_msg !== null && error_msg !== "") {
                     signupErrAlter.innerHTML = `<span><strong>Error: </strong>${error_msg}</span>`;
+                    if (Array.isArray(data.details) && data.details.length > 0) {
+                        const rules = data.details.map(detail => `<li>${detail.message}</li>`).join("");
+                        signupErrAlter.innerHTML += `<ul style="margin: 0;">${rules}</ul>`;
+                    }
                     signupErrAlter.style.display = "block";
                 } else {
                     signupErrAlter.style.display = "none";
@@ -161,4 +167,58 @@ TwoFAButton.addEventListener("click", (e) => {
             });
         }
     });
-});
\ No newline at end of file
+});
+// -----------------------------------------------------
+
+// Invitation links point to this page with the token in the `invite` query parameter
+const inviteSection = document.getElementById("invite-section");
+const inviteForm = document.getElementById("invite-form");
+const inviteButton = document.getElementById("invite-form-submit");
+const inviteErrAlter = document.getElementById("invite-err-alert");
+const inviteToken = new URLSearchParams(window.location.search).get("invite");
+
+if (inviteToken) {
+    loginSection.style.display = "none";
+    inviteSection.style.display = "block";
+}
+
+inviteButton.addEventListener("click", (e) => {
+    e.preventDefault();
+
+    const password = inviteForm.password.value;
+    const requires2FA = inviteForm.twoFA.checked;
+
+    fetch('/accept-invite', {
+        method: 'POST',
+        headers: {
+            'Content-Type': 'application/json',
+        },
+        body: JSON.stringify({ token: inviteToken, password, requires2FA }),
+    }).then(response => {
+        if (response.ok) {
+            response.json().then(data => {
+                inviteForm.password.value = "";
+                inviteErrAlter.style.display = "none";
+                alert(data.message);
+                window.history.replaceState(null, "", "/");
+                loginForm.tenant.value = data.tenant;
+                inviteSection.style.display = "none";
+                loginSection.style.display = "block";
+            });
+        } else {
+            response.json().then(data => {
+                let error_msg = data.error;
+                if (error_msg !== undefined && error_msg !== null && error_msg !== "") {
+                    inviteErrAlter.innerHTML = `<span><strong>Error: </strong>${error_msg}</span>`;
+                    if (Array.isArray(data.details) && data.details.length > 0) {
+                        const rules = data.details.map(detail => `<li>${detail.message}</li>`).join("");
+                        inviteErrAlter.innerHTML += `<ul style="margin: 0;">${rules}</ul>`;
+                    }
+                    inviteErrAlter.style.display = "block";
+                } else {
+                    inviteErrAlter.style.display = "none";
+                }
+            });
+        }
+    });
+});
@@ -0,0 +1,13 @@
+ALTER TABLE audit_log ADD COLUMN IF NOT EXISTS email TEXT;
+UPDATE audit_log SET email = users.email FROM users WHERE audit_log.user_id = users.id;
+ALTER TABLE audit_log DROP CONSTRAINT IF EXISTS audit_log_user_id_fkey;
+ALTER TABLE audit_log DROP COLUMN IF EXISTS user_id;
+
+ALTER TABLE users DROP CONSTRAINT IF EXISTS users_email_key;
+ALTER TABLE users DROP CONSTRAINT IF EXISTS users_pkey;
+ALTER TABLE users ADD PRIMARY KEY (email);
+ALTER TABLE users DROP COLUMN IF EXISTS id;
+
+ALTER TABLE audit_log ALTER COLUMN email SET NOT NULL;
+ALTER TABLE audit_log ADD CONSTRAINT audit_log_email_fkey FOREIGN KEY (email) REFERENCES users(email) ON UPDATE CASCADE ON DELETE CASCADE;
+CREATE INDEX IF NOT EXISTS audit_log_email_idx ON audit_log(email);
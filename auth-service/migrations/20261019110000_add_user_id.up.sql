@@ -0,0 +1,19 @@
+-- Give every user a stable id and make it the primary key instead of the email
+ALTER TABLE users ADD COLUMN IF NOT EXISTS id UUID;
+UPDATE users SET id = gen_random_uuid() WHERE id IS NULL;
+ALTER TABLE users ALTER COLUMN id SET NOT NULL;
+ALTER TABLE users ALTER COLUMN id SET DEFAULT gen_random_uuid();
+
+ALTER TABLE audit_log ADD COLUMN IF NOT EXISTS user_id UUID;
+UPDATE audit_log SET user_id = users.id FROM users WHERE audit_log.email = users.email;
+ALTER TABLE audit_log DROP CONSTRAINT IF EXISTS audit_log_email_fkey;
+ALTER TABLE audit_log DROP COLUMN IF EXISTS email;
+
+ALTER TABLE users DROP CONSTRAINT IF EXISTS users_pkey;
+ALTER TABLE users ADD PRIMARY KEY (id);
+-- Lookups by email keep using an index, case variants are merged when emails are normalized
+ALTER TABLE users ADD CONSTRAINT users_email_key UNIQUE (email);
+
+ALTER TABLE audit_log ALTER COLUMN user_id SET NOT NULL;
+ALTER TABLE audit_log ADD CONSTRAINT audit_log_user_id_fkey FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE;
+CREATE INDEX IF NOT EXISTS audit_log_user_id_idx ON audit_log(user_id);
@@ -0,0 +1,26 @@
+-- Emails are stored in their normalized form, lowercase local part and punycode domain.
+-- SQL cannot convert internationalized domains, addresses using one are normalized beforehand, like the API does,
+-- with `cargo run --bin normalize_emails`, which also reports the accounts to merge.
+DO $$
+DECLARE
+    conflicts TEXT;
+    unicode_domains TEXT;
+BEGIN
+    SELECT string_agg(variants, '; ') INTO conflicts FROM (
+        SELECT string_agg(email, ', ' ORDER BY email) AS variants FROM users
+        GROUP BY LOWER(email)
+        HAVING COUNT(*) > 1
+    ) AS duplicates;
+    IF conflicts IS NOT NULL THEN
+        RAISE EXCEPTION 'Users whose emails only differ by case must be merged or deleted before normalizing emails: %', conflicts;
+    END IF;
+
+    SELECT string_agg(email, ', ' ORDER BY email) INTO unicode_domains FROM users
+    WHERE substring(email FROM '@([^@]*)$') ~ '[^\x01-\x7f]';
+    IF unicode_domains IS NOT NULL THEN
+        RAISE EXCEPTION 'Emails with an internationalized domain must be normalized with the normalize_emails binary first: %', unicode_domains;
+    END IF;
+END $$;
+
+UPDATE users SET email = LOWER(email) WHERE email <> LOWER(email);
+ALTER TABLE users ADD CONSTRAINT users_email_normalized CHECK (email = LOWER(email));
@@ -0,0 +1,4 @@
+DELETE FROM role_permissions WHERE role = 'admin' AND permission = 'users:manage';
+
+ALTER TABLE users DROP COLUMN IF EXISTS password_reset_required;
+ALTER TABLE users DROP COLUMN IF EXISTS locked_at;
@@ -0,0 +1,6 @@
+DELETE FROM role_permissions WHERE role = 'admin' AND permission = 'tenant:manage';
+
+DROP INDEX IF EXISTS users_tenant_id_idx;
+ALTER TABLE users DROP COLUMN IF EXISTS tenant_id;
+
+DROP TABLE IF EXISTS tenants;
@@ -0,0 +1,23 @@
+-- Organizations, each user belongs to exactly one of them
+CREATE TABLE IF NOT EXISTS tenants(
+    id UUID PRIMARY KEY,
+    slug TEXT NOT NULL UNIQUE,
+    name TEXT NOT NULL,
+    host TEXT UNIQUE,
+    requires_2fa BOOLEAN NOT NULL DEFAULT FALSE,
+    allowed_email_domains TEXT[] NOT NULL DEFAULT '{}',
+    -- Password policy overrides, NULL keeps the service wide setting
+    password_min_length INTEGER,
+    password_min_score SMALLINT,
+    password_history_length INTEGER,
+    password_max_age_days INTEGER,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Existing users move to the default organization
+INSERT INTO tenants (id, slug, name) VALUES ('00000000-0000-0000-0000-000000000000', 'default', 'Default') ON CONFLICT DO NOTHING;
+
+ALTER TABLE users ADD COLUMN IF NOT EXISTS tenant_id UUID NOT NULL DEFAULT '00000000-0000-0000-0000-000000000000' REFERENCES tenants(id);
+CREATE INDEX IF NOT EXISTS users_tenant_id_idx ON users(tenant_id);
+
+INSERT INTO role_permissions (role, permission) VALUES ('admin', 'tenant:manage') ON CONFLICT DO NOTHING;
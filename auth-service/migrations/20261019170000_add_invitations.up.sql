@@ -0,0 +1,22 @@
+-- Admins invite people to their organization, the emailed link is accepted once
+CREATE TABLE IF NOT EXISTS invitations(
+    id UUID PRIMARY KEY,
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    email TEXT NOT NULL,
+    -- SHA-256 of the emailed token, the token itself is not stored
+    token_hash TEXT NOT NULL UNIQUE,
+    invited_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMPTZ NOT NULL,
+    accepted_at TIMESTAMPTZ,
+    revoked_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS invitations_tenant_id_email_idx ON invitations(tenant_id, email);
+
+-- Roles assigned once the invitation is accepted
+CREATE TABLE IF NOT EXISTS invitation_roles(
+    invitation_id UUID NOT NULL REFERENCES invitations(id) ON DELETE CASCADE,
+    role TEXT NOT NULL REFERENCES roles(name) ON DELETE CASCADE,
+    PRIMARY KEY (invitation_id, role)
+);
@@ -0,0 +1,4 @@
+ALTER TABLE users DROP COLUMN IF EXISTS approval_pending;
+
+ALTER TABLE tenants DROP COLUMN IF EXISTS signup_email_domains;
+ALTER TABLE tenants DROP COLUMN IF EXISTS signup_mode;
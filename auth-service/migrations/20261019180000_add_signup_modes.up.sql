@@ -0,0 +1,6 @@
+ALTER TABLE tenants ADD COLUMN IF NOT EXISTS signup_mode TEXT NOT NULL DEFAULT 'open'
+    CHECK (signup_mode IN ('open', 'disabled', 'invite_only', 'domain_restricted', 'pending_approval'));
+ALTER TABLE tenants ADD COLUMN IF NOT EXISTS signup_email_domains TEXT[] NOT NULL DEFAULT '{}';
+
+-- Users who signed up while approval was required cannot log in until an admin approves them
+ALTER TABLE users ADD COLUMN IF NOT EXISTS approval_pending BOOLEAN NOT NULL DEFAULT FALSE;
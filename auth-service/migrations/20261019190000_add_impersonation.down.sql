@@ -0,0 +1,3 @@
+DELETE FROM role_permissions WHERE role = 'admin' AND permission = 'users:impersonate';
+
+ALTER TABLE audit_log DROP COLUMN IF EXISTS actor_id;
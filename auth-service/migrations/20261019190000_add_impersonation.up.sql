@@ -0,0 +1,4 @@
+-- The admin who acted for the user, set on the entries of impersonations
+ALTER TABLE audit_log ADD COLUMN IF NOT EXISTS actor_id UUID REFERENCES users(id) ON DELETE SET NULL;
+
+INSERT INTO role_permissions (role, permission) VALUES ('admin', 'users:impersonate') ON CONFLICT DO NOTHING;
@@ -1,34 +1,121 @@
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
-use crate::domain::EmailClient;
-use crate::domain::data_store::{BannedTokenStore, TwoFACodeStore, UserStore};
+use crate::services::data_store::{hashmap_invitation_store::HashmapInvitationStore, hashmap_personal_access_token_store::HashmapPersonalAccessTokenStore, hashmap_role_store::HashmapRoleStore, hashmap_tenant_store::HashmapTenantStore, hashmap_two_fa_code_store::HashmapTwoFACodeStore};
+use crate::services::hashing_pool::HashingPool;
+use crate::domain::{BreachedPasswordChecker, EmailClient, EmailDomainFilter, PasswordHasher, PasswordPolicy, TokenExchangeClient};
+use crate::domain::data_store::{AuditLogStore, BannedTokenStore, EmailChangeStore, InvitationStore, PersonalAccessTokenStore, RoleStore, TenantStore, TwoFACodeStore, UserStore};
 
 pub type UserStoreType = Arc<RwLock<dyn UserStore>>;
 pub type BannedTokenStoreType = Arc<RwLock<dyn BannedTokenStore>>;
 pub type TwoFACodeStoreType = Arc<RwLock<dyn TwoFACodeStore>>;
+pub type EmailChangeStoreType = Arc<RwLock<dyn EmailChangeStore>>;
+pub type AuditLogStoreType = Arc<RwLock<dyn AuditLogStore>>;
+pub type RoleStoreType = Arc<RwLock<dyn RoleStore>>;
+pub type TenantStoreType = Arc<RwLock<dyn TenantStore>>;
+pub type InvitationStoreType = Arc<RwLock<dyn InvitationStore>>;
+pub type PersonalAccessTokenStoreType = Arc<RwLock<dyn PersonalAccessTokenStore>>;
 pub type EmailClientType = Arc<RwLock<dyn EmailClient>>;
+pub type PasswordPolicyType = Arc<dyn PasswordPolicy>;
+pub type BreachedPasswordCheckerType = Arc<dyn BreachedPasswordChecker>;
+pub type EmailDomainFilterType = Arc<dyn EmailDomainFilter>;
+pub type HashingPoolType = Arc<HashingPool>;
+pub type PasswordHasherType = Arc<dyn PasswordHasher>;
 
 #[derive(Clone)]
 pub struct AppState {
     pub user_store: UserStoreType,
     pub banned_token_store: BannedTokenStoreType,
     pub two_fa_code_store: TwoFACodeStoreType,
+    // Codes confirming that 2FA is turned off, kept apart from the login codes so neither replaces nor stands in for the other
+    pub disable_2fa_code_store: TwoFACodeStoreType,
+    pub email_change_store: EmailChangeStoreType,
+    pub audit_log_store: AuditLogStoreType,
     pub email_client: EmailClientType,
+    pub password_policy: PasswordPolicyType,
+    // Signup answers the same whether or not the email is taken and tells the owner instead
+    pub enumeration_safe_signup: bool,
+    // Shared with the user store, kept here to report its metrics
+    pub hashing_pool: HashingPoolType,
+    pub role_store: RoleStoreType,
+    pub tenant_store: TenantStoreType,
+    pub invitation_store: InvitationStoreType,
+    pub personal_access_token_store: PersonalAccessTokenStoreType,
+    // Checked for the email of a signup or an email change, any domain is accepted without it
+    pub email_domain_filter: Option<EmailDomainFilterType>,
+    // Services allowed to exchange the tokens of users, none unless configured
+    pub token_exchange_clients: Arc<Vec<TokenExchangeClient>>,
 }
 
 impl AppState {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         user_store: UserStoreType,
         banned_token_store: BannedTokenStoreType,
         two_fa_code_store: TwoFACodeStoreType,
-        email_client: EmailClientType
+        email_change_store: EmailChangeStoreType,
+        audit_log_store: AuditLogStoreType,
+        email_client: EmailClientType,
+        password_policy: PasswordPolicyType,
+        hashing_pool: HashingPoolType,
     ) -> Self {
         Self {
             user_store,
             banned_token_store,
             two_fa_code_store,
-            email_client
+            disable_2fa_code_store: Arc::new(RwLock::new(HashmapTwoFACodeStore::default())),
+            email_change_store,
+            audit_log_store,
+            email_client,
+            password_policy,
+            enumeration_safe_signup: false,
+            hashing_pool,
+            role_store: Arc::new(RwLock::new(HashmapRoleStore::default())),
+            tenant_store: Arc::new(RwLock::new(HashmapTenantStore::default())),
+            invitation_store: Arc::new(RwLock::new(HashmapInvitationStore::default())),
+            personal_access_token_store: Arc::new(RwLock::new(HashmapPersonalAccessTokenStore::default())),
+            email_domain_filter: None,
+            token_exchange_clients: Arc::new(Vec::new()),
         }
     }
+
+    pub fn with_enumeration_safe_signup(mut self, enumeration_safe_signup: bool) -> Self {
+        self.enumeration_safe_signup = enumeration_safe_signup;
+        self
+    }
+
+    pub fn with_disable_2fa_code_store(mut self, disable_2fa_code_store: TwoFACodeStoreType) -> Self {
+        self.disable_2fa_code_store = disable_2fa_code_store;
+        self
+    }
+
+    pub fn with_role_store(mut self, role_store: RoleStoreType) -> Self {
+        self.role_store = role_store;
+        self
+    }
+
+    pub fn with_tenant_store(mut self, tenant_store: TenantStoreType) -> Self {
+        self.tenant_store = tenant_store;
+        self
+    }
+
+    pub fn with_invitation_store(mut self, invitation_store: InvitationStoreType) -> Self {
+        self.invitation_store = invitation_store;
+        self
+    }
+
+    pub fn with_personal_access_token_store(mut self, personal_access_token_store: PersonalAccessTokenStoreType) -> Self {
+        self.personal_access_token_store = personal_access_token_store;
+        self
+    }
+
+    pub fn with_email_domain_filter(mut self, email_domain_filter: EmailDomainFilterType) -> Self {
+        self.email_domain_filter = Some(email_domain_filter);
+        self
+    }
+
+    pub fn with_token_exchange_clients(mut self, token_exchange_clients: Vec<TokenExchangeClient>) -> Self {
+        self.token_exchange_clients = Arc::new(token_exchange_clients);
+        self
+    }
 }
\ No newline at end of file
@@ -0,0 +1,63 @@
+// Assigns a role to an existing user, which is how the first admin gets the permission to manage roles over the API.
+//
+// Usage: assign_role <email> <role>
+
+use std::{env, process, sync::Arc};
+
+use auth_service::{
+    domain::{
+        data_store::{RoleStore, RoleStoreError, UserStore},
+        Email,
+    },
+    get_postgres_pool,
+    services::{
+        data_store::{postgres_role_store::PostgresRoleStore, postgres_user_store::PostgresUserStore},
+        hashing_pool::HashingPool,
+        password_hasher::Argon2PasswordHasher,
+    },
+    utils::{ARGON2_PARAMS, DATABASE_URL, HASHING_MAX_CONCURRENCY, HASHING_MAX_QUEUED, PASSWORD_PEPPERS},
+};
+
+#[tokio::main]
+async fn main() {
+    let args: Vec<String> = env::args().collect();
+    if args.len() != 3 {
+        eprintln!("Usage: {} <email> <role>", args[0]);
+        process::exit(1);
+    }
+
+    let email = Email::parse(args[1].clone()).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        process::exit(1);
+    });
+    let role = &args[2];
+
+    let pg_pool = get_postgres_pool(&DATABASE_URL)
+        .await
+        .expect("Failed to create Postgres connection pool!");
+    sqlx::migrate!()
+        .run(&pg_pool)
+        .await
+        .expect("Failed to run migrations");
+    let hashing_pool = Arc::new(HashingPool::new(*HASHING_MAX_CONCURRENCY, *HASHING_MAX_QUEUED));
+    let password_hasher = Arc::new(Argon2PasswordHasher::new(ARGON2_PARAMS.clone(), PASSWORD_PEPPERS.clone(), hashing_pool));
+    let user_store = PostgresUserStore::new(pg_pool.clone(), password_hasher);
+    let mut role_store = PostgresRoleStore::new(pg_pool);
+
+    let user = user_store.get_user_by_email(&email).await.unwrap_or_else(|_| {
+        eprintln!("No user with the email {}", email.as_ref());
+        process::exit(1);
+    });
+
+    match role_store.assign_role(&user.get_id(), role).await {
+        Ok(()) => println!("Assigned the {} role to {}", role, email.as_ref()),
+        Err(RoleStoreError::RoleNotFound) => {
+            eprintln!("Unknown role {}", role);
+            process::exit(1);
+        }
+        Err(e) => {
+            eprintln!("Failed to assign the role: {:?}", e);
+            process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,60 @@
+// Builds the filter loaded through BREACHED_PASSWORDS_FILTER_PATH from a text file.
+// Lines can either come from a Pwned Passwords SHA-1 dump ("<hash>:<count>") or be plain passwords.
+//
+// Usage: build_breached_password_filter <input> <output> [false positive rate, default 0.001]
+
+use std::{
+    env, fs,
+    fs::File,
+    io::{BufRead, BufReader},
+    process,
+};
+
+use auth_service::services::breached_password_checker::{parse_hash_line, password_digest, BloomFilter};
+
+const DEFAULT_FALSE_POSITIVE_RATE: f64 = 0.001;
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    if args.len() < 3 || args.len() > 4 {
+        eprintln!("Usage: {} <input> <output> [false positive rate]", args[0]);
+        process::exit(1);
+    }
+
+    let (input, output) = (&args[1], &args[2]);
+    let false_positive_rate = match args.get(3) {
+        Some(rate) => rate.parse().ok().filter(|rate| *rate > 0.0 && *rate < 1.0).unwrap_or_else(|| {
+            eprintln!("The false positive rate must be a number between 0 and 1");
+            process::exit(1);
+        }),
+        None => DEFAULT_FALSE_POSITIVE_RATE,
+    };
+
+    // A dump has hundreds of millions of lines, so it is read twice instead of kept in memory
+    let items = read_lines(input).count();
+    let mut filter = BloomFilter::new(items, false_positive_rate);
+    for line in read_lines(input) {
+        let digest = parse_hash_line(&line).unwrap_or_else(|| password_digest(&line));
+        filter.insert(&digest);
+    }
+
+    let bytes = filter.to_bytes();
+    if let Err(e) = fs::write(output, &bytes) {
+        eprintln!("Failed to write {}: {}", output, e);
+        process::exit(1);
+    }
+
+    println!("Wrote {} passwords to {} ({} bytes)", items, output, bytes.len());
+}
+
+fn read_lines(path: &str) -> impl Iterator<Item = String> {
+    let file = File::open(path).unwrap_or_else(|e| {
+        eprintln!("Failed to open {}: {}", path, e);
+        process::exit(1);
+    });
+
+    BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .filter(|line| !line.is_empty())
+}
@@ -0,0 +1,53 @@
+// Creates an organization, members then sign up with its slug or on its login host.
+//
+// Usage: create_tenant <slug> <name> [host]
+
+use std::{env, process};
+
+use auth_service::{
+    domain::{
+        data_store::{TenantStore, TenantStoreError},
+        Tenant, TenantSlug,
+    },
+    get_postgres_pool,
+    services::data_store::postgres_tenant_store::PostgresTenantStore,
+    utils::DATABASE_URL,
+};
+
+#[tokio::main]
+async fn main() {
+    let args: Vec<String> = env::args().collect();
+    if args.len() != 3 && args.len() != 4 {
+        eprintln!("Usage: {} <slug> <name> [host]", args[0]);
+        process::exit(1);
+    }
+
+    let slug = TenantSlug::parse(args[1].clone()).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        process::exit(1);
+    });
+    let host = args.get(3).map(|host| host.trim().to_lowercase());
+    let tenant = Tenant::new(slug, args[2].clone(), host);
+
+    let pg_pool = get_postgres_pool(&DATABASE_URL)
+        .await
+        .expect("Failed to create Postgres connection pool!");
+    sqlx::migrate!()
+        .run(&pg_pool)
+        .await
+        .expect("Failed to run migrations");
+    let mut tenant_store = PostgresTenantStore::new(pg_pool);
+
+    let (id, slug) = (tenant.id, tenant.slug.clone());
+    match tenant_store.add_tenant(tenant).await {
+        Ok(()) => println!("Created the organization {} with the id {}", slug.as_ref(), id),
+        Err(TenantStoreError::TenantAlreadyExists) => {
+            eprintln!("An organization with the slug or host already exists");
+            process::exit(1);
+        }
+        Err(e) => {
+            eprintln!("Failed to create the organization: {:?}", e);
+            process::exit(1);
+        }
+    }
+}
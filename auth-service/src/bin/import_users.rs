@@ -0,0 +1,95 @@
+// Loads users migrated from older systems together with their existing password hashes.
+// Argon2, bcrypt, scrypt and PBKDF2 hashes are accepted, the legacy ones are upgraded to Argon2 on each user's first login.
+//
+// CSV files need an "email,password_hash,requires_2fa" header, JSON files an array of objects with the same fields.
+// requires_2fa is optional and defaults to false.
+//
+// Usage: import_users <users.csv|users.json>
+
+use std::{env, fs::File, process, sync::Arc};
+
+use auth_service::{
+    domain::{data_store::UserStoreError, Email, Password, User},
+    get_postgres_pool,
+    services::{
+        data_store::postgres_user_store::PostgresUserStore,
+        hashing_pool::HashingPool,
+        password_hasher::{is_supported_password_hash, Argon2PasswordHasher},
+    },
+    utils::{ARGON2_PARAMS, DATABASE_URL, HASHING_MAX_CONCURRENCY, HASHING_MAX_QUEUED, PASSWORD_PEPPERS},
+};
+use serde::Deserialize;
+
+#[derive(Debug, Deserialize)]
+struct ImportedUser {
+    email: String,
+    password_hash: String,
+    #[serde(default)]
+    requires_2fa: bool,
+}
+
+#[tokio::main]
+async fn main() {
+    let args: Vec<String> = env::args().collect();
+    if args.len() != 2 {
+        eprintln!("Usage: {} <users.csv|users.json>", args[0]);
+        process::exit(1);
+    }
+
+    let imported_users = read_users(&args[1]).unwrap_or_else(|e| {
+        eprintln!("Failed to read {}: {}", args[1], e);
+        process::exit(1);
+    });
+
+    let pg_pool = get_postgres_pool(&DATABASE_URL)
+        .await
+        .expect("Failed to create Postgres connection pool!");
+    sqlx::migrate!()
+        .run(&pg_pool)
+        .await
+        .expect("Failed to run migrations");
+    let hashing_pool = Arc::new(HashingPool::new(*HASHING_MAX_CONCURRENCY, *HASHING_MAX_QUEUED));
+    let password_hasher = Arc::new(Argon2PasswordHasher::new(ARGON2_PARAMS.clone(), PASSWORD_PEPPERS.clone(), hashing_pool));
+    let user_store = PostgresUserStore::new(pg_pool, password_hasher);
+
+    let (mut imported, mut skipped) = (0, 0);
+    for (line, imported_user) in imported_users.into_iter().enumerate() {
+        let email = imported_user.email.clone();
+        match import_user(&user_store, imported_user).await {
+            Ok(()) => imported += 1,
+            Err(reason) => {
+                skipped += 1;
+                eprintln!("Skipped user {} ({}): {}", line + 1, email, reason);
+            }
+        }
+    }
+
+    println!("Imported {} users, skipped {}", imported, skipped);
+}
+
+fn read_users(path: &str) -> Result<Vec<ImportedUser>, String> {
+    let file = File::open(path).map_err(|e| e.to_string())?;
+
+    if path.ends_with(".json") {
+        serde_json::from_reader(file).map_err(|e| e.to_string())
+    } else {
+        csv::Reader::from_reader(file)
+            .deserialize()
+            .collect::<Result<Vec<ImportedUser>, _>>()
+            .map_err(|e| e.to_string())
+    }
+}
+
+async fn import_user(user_store: &PostgresUserStore, imported_user: ImportedUser) -> Result<(), String> {
+    let email = Email::parse(imported_user.email)?;
+    if !is_supported_password_hash(&imported_user.password_hash) {
+        return Err("Unsupported password hash".to_string());
+    }
+    let password_hash = Password::parse(imported_user.password_hash)?;
+
+    match user_store.import_user(User::new(email, password_hash, imported_user.requires_2fa)).await {
+        Ok(()) => Ok(()),
+        Err(UserStoreError::UserAlreadyExists) => Err("A user with this email already exists".to_string()),
+        Err(e) => Err(format!("{:?}", e)),
+    }
+}
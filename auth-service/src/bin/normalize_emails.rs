@@ -0,0 +1,58 @@
+// Normalizes the stored emails like the API does, lowercase local part and punycode domain, which the
+// normalize_emails migration cannot do in SQL for internationalized domains. It runs before that migration,
+// and changes nothing while some users would end up with the same email, listing them to be merged or deleted.
+//
+// Usage: normalize_emails
+
+use std::{collections::BTreeMap, process};
+
+use auth_service::{domain::Email, get_postgres_pool, utils::DATABASE_URL};
+
+#[tokio::main]
+async fn main() {
+    let pg_pool = get_postgres_pool(&DATABASE_URL)
+        .await
+        .expect("Failed to create Postgres connection pool!");
+
+    let emails = sqlx::query_scalar!("SELECT email FROM users")
+        .fetch_all(&pg_pool)
+        .await
+        .expect("Failed to read the users");
+
+    let mut normalized: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    for email in emails {
+        match Email::parse(email.clone()) {
+            Ok(parsed) => normalized.entry(parsed.as_ref().to_owned()).or_default().push(email),
+            Err(e) => eprintln!("Skipping {}: {}", email, e),
+        }
+    }
+
+    let conflicts: Vec<_> = normalized.iter().filter(|(_, emails)| emails.len() > 1).collect();
+    if !conflicts.is_empty() {
+        eprintln!("These users would share an email, merge or delete them first:");
+        for (email, variants) in conflicts {
+            eprintln!("  {}: {}", email, variants.join(", "));
+        }
+        process::exit(1);
+    }
+
+    let mut transaction = pg_pool.begin().await.expect("Failed to start a transaction");
+    let mut updated = 0;
+    for (email, variants) in &normalized {
+        if &variants[0] == email {
+            continue;
+        }
+
+        sqlx::query!("UPDATE users SET email = $2 WHERE email = $1", variants[0], email)
+            .execute(&mut *transaction)
+            .await
+            .unwrap_or_else(|e| {
+                eprintln!("Failed to normalize {}: {}", variants[0], e);
+                process::exit(1);
+            });
+        updated += 1;
+    }
+    transaction.commit().await.expect("Failed to commit the normalized emails");
+
+    println!("Normalized {} emails", updated);
+}
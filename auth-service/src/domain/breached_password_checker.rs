@@ -0,0 +1,6 @@
+use super::Password;
+
+pub trait BreachedPasswordChecker: Send + Sync {
+    // Whether the password is known from a public breach
+    fn is_breached(&self, password: &Password) -> bool;
+}
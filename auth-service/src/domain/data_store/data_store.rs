@@ -1,12 +1,47 @@
+use chrono::{DateTime, Utc};
 use rand::Rng;
 use serde::{Deserialize, Serialize};
-use crate::domain::{Email, Password, User};
+use crate::domain::{Email, Invitation, InvitationId, InvitationToken, Password, PersonalAccessToken, PersonalAccessTokenId, Tenant, TenantId, TenantSettings, User, UserAccount, UserId, UserRoles};
 
+// An email belongs to a single user across all organizations, so the methods taking one are unambiguous.
+// Lookups that start from what a visitor typed are still scoped to an organization.
 #[async_trait::async_trait]
 pub trait UserStore: Send + Sync {
     async fn add_user(&mut self, user: User) -> Result<(), UserStoreError>;
-    async fn get_user(&self, email: &Email) -> Result<User, UserStoreError>;
-    async fn validate_user(&self, email: &Email, password: &Password) -> Result<(), UserStoreError>;
+    // Like `add_user`, but the user cannot log in until `approve_user` is called
+    async fn add_pending_user(&mut self, user: User) -> Result<(), UserStoreError>;
+    async fn approve_user(&mut self, email: &Email) -> Result<(), UserStoreError>;
+    async fn get_user(&self, tenant_id: &TenantId, email: &Email) -> Result<User, UserStoreError>;
+    // For flows keyed by the address itself, like 2FA codes and email change links, whose owner already proved who they are
+    async fn get_user_by_email(&self, email: &Email) -> Result<User, UserStoreError>;
+    // The organization of the user holding the email, users scheduled for deletion included
+    async fn get_email_tenant(&self, email: &Email) -> Result<TenantId, UserStoreError>;
+    async fn get_user_by_id(&self, id: &UserId) -> Result<User, UserStoreError>;
+    async fn validate_user(&self, tenant_id: &TenantId, email: &Email, password: &Password) -> Result<(), UserStoreError>;
+    // The replaced password is kept in the user's password history
+    async fn update_password(&mut self, email: &Email, password: Password) -> Result<(), UserStoreError>;
+    // Whether the password is one of the user's last `count` passwords, the current one included
+    async fn is_recent_password(&self, email: &Email, password: &Password, count: usize) -> Result<bool, UserStoreError>;
+    // Number of replaced passwords kept for the user, the current one excluded
+    async fn get_password_history_count(&self, id: &UserId) -> Result<u64, UserStoreError>;
+    async fn get_password_changed_at(&self, email: &Email) -> Result<DateTime<Utc>, UserStoreError>;
+    async fn update_email(&mut self, email: &Email, new_email: Email) -> Result<(), UserStoreError>;
+    async fn update_requires_2fa(&mut self, email: &Email, requires_2fa: bool) -> Result<(), UserStoreError>;
+    async fn delete_user(&mut self, email: &Email) -> Result<(), UserStoreError>;
+    // A user scheduled for deletion is hidden from lookups until it is deleted for good
+    async fn schedule_user_deletion(&mut self, email: &Email, delete_at: DateTime<Utc>) -> Result<(), UserStoreError>;
+    // Clears the scheduled deletion of the member of the organization once its password is verified, during the grace period only
+    async fn cancel_user_deletion(&mut self, tenant_id: &TenantId, email: &Email, password: &Password) -> Result<User, UserStoreError>;
+    async fn delete_scheduled_users(&mut self) -> Result<u64, UserStoreError>;
+    // Members of the organization whose email contains `search`, ordered by email, along with the number of matching users.
+    // Unlike the lookups above it includes users scheduled for deletion. `pending_only` keeps the users awaiting approval.
+    async fn list_users(&self, tenant_id: &TenantId, search: Option<&str>, pending_only: bool, offset: u64, limit: u64) -> Result<(Vec<UserAccount>, u64), UserStoreError>;
+    async fn get_user_account(&self, id: &UserId) -> Result<UserAccount, UserStoreError>;
+    async fn set_locked(&mut self, email: &Email, locked: bool) -> Result<(), UserStoreError>;
+    // Cleared by the next password update
+    async fn require_password_reset(&mut self, email: &Email) -> Result<(), UserStoreError>;
+    // Moves the user to another organization
+    async fn update_tenant(&mut self, email: &Email, tenant_id: &TenantId) -> Result<(), UserStoreError>;
 }
 
 #[derive(Debug, PartialEq)]
@@ -14,6 +49,12 @@ pub enum UserStoreError {
     UserAlreadyExists,
     UserNotFound,
     InvalidCredentials,
+    // Only reported once the password was verified, so it reveals nothing to someone guessing
+    AccountLocked,
+    // Reported like `AccountLocked`, once the password was verified
+    ApprovalPending,
+    // Too many passwords are being hashed, the request can be retried later
+    Overloaded,
     UnexpectedError,
 }
 
@@ -22,6 +63,9 @@ pub enum UserStoreError {
 pub trait BannedTokenStore: Send + Sync {
     async fn storing_tokens(&mut self, token: String) -> Result<(), BannedTokenStoreError>;
     async fn token_is_banned(&self, token: &str) -> Result<bool, BannedTokenStoreError>;
+    // Ban every token of the user (JWT `sub`) that was issued before the current moment
+    async fn ban_user_tokens(&mut self, sub: &str) -> Result<(), BannedTokenStoreError>;
+    async fn user_token_is_banned(&self, sub: &str, issued_at: DateTime<Utc>) -> Result<bool, BannedTokenStoreError>;
 }
 
 #[derive(Debug, PartialEq)]
@@ -43,6 +87,174 @@ pub enum TwoFACodeStoreError {
     UnexpectedError,
 }
 
+#[async_trait::async_trait]
+pub trait EmailChangeStore: Send + Sync {
+    async fn add_request(&mut self, email: Email, new_email: Email, confirmation_token: EmailChangeToken, cancel_token: EmailChangeToken) -> Result<(), EmailChangeStoreError>;
+    async fn remove_request(&mut self, email: &Email) -> Result<(), EmailChangeStoreError>;
+    async fn get_request(&self, email: &Email) -> Result<(Email, EmailChangeToken, EmailChangeToken), EmailChangeStoreError>;
+}
+
+#[derive(Debug, PartialEq)]
+pub enum EmailChangeStoreError {
+    RequestNotFound,
+    UnexpectedError,
+}
+
+#[async_trait::async_trait]
+pub trait AuditLogStore: Send + Sync {
+    async fn add_entry(&mut self, user_id: &UserId, event: AuditEvent) -> Result<(), AuditLogStoreError>;
+    // An entry of something another user, like an admin impersonating the user, did for them
+    async fn add_entry_by_actor(&mut self, user_id: &UserId, actor_id: &UserId, event: AuditEvent) -> Result<(), AuditLogStoreError>;
+    async fn get_entries(&self, user_id: &UserId) -> Result<Vec<AuditEntry>, AuditLogStoreError>;
+}
+
+#[derive(Debug, PartialEq)]
+pub enum AuditLogStoreError {
+    UnexpectedError,
+}
+
+#[async_trait::async_trait]
+pub trait RoleStore: Send + Sync {
+    // The user's roles and the permissions they grant, both sorted and without duplicates
+    async fn get_user_roles(&self, user_id: &UserId) -> Result<UserRoles, RoleStoreError>;
+    // Assigning a role the user already has is not an error
+    async fn assign_role(&mut self, user_id: &UserId, role: &str) -> Result<(), RoleStoreError>;
+    async fn revoke_role(&mut self, user_id: &UserId, role: &str) -> Result<(), RoleStoreError>;
+}
+
+#[derive(Debug, PartialEq)]
+pub enum RoleStoreError {
+    RoleNotFound,
+    RoleNotAssigned,
+    UserNotFound,
+    UnexpectedError,
+}
+
+#[async_trait::async_trait]
+pub trait TenantStore: Send + Sync {
+    async fn add_tenant(&mut self, tenant: Tenant) -> Result<(), TenantStoreError>;
+    async fn get_tenant(&self, id: &TenantId) -> Result<Tenant, TenantStoreError>;
+    async fn get_tenant_by_slug(&self, slug: &str) -> Result<Tenant, TenantStoreError>;
+    async fn get_tenant_by_host(&self, host: &str) -> Result<Tenant, TenantStoreError>;
+    async fn update_settings(&mut self, id: &TenantId, settings: TenantSettings) -> Result<(), TenantStoreError>;
+}
+
+#[derive(Debug, PartialEq)]
+pub enum TenantStoreError {
+    // The slug or the host is taken
+    TenantAlreadyExists,
+    TenantNotFound,
+    UnexpectedError,
+}
+
+#[async_trait::async_trait]
+pub trait InvitationStore: Send + Sync {
+    // Revokes the pending invitations of the organization for the same address, so only the latest link works
+    async fn add_invitation(&mut self, invitation: Invitation) -> Result<(), InvitationStoreError>;
+    async fn get_invitation(&self, id: &InvitationId) -> Result<Invitation, InvitationStoreError>;
+    // Looked up by the hash of the token
+    async fn get_invitation_by_token(&self, token: &InvitationToken) -> Result<Invitation, InvitationStoreError>;
+    // Newest first
+    async fn list_invitations(&self, tenant_id: &TenantId) -> Result<Vec<Invitation>, InvitationStoreError>;
+    // Pending invitations to the address from any organization, newest first
+    async fn list_pending_invitations(&self, email: &Email) -> Result<Vec<Invitation>, InvitationStoreError>;
+    // Accepting and revoking only succeed for a pending invitation, so each link is used at most once
+    async fn accept_invitation(&mut self, id: &InvitationId) -> Result<(), InvitationStoreError>;
+    // Makes an accepted invitation pending again, for an acceptance that failed after claiming it
+    async fn release_invitation(&mut self, id: &InvitationId) -> Result<(), InvitationStoreError>;
+    async fn revoke_invitation(&mut self, id: &InvitationId) -> Result<(), InvitationStoreError>;
+}
+
+#[derive(Debug, PartialEq)]
+pub enum InvitationStoreError {
+    // Unknown, or no longer pending when accepting or revoking
+    InvitationNotFound,
+    RoleNotFound,
+    UnexpectedError,
+}
+
+#[async_trait::async_trait]
+pub trait PersonalAccessTokenStore: Send + Sync {
+    async fn add_token(&mut self, token: PersonalAccessToken) -> Result<(), PersonalAccessTokenStoreError>;
+    async fn get_token_by_prefix(&self, prefix: &str) -> Result<PersonalAccessToken, PersonalAccessTokenStoreError>;
+    // Newest first, revoked and expired tokens included
+    async fn list_tokens(&self, user_id: &UserId) -> Result<Vec<PersonalAccessToken>, PersonalAccessTokenStoreError>;
+    // Only an active token of the user can be revoked
+    async fn revoke_token(&mut self, user_id: &UserId, id: &PersonalAccessTokenId) -> Result<(), PersonalAccessTokenStoreError>;
+    // Revokes every active token of the user, a user without any is not an error
+    async fn revoke_user_tokens(&mut self, user_id: &UserId) -> Result<(), PersonalAccessTokenStoreError>;
+    async fn record_use(&mut self, id: &PersonalAccessTokenId) -> Result<(), PersonalAccessTokenStoreError>;
+}
+
+#[derive(Debug, PartialEq)]
+pub enum PersonalAccessTokenStoreError {
+    // Unknown, or no longer active when revoking
+    TokenNotFound,
+    UnexpectedError,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AuditEvent {
+    Signup,
+    Login,
+    PasswordChanged,
+    EmailChangeRequested,
+    EmailChanged,
+    TwoFactorAuthEnabled,
+    TwoFactorAuthDisabled,
+    DataExported,
+    AccountDeletionRequested,
+    AccountDeletionCancelled,
+    RoleAssigned,
+    RoleRevoked,
+    AccountLocked,
+    AccountUnlocked,
+    PasswordResetForced,
+    SessionsRevoked,
+    InvitationAccepted,
+    AccountApproved,
+    Impersonated,
+    PersonalAccessTokenCreated,
+    PersonalAccessTokenRevoked,
+}
+
+impl AsRef<str> for AuditEvent {
+    fn as_ref(&self) -> &str {
+        match self {
+            AuditEvent::Signup => "signup",
+            AuditEvent::Login => "login",
+            AuditEvent::PasswordChanged => "password_changed",
+            AuditEvent::EmailChangeRequested => "email_change_requested",
+            AuditEvent::EmailChanged => "email_changed",
+            AuditEvent::TwoFactorAuthEnabled => "two_factor_auth_enabled",
+            AuditEvent::TwoFactorAuthDisabled => "two_factor_auth_disabled",
+            AuditEvent::DataExported => "data_exported",
+            AuditEvent::AccountDeletionRequested => "account_deletion_requested",
+            AuditEvent::AccountDeletionCancelled => "account_deletion_cancelled",
+            AuditEvent::RoleAssigned => "role_assigned",
+            AuditEvent::RoleRevoked => "role_revoked",
+            AuditEvent::AccountLocked => "account_locked",
+            AuditEvent::AccountUnlocked => "account_unlocked",
+            AuditEvent::PasswordResetForced => "password_reset_forced",
+            AuditEvent::SessionsRevoked => "sessions_revoked",
+            AuditEvent::InvitationAccepted => "invitation_accepted",
+            AuditEvent::AccountApproved => "account_approved",
+            AuditEvent::Impersonated => "impersonated",
+            AuditEvent::PersonalAccessTokenCreated => "personal_access_token_created",
+            AuditEvent::PersonalAccessTokenRevoked => "personal_access_token_revoked",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    pub event: String,
+    #[serde(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    #[serde(rename = "actorId", default, skip_serializing_if = "Option::is_none")]
+    pub actor_id: Option<UserId>,
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct LoginAttemptId(String);
 
@@ -95,4 +307,28 @@ impl AsRef<str> for TwoFACode {
     fn as_ref(&self) -> &str {
         &self.0
     }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EmailChangeToken(String);
+
+impl EmailChangeToken {
+    pub fn parse(token: String) -> Result<Self, String> {
+        match uuid::Uuid::parse_str(&token) {
+            Ok(uuid_token) => Ok(EmailChangeToken(uuid_token.to_string())),
+            Err(_) => Err(format!("Invalid token: {}", token)),
+        }
+    }
+}
+
+impl Default for EmailChangeToken {
+    fn default() -> Self {
+        EmailChangeToken(uuid::Uuid::new_v4().to_string())
+    }
+}
+
+impl AsRef<str> for EmailChangeToken {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
 }
\ No newline at end of file
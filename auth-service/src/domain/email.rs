@@ -5,13 +5,24 @@ use validator::validate_email;
 pub struct Email(String);
 
 impl Email {
+    // Emails are kept normalized so that the same address always maps to the same user:
+    // the local part is compared case-insensitively and the domain is converted to lowercase punycode
     pub fn parse(s: String) -> Result<Self, String> {
-        if validate_email(&s) {
-            Ok(Email(s))
+        let normalized = Self::normalize(s.trim()).ok_or_else(|| format!("{} is not a valid email.", s))?;
+
+        if validate_email(&normalized) {
+            Ok(Email(normalized))
         } else {
             Err(format!("{} is not a valid email.", s))
         }
     }
+
+    fn normalize(s: &str) -> Option<String> {
+        let (local, domain) = s.rsplit_once('@')?;
+        let domain = idna::domain_to_ascii(domain).ok()?;
+
+        Some(format!("{}@{}", local.to_lowercase(), domain))
+    }
 }
 
 impl AsRef<str> for Email {
@@ -40,6 +51,19 @@ mod tests {
         assert!(email.is_err());
     }
 
+    #[test]
+    fn email_is_normalized() {
+        let email = Email::parse(" Alice@Example.COM ".to_string()).unwrap();
+        assert_eq!(email.as_ref(), "alice@example.com");
+        assert_eq!(email, Email::parse("alice@example.com".to_string()).unwrap());
+    }
+
+    #[test]
+    fn international_domain_is_converted_to_punycode() {
+        let email = Email::parse("user@Bücher.de".to_string()).unwrap();
+        assert_eq!(email.as_ref(), "user@xn--bcher-kva.de");
+    }
+
     #[test]
     fn invalid_email_is_empty() {
         let email_str = "".to_string();
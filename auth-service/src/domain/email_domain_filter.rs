@@ -0,0 +1,6 @@
+use super::Email;
+
+pub trait EmailDomainFilter: Send + Sync {
+    // Whether accounts may use the domain of the email, disposable mail providers are usually not
+    fn is_allowed(&self, email: &Email) -> bool;
+}
@@ -1,9 +1,106 @@
+use super::{data_store::{InvitationStoreError, PersonalAccessTokenStoreError, RoleStoreError, TenantStoreError, UserStoreError}, PasswordPolicyViolation};
+
 #[derive(Debug)]
 pub enum AuthAPIError {
     UserAlreadyExists,
     InvalidCredentials,
+    WeakPassword(Vec<PasswordPolicyViolation>),
     IncorrectCredentials,
+    AccountLocked,
+    // The password was right, but an admin has yet to approve the account
+    ApprovalPending,
     MissingToken,
     InvalidToken,
+    // The token is valid but lacks the permission the route requires
+    Forbidden,
+    // The token was issued to an admin impersonating the user, it cannot act on the account
+    ImpersonationNotAllowed,
+    // The request was authenticated with a personal access token, which cannot create others
+    PersonalAccessTokenNotAllowed,
+    // The new email of a change is the current one
+    EmailUnchanged,
+    UserNotFound,
+    RoleNotFound,
+    TenantNotFound,
+    InvalidTenantSettings,
+    // The page of a listing starts past the rows any store can hold
+    InvalidPage,
+    // The email is outside the domains the organization allows
+    EmailDomainNotAllowed,
+    // The email domain is blocked service-wide, like disposable mail providers
+    EmailDomainBlocked,
+    // The signup mode of the organization does not let anyone sign up, or only through an invitation
+    SignupDisabled,
+    InvitationRequired,
+    // The organization does not let its members turn 2FA off
+    TwoFactorAuthRequired,
+    InvitationNotFound,
+    // The invitation link is unknown, used, revoked or expired
+    InvalidInvitation,
+    PersonalAccessTokenNotFound,
+    // The name is empty or too long, or the expiry out of range
+    InvalidPersonalAccessToken,
+    // Token exchange errors, answered with the error codes of RFC 6749 and RFC 8693
+    InvalidTokenRequest,
+    UnsupportedGrantType,
+    InvalidClient,
+    // The subject token is invalid, expired or cannot be exchanged
+    InvalidGrant,
+    // The requested scopes are not all granted to the subject token
+    InvalidScope,
+    // The client may not call the requested audience
+    InvalidTarget,
+    ServiceUnavailable,
     UnexpectedError,
-}
\ No newline at end of file
+}
+
+// Unknown users and wrong passwords are reported alike, so the response does not reveal which one it was
+impl From<UserStoreError> for AuthAPIError {
+    fn from(error: UserStoreError) -> Self {
+        match error {
+            UserStoreError::UserAlreadyExists => AuthAPIError::UserAlreadyExists,
+            UserStoreError::UserNotFound | UserStoreError::InvalidCredentials => AuthAPIError::IncorrectCredentials,
+            UserStoreError::AccountLocked => AuthAPIError::AccountLocked,
+            UserStoreError::ApprovalPending => AuthAPIError::ApprovalPending,
+            UserStoreError::Overloaded => AuthAPIError::ServiceUnavailable,
+            UserStoreError::UnexpectedError => AuthAPIError::UnexpectedError,
+        }
+    }
+}
+
+impl From<RoleStoreError> for AuthAPIError {
+    fn from(error: RoleStoreError) -> Self {
+        match error {
+            RoleStoreError::RoleNotFound | RoleStoreError::RoleNotAssigned => AuthAPIError::RoleNotFound,
+            RoleStoreError::UserNotFound => AuthAPIError::UserNotFound,
+            RoleStoreError::UnexpectedError => AuthAPIError::UnexpectedError,
+        }
+    }
+}
+
+impl From<TenantStoreError> for AuthAPIError {
+    fn from(error: TenantStoreError) -> Self {
+        match error {
+            TenantStoreError::TenantNotFound => AuthAPIError::TenantNotFound,
+            TenantStoreError::TenantAlreadyExists | TenantStoreError::UnexpectedError => AuthAPIError::UnexpectedError,
+        }
+    }
+}
+impl From<PersonalAccessTokenStoreError> for AuthAPIError {
+    fn from(error: PersonalAccessTokenStoreError) -> Self {
+        match error {
+            PersonalAccessTokenStoreError::TokenNotFound => AuthAPIError::PersonalAccessTokenNotFound,
+            PersonalAccessTokenStoreError::UnexpectedError => AuthAPIError::UnexpectedError,
+        }
+    }
+}
+
+impl From<InvitationStoreError> for AuthAPIError {
+    fn from(error: InvitationStoreError) -> Self {
+        match error {
+            InvitationStoreError::InvitationNotFound => AuthAPIError::InvitationNotFound,
+            InvitationStoreError::RoleNotFound => AuthAPIError::RoleNotFound,
+            InvitationStoreError::UnexpectedError => AuthAPIError::UnexpectedError,
+        }
+    }
+}
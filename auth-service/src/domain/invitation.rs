@@ -0,0 +1,175 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use super::{Email, TenantId, UserId};
+
+// An admin's invitation for someone to join their organization, accepted once through the emailed link
+#[derive(Debug, Clone, PartialEq)]
+pub struct Invitation {
+    pub id: InvitationId,
+    pub tenant_id: TenantId,
+    pub email: Email,
+    // Assigned to the user when the invitation is accepted
+    pub roles: Vec<String>,
+    // SHA-256 of the emailed token, the token itself is not stored
+    pub token_hash: String,
+    // None once the inviting admin's account is deleted
+    pub invited_by: Option<UserId>,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub accepted_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+impl Invitation {
+    // The token is returned along with it, to be emailed to the invited address
+    pub fn new(tenant_id: TenantId, email: Email, mut roles: Vec<String>, invited_by: UserId, ttl: Duration) -> (Self, InvitationToken) {
+        roles.sort();
+        roles.dedup();
+        let token = InvitationToken::default();
+        let created_at = Utc::now();
+
+        let invitation = Invitation {
+            id: InvitationId::new(),
+            tenant_id,
+            email,
+            roles,
+            token_hash: token.hash(),
+            invited_by: Some(invited_by),
+            created_at,
+            expires_at: created_at + ttl,
+            accepted_at: None,
+            revoked_at: None,
+        };
+
+        (invitation, token)
+    }
+
+    pub fn status(&self, now: DateTime<Utc>) -> InvitationStatus {
+        if self.accepted_at.is_some() {
+            InvitationStatus::Accepted
+        } else if self.revoked_at.is_some() {
+            InvitationStatus::Revoked
+        } else if self.expires_at <= now {
+            InvitationStatus::Expired
+        } else {
+            InvitationStatus::Pending
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InvitationStatus {
+    Pending,
+    Accepted,
+    Revoked,
+    Expired,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct InvitationId(uuid::Uuid);
+
+impl InvitationId {
+    pub fn new() -> Self {
+        InvitationId(uuid::Uuid::new_v4())
+    }
+
+    pub fn parse(id: String) -> Result<Self, String> {
+        match uuid::Uuid::parse_str(&id) {
+            Ok(uuid_id) => Ok(InvitationId(uuid_id)),
+            Err(_) => Err(format!("Invalid invitation id: {}", id)),
+        }
+    }
+
+    pub fn as_uuid(&self) -> uuid::Uuid {
+        self.0
+    }
+}
+
+impl Default for InvitationId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl From<uuid::Uuid> for InvitationId {
+    fn from(id: uuid::Uuid) -> Self {
+        InvitationId(id)
+    }
+}
+
+impl std::fmt::Display for InvitationId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InvitationToken(String);
+
+impl InvitationToken {
+    pub fn parse(token: String) -> Result<Self, String> {
+        match uuid::Uuid::parse_str(&token) {
+            Ok(uuid_token) => Ok(InvitationToken(uuid_token.to_string())),
+            Err(_) => Err(format!("Invalid token: {}", token)),
+        }
+    }
+
+    // The token is random enough not to need a slow hash
+    pub fn hash(&self) -> String {
+        hex::encode(Sha256::digest(self.0.as_bytes()))
+    }
+}
+
+impl Default for InvitationToken {
+    fn default() -> Self {
+        InvitationToken(uuid::Uuid::new_v4().to_string())
+    }
+}
+
+impl AsRef<str> for InvitationToken {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn invitation() -> Invitation {
+        let email = Email::parse("alice@example.com".to_string()).unwrap();
+        let roles = vec!["viewer".to_string(), "admin".to_string(), "viewer".to_string()];
+        Invitation::new(TenantId::default(), email, roles, UserId::default(), Duration::hours(1)).0
+    }
+
+    #[test]
+    fn test_new_invitation_is_pending() {
+        let invitation = invitation();
+        assert_eq!(invitation.roles, vec!["admin", "viewer"]);
+        assert_eq!(invitation.status(Utc::now()), InvitationStatus::Pending);
+        assert_eq!(invitation.status(invitation.expires_at), InvitationStatus::Expired);
+    }
+
+    #[test]
+    fn test_only_the_token_hash_is_kept() {
+        let email = Email::parse("alice@example.com".to_string()).unwrap();
+        let (invitation, token) = Invitation::new(TenantId::default(), email, Vec::new(), UserId::default(), Duration::hours(1));
+        assert_ne!(invitation.token_hash, token.as_ref());
+        assert_eq!(invitation.token_hash, InvitationToken::parse(token.as_ref().to_uppercase()).unwrap().hash());
+    }
+
+    #[test]
+    fn test_status_of_used_invitation() {
+        let mut invitation = invitation();
+        invitation.revoked_at = Some(Utc::now());
+        assert_eq!(invitation.status(Utc::now()), InvitationStatus::Revoked);
+
+        // Accepting is final, even for an invitation that later expires
+        invitation.revoked_at = None;
+        invitation.accepted_at = Some(Utc::now());
+        assert_eq!(invitation.status(invitation.expires_at), InvitationStatus::Accepted);
+    }
+}
@@ -2,6 +2,15 @@ pub mod error;
 pub mod user;
 pub mod email;
 pub mod password;
+pub mod password_policy;
+pub mod password_hasher;
+pub mod role;
+pub mod tenant;
+pub mod invitation;
+pub mod personal_access_token;
+pub mod token_exchange_client;
+pub mod breached_password_checker;
+pub mod email_domain_filter;
 pub mod email_client;
 pub mod mock_email_client;
 pub mod data_store;
@@ -10,5 +19,14 @@ pub use error::*;
 pub use user::*;
 pub use email::*;
 pub use password::*;
+pub use password_policy::*;
+pub use password_hasher::*;
+pub use role::*;
+pub use tenant::*;
+pub use invitation::*;
+pub use personal_access_token::*;
+pub use token_exchange_client::*;
+pub use breached_password_checker::*;
+pub use email_domain_filter::*;
 pub use email_client::*;
 pub use mock_email_client::*;
\ No newline at end of file
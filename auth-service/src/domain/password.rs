@@ -3,9 +3,10 @@ use serde::Serialize;
 #[derive(Debug, Serialize, Clone, PartialEq, Eq, Hash)]
 pub struct Password(String);
 
+// Only empty input is rejected, the length rules of a new password belong to the password policy
 impl Password {
     pub fn parse(s: String) -> Result<Self, String> {
-        if s.len() >= 8 {
+        if !s.is_empty() {
             Ok(Password(s))
         } else{
             Err("Failed to parse string to a Password type".to_string())
@@ -32,9 +33,15 @@ mod test {
     }
 
     #[test]
-    fn invalid_password() {
+    fn short_password() {
         let str_password = "1234567".to_string();
         let password = Password::parse(str_password);
+        assert!(password.is_ok());
+    }
+
+    #[test]
+    fn invalid_password() {
+        let password = Password::parse(String::new());
         assert!(password.is_err());
     }
 }
\ No newline at end of file
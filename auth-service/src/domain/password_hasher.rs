@@ -0,0 +1,63 @@
+use super::{data_store::UserStoreError, Password};
+
+#[derive(Debug, PartialEq)]
+pub enum PasswordHasherError {
+    IncorrectPassword,
+    // Too many passwords are being hashed, the request can be retried later
+    Overloaded,
+    UnexpectedError,
+}
+
+// The longest pepper id, which hashes carry to find their pepper
+pub const MAX_PEPPER_ID_LENGTH: usize = 8;
+
+// Secret mixed into every hash and kept out of the database. The id is stored in the hash,
+// which is how old hashes find their pepper after a rotation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PasswordPepper {
+    id: String,
+    secret: String,
+}
+
+impl PasswordPepper {
+    pub fn new(id: String, secret: String) -> Result<Self, String> {
+        if id.is_empty() || id.len() > MAX_PEPPER_ID_LENGTH {
+            return Err(format!("Pepper ids must be 1 to {} bytes long", MAX_PEPPER_ID_LENGTH));
+        }
+        if secret.is_empty() {
+            return Err(format!("Pepper {} has an empty secret", id));
+        }
+
+        Ok(Self { id, secret })
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn secret(&self) -> &str {
+        &self.secret
+    }
+}
+
+// Hashing and verification shared by every user store, so they all store the same hashes
+#[async_trait::async_trait]
+pub trait PasswordHasher: Send + Sync {
+    async fn hash_password(&self, password: &Password) -> Result<String, PasswordHasherError>;
+    async fn verify_password(&self, password_hash: &str, password: &Password) -> Result<(), PasswordHasherError>;
+    // Takes as long as verifying a real hash, so unknown users cannot be told apart by timing.
+    // Only fails when the hasher is overloaded.
+    async fn verify_dummy_password(&self, password: &Password) -> Result<(), PasswordHasherError>;
+    // Whether the hash was made with other settings than the current ones and should be replaced
+    fn needs_rehash(&self, password_hash: &str) -> bool;
+}
+
+impl From<PasswordHasherError> for UserStoreError {
+    fn from(error: PasswordHasherError) -> Self {
+        match error {
+            PasswordHasherError::IncorrectPassword => UserStoreError::InvalidCredentials,
+            PasswordHasherError::Overloaded => UserStoreError::Overloaded,
+            PasswordHasherError::UnexpectedError => UserStoreError::UnexpectedError,
+        }
+    }
+}
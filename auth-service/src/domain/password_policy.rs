@@ -0,0 +1,82 @@
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use super::{Email, Password};
+
+pub trait PasswordPolicy: Send + Sync {
+    // Check a new password, returning every rule it breaks
+    fn check(&self, password: &Password, email: &Email) -> Result<(), Vec<PasswordPolicyViolation>>;
+    // How many of the last passwords, the current one included, cannot be reused
+    fn history_length(&self) -> usize;
+    fn is_expired(&self, password_changed_at: DateTime<Utc>) -> bool;
+    // The policy of an organization, based on this one
+    fn with_overrides(&self, overrides: &PasswordPolicyOverrides) -> Arc<dyn PasswordPolicy>;
+}
+
+// Organization settings tightening the service wide ones, those left out are kept
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PasswordPolicyOverrides {
+    #[serde(rename = "minLength", skip_serializing_if = "Option::is_none")]
+    pub min_length: Option<usize>,
+    #[serde(rename = "minScore", skip_serializing_if = "Option::is_none")]
+    pub min_score: Option<u8>,
+    #[serde(rename = "historyLength", skip_serializing_if = "Option::is_none")]
+    pub history_length: Option<usize>,
+    #[serde(rename = "maxAgeDays", skip_serializing_if = "Option::is_none")]
+    pub max_age_days: Option<i64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PasswordPolicyViolation {
+    TooShort { min_length: usize },
+    TooLong { max_length: usize },
+    TooWeak { score: u8, min_score: u8 },
+    ContainsEmail,
+    Breached,
+    RecentlyUsed { history_length: usize },
+}
+
+impl PasswordPolicyViolation {
+    pub fn message(&self) -> String {
+        match self {
+            PasswordPolicyViolation::TooShort { min_length } => format!("Password must be at least {} characters long", min_length),
+            PasswordPolicyViolation::TooLong { max_length } => format!("Password must be at most {} characters long", max_length),
+            PasswordPolicyViolation::TooWeak { score, min_score } => format!("Password is too easy to guess (strength {} of 4, at least {} required)", score, min_score),
+            PasswordPolicyViolation::ContainsEmail => "Password must not contain your email address".to_string(),
+            PasswordPolicyViolation::Breached => "Password has appeared in a data breach, please choose another one".to_string(),
+            PasswordPolicyViolation::RecentlyUsed { history_length } => format!("Password must differ from your last {} passwords", history_length),
+        }
+    }
+}
+
+impl AsRef<str> for PasswordPolicyViolation {
+    fn as_ref(&self) -> &str {
+        match self {
+            PasswordPolicyViolation::TooShort { .. } => "too_short",
+            PasswordPolicyViolation::TooLong { .. } => "too_long",
+            PasswordPolicyViolation::TooWeak { .. } => "too_weak",
+            PasswordPolicyViolation::ContainsEmail => "contains_email",
+            PasswordPolicyViolation::Breached => "breached",
+            PasswordPolicyViolation::RecentlyUsed { .. } => "recently_used",
+        }
+    }
+}
+
+// Shape of a violation in error responses, so clients can show every failed rule
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct PasswordRuleDetail {
+    pub rule: String,
+    pub message: String,
+}
+
+impl From<&PasswordPolicyViolation> for PasswordRuleDetail {
+    fn from(violation: &PasswordPolicyViolation) -> Self {
+        PasswordRuleDetail {
+            rule: violation.as_ref().to_string(),
+            message: violation.message(),
+        }
+    }
+}
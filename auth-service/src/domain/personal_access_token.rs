@@ -0,0 +1,173 @@
+use chrono::{DateTime, Duration, Utc};
+use rand::{distr::Alphanumeric, Rng};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use super::UserId;
+
+pub const PERSONAL_ACCESS_TOKEN_PREFIX: &str = "pat_";
+const LOOKUP_PREFIX_LENGTH: usize = 12;
+const SECRET_LENGTH: usize = 40;
+
+// A named, scoped and expiring token a user creates to call the APIs from scripts, sent as `Authorization: Bearer`.
+// Tokens look like `pat_<lookup prefix>_<secret>`. Only their hash is stored, the prefix is kept to find them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PersonalAccessToken {
+    pub id: PersonalAccessTokenId,
+    pub user_id: UserId,
+    pub name: String,
+    // Permissions of the user the token grants, as long as the user still has them
+    pub scopes: Vec<String>,
+    pub prefix: String,
+    // SHA-256 of the whole token, which is random enough not to need a slow hash
+    pub token_hash: String,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+impl PersonalAccessToken {
+    // The token itself is returned along with it, to be shown once and never stored
+    pub fn new(user_id: UserId, name: String, mut scopes: Vec<String>, ttl: Duration) -> (Self, String) {
+        scopes.sort();
+        scopes.dedup();
+
+        let prefix = random_string(LOOKUP_PREFIX_LENGTH);
+        let token = format!("{}{}_{}", PERSONAL_ACCESS_TOKEN_PREFIX, prefix, random_string(SECRET_LENGTH));
+        let created_at = Utc::now();
+
+        let personal_access_token = PersonalAccessToken {
+            id: PersonalAccessTokenId::new(),
+            user_id,
+            name,
+            scopes,
+            prefix,
+            token_hash: hash_token(&token),
+            created_at,
+            expires_at: created_at + ttl,
+            last_used_at: None,
+            revoked_at: None,
+        };
+
+        (personal_access_token, token)
+    }
+
+    // The lookup prefix of what looks like a personal access token
+    pub fn parse_prefix(token: &str) -> Option<&str> {
+        let (prefix, _) = token.strip_prefix(PERSONAL_ACCESS_TOKEN_PREFIX)?.split_once('_')?;
+        (prefix.len() == LOOKUP_PREFIX_LENGTH).then_some(prefix)
+    }
+
+    pub fn verify(&self, token: &str) -> bool {
+        hash_token(token) == self.token_hash
+    }
+
+    pub fn status(&self, now: DateTime<Utc>) -> PersonalAccessTokenStatus {
+        if self.revoked_at.is_some() {
+            PersonalAccessTokenStatus::Revoked
+        } else if self.expires_at <= now {
+            PersonalAccessTokenStatus::Expired
+        } else {
+            PersonalAccessTokenStatus::Active
+        }
+    }
+}
+
+fn random_string(length: usize) -> String {
+    rand::rng().sample_iter(&Alphanumeric).take(length).map(char::from).collect()
+}
+
+fn hash_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PersonalAccessTokenStatus {
+    Active,
+    Revoked,
+    Expired,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PersonalAccessTokenId(uuid::Uuid);
+
+impl PersonalAccessTokenId {
+    pub fn new() -> Self {
+        PersonalAccessTokenId(uuid::Uuid::new_v4())
+    }
+
+    pub fn parse(id: String) -> Result<Self, String> {
+        match uuid::Uuid::parse_str(&id) {
+            Ok(uuid_id) => Ok(PersonalAccessTokenId(uuid_id)),
+            Err(_) => Err(format!("Invalid personal access token id: {}", id)),
+        }
+    }
+
+    pub fn as_uuid(&self) -> uuid::Uuid {
+        self.0
+    }
+}
+
+impl Default for PersonalAccessTokenId {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl From<uuid::Uuid> for PersonalAccessTokenId {
+    fn from(id: uuid::Uuid) -> Self {
+        PersonalAccessTokenId(id)
+    }
+}
+
+impl std::fmt::Display for PersonalAccessTokenId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn personal_access_token() -> (PersonalAccessToken, String) {
+        let scopes = vec!["users:manage".to_string(), "roles:manage".to_string(), "users:manage".to_string()];
+        PersonalAccessToken::new(UserId::default(), "deploy script".to_string(), scopes, Duration::days(30))
+    }
+
+    #[test]
+    fn test_new_token_is_only_stored_hashed() {
+        let (personal_access_token, token) = personal_access_token();
+        assert_eq!(personal_access_token.scopes, vec!["roles:manage", "users:manage"]);
+        assert_eq!(personal_access_token.status(Utc::now()), PersonalAccessTokenStatus::Active);
+        assert_eq!(personal_access_token.status(personal_access_token.expires_at), PersonalAccessTokenStatus::Expired);
+
+        assert!(token.starts_with(PERSONAL_ACCESS_TOKEN_PREFIX));
+        assert_eq!(PersonalAccessToken::parse_prefix(&token), Some(personal_access_token.prefix.as_str()));
+        assert!(!personal_access_token.token_hash.contains(&token));
+        assert!(personal_access_token.verify(&token));
+        assert!(!personal_access_token.verify(&format!("{}x", token)));
+
+        // Each token is different
+        let (other, other_token) = self::personal_access_token();
+        assert_ne!(other.prefix, personal_access_token.prefix);
+        assert!(!personal_access_token.verify(&other_token));
+    }
+
+    #[test]
+    fn test_parse_prefix() {
+        assert_eq!(PersonalAccessToken::parse_prefix("pat_abcdefghijkl_secret"), Some("abcdefghijkl"));
+        assert_eq!(PersonalAccessToken::parse_prefix("pat_abc_secret"), None);
+        assert_eq!(PersonalAccessToken::parse_prefix("pat_abcdefghijkl"), None);
+        assert_eq!(PersonalAccessToken::parse_prefix("eyJhbGciOiJIUzI1NiJ9.e30.sig"), None);
+    }
+
+    #[test]
+    fn test_revoked_token() {
+        let (mut personal_access_token, _) = personal_access_token();
+        personal_access_token.revoked_at = Some(Utc::now());
+        assert_eq!(personal_access_token.status(Utc::now()), PersonalAccessTokenStatus::Revoked);
+    }
+}
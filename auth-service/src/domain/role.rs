@@ -0,0 +1,23 @@
+use serde::{Deserialize, Serialize};
+
+// Created by the migrations, the first admin is assigned with the `assign_role` command
+pub const ADMIN_ROLE: &str = "admin";
+
+// Permissions are granted through roles and carried in auth tokens
+pub const MANAGE_ROLES_PERMISSION: &str = "roles:manage";
+pub const MANAGE_USERS_PERMISSION: &str = "users:manage";
+pub const MANAGE_TENANT_PERMISSION: &str = "tenant:manage";
+pub const IMPERSONATE_USERS_PERMISSION: &str = "users:impersonate";
+pub const READ_METRICS_PERMISSION: &str = "metrics:read";
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct UserRoles {
+    pub roles: Vec<String>,
+    pub permissions: Vec<String>,
+}
+
+impl UserRoles {
+    pub fn has_permission(&self, permission: &str) -> bool {
+        self.permissions.iter().any(|granted| granted == permission)
+    }
+}
@@ -0,0 +1,241 @@
+use serde::{Deserialize, Serialize};
+
+use super::{Email, PasswordPolicyOverrides};
+
+// Created by the migrations, users that signed up before organizations existed belong to it
+pub const DEFAULT_TENANT_SLUG: &str = "default";
+
+// An organization, with its own members and settings
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tenant {
+    pub id: TenantId,
+    pub slug: TenantSlug,
+    pub name: String,
+    // Host of the login page of the organization, without the port
+    pub host: Option<String>,
+    pub settings: TenantSettings,
+}
+
+impl Tenant {
+    pub fn new(slug: TenantSlug, name: String, host: Option<String>) -> Self {
+        Tenant { id: TenantId::new(), slug, name, host, settings: TenantSettings::default() }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TenantSettings {
+    // Members have to confirm every login with a 2FA code, whatever they chose themselves
+    #[serde(rename = "requires2FA")]
+    pub requires_2fa: bool,
+    // Lowercase punycode domains members may use, empty allows any
+    #[serde(rename = "allowedEmailDomains")]
+    pub allowed_email_domains: Vec<String>,
+    #[serde(rename = "passwordPolicy")]
+    pub password_policy: PasswordPolicyOverrides,
+    #[serde(rename = "signupMode")]
+    pub signup_mode: SignupMode,
+    // Domains that may sign up on their own in the `domain_restricted` mode, other addresses need an invitation
+    #[serde(rename = "signupEmailDomains")]
+    pub signup_email_domains: Vec<String>,
+}
+
+impl TenantSettings {
+    // Domains are normalized the way emails are, so they can be compared with the domain of an `Email`
+    pub fn parse(mut self) -> Result<Self, String> {
+        self.allowed_email_domains = parse_domains(&self.allowed_email_domains)?;
+        self.signup_email_domains = parse_domains(&self.signup_email_domains)?;
+
+        if self.signup_mode == SignupMode::DomainRestricted && self.signup_email_domains.is_empty() {
+            return Err("The domain_restricted signup mode needs signup email domains.".to_string());
+        }
+
+        Ok(self)
+    }
+
+    pub fn allows_email(&self, email: &Email) -> bool {
+        self.allowed_email_domains.is_empty() || self.allowed_email_domains.iter().any(|allowed| allowed == email_domain(email))
+    }
+
+    pub fn allows_signup_email(&self, email: &Email) -> bool {
+        self.signup_email_domains.iter().any(|allowed| allowed == email_domain(email))
+    }
+}
+
+fn parse_domains(domains: &[String]) -> Result<Vec<String>, String> {
+    let mut domains = domains
+        .iter()
+        .map(|domain| match idna::domain_to_ascii(domain.trim()) {
+            Ok(normalized) if !normalized.is_empty() => Ok(normalized),
+            _ => Err(format!("{} is not a valid domain.", domain)),
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    domains.sort();
+    domains.dedup();
+
+    Ok(domains)
+}
+
+fn email_domain(email: &Email) -> &str {
+    email.as_ref().rsplit_once('@').map(|(_, domain)| domain).unwrap_or_default()
+}
+
+// Who may create an account through `/signup`. Accepting an invitation works in every mode.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SignupMode {
+    #[default]
+    Open,
+    Disabled,
+    InviteOnly,
+    // Only addresses of the signup email domains
+    DomainRestricted,
+    // Accounts are created but cannot log in until an admin approves them
+    PendingApproval,
+}
+
+impl SignupMode {
+    pub fn parse(mode: &str) -> Result<Self, String> {
+        match mode {
+            "open" => Ok(SignupMode::Open),
+            "disabled" => Ok(SignupMode::Disabled),
+            "invite_only" => Ok(SignupMode::InviteOnly),
+            "domain_restricted" => Ok(SignupMode::DomainRestricted),
+            "pending_approval" => Ok(SignupMode::PendingApproval),
+            _ => Err(format!("{} is not a valid signup mode.", mode)),
+        }
+    }
+}
+
+impl AsRef<str> for SignupMode {
+    fn as_ref(&self) -> &str {
+        match self {
+            SignupMode::Open => "open",
+            SignupMode::Disabled => "disabled",
+            SignupMode::InviteOnly => "invite_only",
+            SignupMode::DomainRestricted => "domain_restricted",
+            SignupMode::PendingApproval => "pending_approval",
+        }
+    }
+}
+
+// The default organization has the nil id, so it is known before the store is queried
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct TenantId(uuid::Uuid);
+
+impl TenantId {
+    pub fn new() -> Self {
+        TenantId(uuid::Uuid::new_v4())
+    }
+
+    pub fn parse(id: String) -> Result<Self, String> {
+        match uuid::Uuid::parse_str(&id) {
+            Ok(uuid_id) => Ok(TenantId(uuid_id)),
+            Err(_) => Err(format!("Invalid tenant id: {}", id)),
+        }
+    }
+
+    pub fn as_uuid(&self) -> uuid::Uuid {
+        self.0
+    }
+}
+
+impl From<uuid::Uuid> for TenantId {
+    fn from(id: uuid::Uuid) -> Self {
+        TenantId(id)
+    }
+}
+
+impl std::fmt::Display for TenantId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+// Short name users type on the login page, usable as a subdomain
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize)]
+pub struct TenantSlug(String);
+
+impl TenantSlug {
+    pub fn parse(s: String) -> Result<Self, String> {
+        let slug = s.trim().to_lowercase();
+        let valid_chars = slug.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-');
+
+        if (1..=63).contains(&slug.len()) && valid_chars && !slug.starts_with('-') && !slug.ends_with('-') {
+            Ok(TenantSlug(slug))
+        } else {
+            Err(format!("{} is not a valid organization slug.", s))
+        }
+    }
+}
+
+impl AsRef<str> for TenantSlug {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn valid_tenant_slug() {
+        let slug = TenantSlug::parse(" Acme-42 ".to_string()).unwrap();
+        assert_eq!(slug.as_ref(), "acme-42");
+    }
+
+    #[test]
+    fn invalid_tenant_slug() {
+        for slug in ["", "-acme", "acme-", "acme corp", "acme.com", &"a".repeat(64)] {
+            assert!(TenantSlug::parse(slug.to_string()).is_err(), "{}", slug);
+        }
+    }
+
+    #[test]
+    fn allowed_email_domains() {
+        let settings = TenantSettings {
+            allowed_email_domains: vec![" Example.COM".to_string(), "bücher.example".to_string()],
+            ..TenantSettings::default()
+        }.parse().unwrap();
+        assert_eq!(settings.allowed_email_domains, vec!["example.com", "xn--bcher-kva.example"]);
+
+        let email = |s: &str| Email::parse(s.to_string()).unwrap();
+        assert!(settings.allows_email(&email("alice@example.com")));
+        assert!(settings.allows_email(&email("bob@bücher.example")));
+        assert!(!settings.allows_email(&email("carol@sub.example.com")));
+        assert!(TenantSettings::default().allows_email(&email("carol@sub.example.com")));
+    }
+
+    #[test]
+    fn signup_email_domains() {
+        let settings = TenantSettings {
+            signup_mode: SignupMode::DomainRestricted,
+            signup_email_domains: vec!["Example.com".to_string()],
+            ..TenantSettings::default()
+        }.parse().unwrap();
+
+        let email = |s: &str| Email::parse(s.to_string()).unwrap();
+        assert!(settings.allows_signup_email(&email("alice@example.com")));
+        assert!(!settings.allows_signup_email(&email("bob@example.org")));
+        assert!(settings.allows_email(&email("bob@example.org")));
+
+        let settings = TenantSettings { signup_mode: SignupMode::DomainRestricted, ..TenantSettings::default() };
+        assert!(settings.parse().is_err());
+    }
+
+    #[test]
+    fn signup_mode_round_trip() {
+        for mode in [SignupMode::Open, SignupMode::Disabled, SignupMode::InviteOnly, SignupMode::DomainRestricted, SignupMode::PendingApproval] {
+            assert_eq!(mode, SignupMode::parse(mode.as_ref()).unwrap());
+            assert_eq!(serde_json::to_value(mode).unwrap(), mode.as_ref());
+        }
+        assert!(SignupMode::parse("closed").is_err());
+    }
+
+    #[test]
+    fn invalid_allowed_email_domain() {
+        let settings = TenantSettings { allowed_email_domains: vec!["  ".to_string()], ..TenantSettings::default() };
+        assert!(settings.parse().is_err());
+    }
+}
@@ -0,0 +1,62 @@
+use sha2::{Digest, Sha256};
+
+// A service allowed to exchange the tokens of users for tokens to call the services of `audiences` on their behalf
+#[derive(Debug, Clone, PartialEq)]
+pub struct TokenExchangeClient {
+    id: String,
+    secret: String,
+    audiences: Vec<String>,
+}
+
+impl TokenExchangeClient {
+    pub fn new(id: String, secret: String, audiences: Vec<String>) -> Result<Self, String> {
+        if id.trim().is_empty() || id.contains(':') {
+            return Err("Client ids must not be empty nor contain ':'.".to_string());
+        }
+        if secret.is_empty() {
+            return Err(format!("The secret of client {} must not be empty.", id));
+        }
+        if audiences.is_empty() || audiences.iter().any(|audience| audience.trim().is_empty()) {
+            return Err(format!("Client {} needs at least one audience.", id));
+        }
+
+        Ok(Self { id, secret, audiences })
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    // Digests are compared so the time taken says nothing about how much of the secret matched
+    pub fn verify_secret(&self, secret: &str) -> bool {
+        Sha256::digest(secret.as_bytes()) == Sha256::digest(self.secret.as_bytes())
+    }
+
+    pub fn may_call(&self, audience: &str) -> bool {
+        self.audiences.iter().any(|allowed| allowed == audience)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_client() {
+        let client = TokenExchangeClient::new("orders".to_string(), "s3cret".to_string(), vec!["billing".to_string()]).unwrap();
+        assert_eq!(client.id(), "orders");
+        assert!(client.verify_secret("s3cret"));
+        assert!(!client.verify_secret("s3cret "));
+        assert!(!client.verify_secret(""));
+        assert!(client.may_call("billing"));
+        assert!(!client.may_call("shipping"));
+    }
+
+    #[test]
+    fn test_invalid_client() {
+        assert!(TokenExchangeClient::new("".to_string(), "s3cret".to_string(), vec!["billing".to_string()]).is_err());
+        assert!(TokenExchangeClient::new("or:ders".to_string(), "s3cret".to_string(), vec!["billing".to_string()]).is_err());
+        assert!(TokenExchangeClient::new("orders".to_string(), "".to_string(), vec!["billing".to_string()]).is_err());
+        assert!(TokenExchangeClient::new("orders".to_string(), "s3cret".to_string(), vec![]).is_err());
+    }
+}
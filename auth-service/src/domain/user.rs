@@ -1,17 +1,34 @@
-use serde::Serialize;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
 
-use super::{Email, Password};
+use super::{Email, Password, TenantId};
 
 #[derive(Debug, Serialize, Clone)]
 pub struct User {
+    id: UserId,
     email: Email,
     password: Password,
-    requires_2fa: bool
+    requires_2fa: bool,
+    tenant_id: TenantId,
 }
 
+// Users belong to the default organization unless `with_tenant` says otherwise
 impl User {
     pub fn new (email: Email, password: Password, requires_2fa: bool) -> Self {
-        User { email, password, requires_2fa }
+        User { id: UserId::default(), email, password, requires_2fa, tenant_id: TenantId::default() }
+    }
+
+    pub fn with_id(id: UserId, email: Email, password: Password, requires_2fa: bool) -> Self {
+        User { id, email, password, requires_2fa, tenant_id: TenantId::default() }
+    }
+
+    pub fn with_tenant(mut self, tenant_id: TenantId) -> Self {
+        self.tenant_id = tenant_id;
+        self
+    }
+
+    pub fn get_id(&self) -> UserId {
+        self.id
     }
 
     pub fn get_email(&self) -> Email {
@@ -25,4 +42,75 @@ impl User {
     pub fn use_requires_2fa(&self) -> bool {
         self.requires_2fa
     }
-}
\ No newline at end of file
+
+    pub fn get_tenant_id(&self) -> TenantId {
+        self.tenant_id
+    }
+}
+
+// A user together with the account state operators look after
+#[derive(Debug, Clone)]
+pub struct UserAccount {
+    pub user: User,
+    // Locked users cannot log in until an admin unlocks them
+    pub locked: bool,
+    // Set by an admin, the next login only allows changing the password
+    pub password_reset_required: bool,
+    // Signed up while the organization required approval, cannot log in until an admin approves it
+    pub approval_pending: bool,
+    pub password_changed_at: DateTime<Utc>,
+    pub deletion_scheduled_at: Option<DateTime<Utc>>,
+}
+
+// Stable identifier of a user, used as the JWT `sub` instead of the email
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct UserId(uuid::Uuid);
+
+impl UserId {
+    pub fn parse(id: String) -> Result<Self, String> {
+        match uuid::Uuid::parse_str(&id) {
+            Ok(uuid_id) => Ok(UserId(uuid_id)),
+            Err(_) => Err(format!("Invalid user id: {}", id)),
+        }
+    }
+
+    pub fn as_uuid(&self) -> uuid::Uuid {
+        self.0
+    }
+}
+
+impl Default for UserId {
+    fn default() -> Self {
+        UserId(uuid::Uuid::new_v4())
+    }
+}
+
+impl From<uuid::Uuid> for UserId {
+    fn from(id: uuid::Uuid) -> Self {
+        UserId(id)
+    }
+}
+
+impl std::fmt::Display for UserId {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn valid_user_id() {
+        let id = UserId::default();
+        let parsed = UserId::parse(id.to_string());
+        assert_eq!(Ok(id), parsed);
+    }
+
+    #[test]
+    fn invalid_user_id() {
+        let parsed = UserId::parse("user@example.com".to_string());
+        assert!(parsed.is_err());
+    }
+}
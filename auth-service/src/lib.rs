@@ -1,18 +1,20 @@
 use std::error::Error;
 use app_state::AppState;
 use axum::{
-    http::{Method, StatusCode},
+    http::{header, Method, StatusCode},
     response::{IntoResponse, Response},
-    routing::post,
+    middleware,
+    routing::{delete, get, post, put},
     serve::Serve,
     Json, Router,
 };
 use redis::RedisResult;
-use domain::AuthAPIError;
+use domain::{AuthAPIError, PasswordRuleDetail};
 use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
 use sqlx::postgres::PgPoolOptions;
 use tower_http::{cors::CorsLayer, services::ServeDir};
+use utils::auth::authenticate_bearer_token;
 
 pub mod routes;
 pub mod domain;
@@ -34,7 +36,7 @@ impl Application {
         ];
 
         let cors = CorsLayer::new()
-            .allow_methods([Method::GET, Method::POST])
+            .allow_methods([Method::GET, Method::POST, Method::PUT, Method::DELETE])
             .allow_credentials(true)
             .allow_origin(allowed_origins);
 
@@ -45,6 +47,42 @@ impl Application {
             .route("/verify-2fa", post(routes::verify_2fa))
             .route("/logout", post(routes::logout))
             .route("/verify-token", post(routes::verify_token))
+            .route("/token", post(routes::exchange_token))
+            .route("/change-password", post(routes::change_password))
+            .route("/change-email", post(routes::change_email))
+            .route("/confirm-email-change", get(routes::confirm_email_change))
+            .route("/cancel-email-change", get(routes::cancel_email_change))
+            .route("/enable-2fa", post(routes::enable_2fa))
+            .route("/disable-2fa", post(routes::disable_2fa))
+            .route("/account/export", get(routes::export_account))
+            .route("/account/delete", post(routes::delete_account))
+            .route("/account/cancel-deletion", post(routes::cancel_account_deletion))
+            .route("/account/tokens", get(routes::list_personal_access_tokens).post(routes::create_personal_access_token))
+            .route("/account/tokens/:token_id", delete(routes::revoke_personal_access_token))
+            .route("/metrics", get(routes::metrics))
+            .route("/admin/users", get(routes::list_users))
+            .route("/admin/users/:user_id", get(routes::get_user))
+            .route("/admin/users/:user_id/force-password-reset", post(routes::force_password_reset))
+            .route("/admin/users/:user_id/2fa", post(routes::set_user_2fa))
+            .route("/admin/users/:user_id/lock", post(routes::lock_user))
+            .route("/admin/users/:user_id/unlock", post(routes::unlock_user))
+            .route("/admin/users/:user_id/revoke-sessions", post(routes::revoke_sessions))
+            .route("/admin/users/:user_id/approve", post(routes::approve_user))
+            .route("/admin/users/:user_id/reject", post(routes::reject_user))
+            .route("/admin/users/:user_id/impersonate", post(routes::impersonate_user))
+            .route("/admin/users/:user_id/roles", get(routes::get_user_roles).post(routes::assign_role))
+            .route("/admin/users/:user_id/roles/:role", delete(routes::revoke_role))
+            .route("/accept-invite", post(routes::accept_invitation))
+            .route("/admin/invitations", get(routes::list_invitations).post(routes::create_invitation))
+            .route("/admin/invitations/:invitation_id", delete(routes::revoke_invitation))
+            .route("/admin/tenant", get(routes::get_tenant))
+            .route("/admin/tenant/settings", put(routes::update_tenant_settings))
+            .route("/admin/console", get(routes::console_users))
+            .route("/admin/console/users/:user_id", get(routes::console_user))
+            .route("/admin/console/users/:user_id/lock", post(routes::console_lock_user))
+            .route("/admin/console/users/:user_id/unlock", post(routes::console_unlock_user))
+            .route("/admin/console/users/:user_id/force-password-reset", post(routes::console_force_password_reset))
+            .layer(middleware::from_fn_with_state(app_state.clone(), authenticate_bearer_token))
             .with_state(app_state)
             .layer(cors);
 
@@ -65,23 +103,83 @@ impl Application {
 #[derive(Serialize, Deserialize)]
 pub struct ErrorResponse {
     pub error: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<Vec<PasswordRuleDetail>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error_description: Option<String>,
 }
 
 impl IntoResponse for AuthAPIError {
     fn into_response(self) -> Response {
+        let details = match &self {
+            AuthAPIError::WeakPassword(violations) => Some(violations.iter().map(PasswordRuleDetail::from).collect()),
+            _ => None,
+        };
+
+        // The token endpoint answers with the error codes of RFC 6749, describing them with the usual message
+        let oauth_error = match &self {
+            AuthAPIError::InvalidTokenRequest => Some("invalid_request"),
+            AuthAPIError::UnsupportedGrantType => Some("unsupported_grant_type"),
+            AuthAPIError::InvalidClient => Some("invalid_client"),
+            AuthAPIError::InvalidGrant => Some("invalid_grant"),
+            AuthAPIError::InvalidScope => Some("invalid_scope"),
+            AuthAPIError::InvalidTarget => Some("invalid_target"),
+            _ => None,
+        };
+        let invalid_client = matches!(self, AuthAPIError::InvalidClient);
+
         let (status, error_message) = match self {
             AuthAPIError::UserAlreadyExists => (StatusCode::CONFLICT, "User already exists"),
             AuthAPIError::InvalidCredentials => (StatusCode::BAD_REQUEST, "Invalid credentials"),
+            AuthAPIError::WeakPassword(_) => (StatusCode::BAD_REQUEST, "Password does not meet the requirements"),
             AuthAPIError::IncorrectCredentials => (StatusCode::UNAUTHORIZED, "Incorrect credentials"),
+            AuthAPIError::AccountLocked => (StatusCode::FORBIDDEN, "Account is locked"),
+            AuthAPIError::ApprovalPending => (StatusCode::FORBIDDEN, "Account is pending approval"),
             AuthAPIError::MissingToken => (StatusCode::BAD_REQUEST, "Missing token"),
             AuthAPIError::InvalidToken => (StatusCode::UNAUTHORIZED, "Invalid token"),
+            AuthAPIError::Forbidden => (StatusCode::FORBIDDEN, "Forbidden"),
+            AuthAPIError::ImpersonationNotAllowed => (StatusCode::FORBIDDEN, "Not allowed while impersonating a user"),
+            AuthAPIError::PersonalAccessTokenNotAllowed => (StatusCode::FORBIDDEN, "Not allowed with a personal access token"),
+            AuthAPIError::EmailUnchanged => (StatusCode::BAD_REQUEST, "New email is the current one"),
+            AuthAPIError::UserNotFound => (StatusCode::NOT_FOUND, "User not found"),
+            AuthAPIError::RoleNotFound => (StatusCode::NOT_FOUND, "Role not found"),
+            AuthAPIError::TenantNotFound => (StatusCode::NOT_FOUND, "Organization not found"),
+            AuthAPIError::InvalidTenantSettings => (StatusCode::BAD_REQUEST, "Invalid organization settings"),
+            AuthAPIError::InvalidPage => (StatusCode::BAD_REQUEST, "Invalid page"),
+            AuthAPIError::EmailDomainNotAllowed => (StatusCode::BAD_REQUEST, "Email domain is not allowed by the organization"),
+            AuthAPIError::EmailDomainBlocked => (StatusCode::BAD_REQUEST, "Email domain is not accepted"),
+            AuthAPIError::SignupDisabled => (StatusCode::FORBIDDEN, "Signup is disabled for the organization"),
+            AuthAPIError::InvitationRequired => (StatusCode::FORBIDDEN, "Signup to the organization takes an invitation"),
+            AuthAPIError::TwoFactorAuthRequired => (StatusCode::FORBIDDEN, "Two-factor authentication is required by the organization"),
+            AuthAPIError::InvitationNotFound => (StatusCode::NOT_FOUND, "Invitation not found"),
+            AuthAPIError::InvalidInvitation => (StatusCode::BAD_REQUEST, "Invitation is invalid or has expired"),
+            AuthAPIError::PersonalAccessTokenNotFound => (StatusCode::NOT_FOUND, "Personal access token not found"),
+            AuthAPIError::InvalidPersonalAccessToken => (StatusCode::BAD_REQUEST, "Personal access token needs a name and a valid expiry"),
+            AuthAPIError::InvalidTokenRequest => (StatusCode::BAD_REQUEST, "Invalid token request"),
+            AuthAPIError::UnsupportedGrantType => (StatusCode::BAD_REQUEST, "Grant type is not supported"),
+            AuthAPIError::InvalidClient => (StatusCode::UNAUTHORIZED, "Invalid client credentials"),
+            AuthAPIError::InvalidGrant => (StatusCode::BAD_REQUEST, "Subject token is invalid or cannot be exchanged"),
+            AuthAPIError::InvalidScope => (StatusCode::BAD_REQUEST, "Requested scope is not granted to the subject token"),
+            AuthAPIError::InvalidTarget => (StatusCode::BAD_REQUEST, "Client may not call the requested audience"),
+            AuthAPIError::ServiceUnavailable => (StatusCode::SERVICE_UNAVAILABLE, "Service is busy, please try again later"),
             AuthAPIError::UnexpectedError => (StatusCode::INTERNAL_SERVER_ERROR, "Unexpected error")
         };
 
         let body = Json(ErrorResponse {
-            error: error_message.to_string(),
+            error: oauth_error.unwrap_or(error_message).to_string(),
+            details,
+            error_description: oauth_error.map(|_| error_message.to_string()),
         });
 
+        // Saturation is short lived, clients can retry right away
+        if status == StatusCode::SERVICE_UNAVAILABLE {
+            return (status, [(header::RETRY_AFTER, "1")], body).into_response();
+        }
+
+        if invalid_client {
+            return (status, [(header::WWW_AUTHENTICATE, "Basic")], body).into_response();
+        }
+
         (status, body).into_response()
     }
 }
@@ -2,12 +2,25 @@ use auth_service::{app_state::AppState, get_postgres_pool, get_redis_client, ser
                                                                                                     hashset_token_store::HashsetBannedTokenStore,
                                                                                                     hashmap_two_fa_code_store::HashmapTwoFACodeStore}, utils::prod, Application};
 use std::sync::Arc;
+use auth_service::app_state::UserStoreType;
 use sqlx::PgPool;
 use tokio::sync::RwLock;
 use auth_service::domain::MockEmailClient;
 use auth_service::services::data_store::redis_banned_token_store::RedisBannedTokenStore;
-use auth_service::services::data_store::redis_two_fa_code_store::RedisTwoFACodeStore;
-use auth_service::utils::{constants, REDIS_HOST_NAME};
+use auth_service::services::data_store::redis_two_fa_code_store::{RedisTwoFACodeStore, DISABLE_2FA_CODE_PREFIX};
+use auth_service::services::data_store::redis_email_change_store::RedisEmailChangeStore;
+use auth_service::services::data_store::postgres_audit_log_store::PostgresAuditLogStore;
+use auth_service::services::data_store::postgres_role_store::PostgresRoleStore;
+use auth_service::services::data_store::postgres_tenant_store::PostgresTenantStore;
+use auth_service::services::data_store::postgres_invitation_store::PostgresInvitationStore;
+use auth_service::services::data_store::postgres_personal_access_token_store::PostgresPersonalAccessTokenStore;
+use auth_service::app_state::BreachedPasswordCheckerType;
+use auth_service::services::breached_password_checker::BloomFilterBreachedPasswordChecker;
+use auth_service::services::email_domain_filter::FileEmailDomainFilter;
+use auth_service::services::password_policy::StrengthPasswordPolicy;
+use auth_service::services::hashing_pool::HashingPool;
+use auth_service::services::password_hasher::Argon2PasswordHasher;
+use auth_service::utils::{constants, ARGON2_PARAMS, BLOCK_DISPOSABLE_EMAIL_DOMAINS, BREACHED_PASSWORDS_FILTER_PATH, EMAIL_DOMAIN_RULES_PATH, EMAIL_DOMAIN_RULES_RELOAD_SECONDS, ENUMERATION_SAFE_SIGNUP, HASHING_MAX_CONCURRENCY, HASHING_MAX_QUEUED, PASSWORD_HISTORY_LENGTH, PASSWORD_PEPPERS, PASSWORD_MAX_AGE_DAYS, PASSWORD_MAX_LENGTH, PASSWORD_MIN_LENGTH, PASSWORD_MIN_STRENGTH, REDIS_HOST_NAME, TOKEN_EXCHANGE_CLIENTS};
 use constants::{DATABASE_URL};
 
 
@@ -16,12 +29,45 @@ async fn main() {
     let pg_pool = configure_postgresql().await;
     let redis_connection = Arc::new(RwLock::new(configure_redis()));
 
-    let user_store  = Arc::new(RwLock::new(PostgresUserStore::new(pg_pool)));
+    let hashing_pool = Arc::new(HashingPool::new(*HASHING_MAX_CONCURRENCY, *HASHING_MAX_QUEUED));
+
+    let password_hasher = Arc::new(Argon2PasswordHasher::new(ARGON2_PARAMS.clone(), PASSWORD_PEPPERS.clone(), hashing_pool.clone()));
+
+    let user_store  = Arc::new(RwLock::new(PostgresUserStore::new(pg_pool.clone(), password_hasher)));
+    let audit_log_store  = Arc::new(RwLock::new(PostgresAuditLogStore::new(pg_pool.clone())));
+    let role_store  = Arc::new(RwLock::new(PostgresRoleStore::new(pg_pool.clone())));
+    let tenant_store  = Arc::new(RwLock::new(PostgresTenantStore::new(pg_pool.clone())));
+    let invitation_store  = Arc::new(RwLock::new(PostgresInvitationStore::new(pg_pool.clone())));
+    let personal_access_token_store  = Arc::new(RwLock::new(PostgresPersonalAccessTokenStore::new(pg_pool)));
     let banned_token_store  = Arc::new(RwLock::new(RedisBannedTokenStore::new(redis_connection.clone())));
     let two_fa_code_store  = Arc::new(RwLock::new(RedisTwoFACodeStore::new(redis_connection.clone())));
+    let disable_2fa_code_store  = Arc::new(RwLock::new(RedisTwoFACodeStore::new(redis_connection.clone()).with_key_prefix(DISABLE_2FA_CODE_PREFIX)));
+    let email_change_store  = Arc::new(RwLock::new(RedisEmailChangeStore::new(redis_connection.clone())));
     let email_client = Arc::new(RwLock::new(MockEmailClient));
 
-    let app_state = AppState::new(user_store, banned_token_store, two_fa_code_store, email_client);
+    let password_policy = Arc::new(StrengthPasswordPolicy::new(
+        *PASSWORD_MIN_LENGTH,
+        *PASSWORD_MAX_LENGTH,
+        *PASSWORD_MIN_STRENGTH,
+        *PASSWORD_HISTORY_LENGTH,
+        *PASSWORD_MAX_AGE_DAYS,
+        configure_breached_password_checker(),
+    ));
+
+    let email_domain_filter = configure_email_domain_filter();
+
+    let app_state = AppState::new(user_store, banned_token_store, two_fa_code_store, email_change_store, audit_log_store, email_client, password_policy, hashing_pool)
+        .with_enumeration_safe_signup(*ENUMERATION_SAFE_SIGNUP)
+        .with_disable_2fa_code_store(disable_2fa_code_store)
+        .with_role_store(role_store)
+        .with_tenant_store(tenant_store)
+        .with_invitation_store(invitation_store)
+        .with_personal_access_token_store(personal_access_token_store)
+        .with_email_domain_filter(email_domain_filter.clone())
+        .with_token_exchange_clients(TOKEN_EXCHANGE_CLIENTS.clone());
+
+    spawn_scheduled_user_deletion(app_state.user_store.clone());
+    spawn_email_domain_rules_reload(email_domain_filter);
 
     let app = Application::build(app_state, prod::APP_ADDRESS)
         .await
@@ -45,9 +91,57 @@ async fn configure_postgresql() -> PgPool {
     pg_pool
 }
 
+fn configure_breached_password_checker() -> Option<BreachedPasswordCheckerType> {
+    BREACHED_PASSWORDS_FILTER_PATH.as_ref().map(|path| {
+        let checker = BloomFilterBreachedPasswordChecker::load(path).expect("Failed to load breached passwords filter");
+        Arc::new(checker) as BreachedPasswordCheckerType
+    })
+}
+
+fn configure_email_domain_filter() -> Arc<FileEmailDomainFilter> {
+    let filter = match EMAIL_DOMAIN_RULES_PATH.as_ref() {
+        Some(path) => FileEmailDomainFilter::load(path, *BLOCK_DISPOSABLE_EMAIL_DOMAINS).expect("Failed to load email domain rules"),
+        None => FileEmailDomainFilter::new(*BLOCK_DISPOSABLE_EMAIL_DOMAINS),
+    };
+
+    Arc::new(filter)
+}
+
 fn configure_redis() -> redis::Connection {
     get_redis_client(REDIS_HOST_NAME.to_owned())
         .expect("Failed to get Redis client")
         .get_connection()
         .expect("Failed to get Redis connection")
-}
\ No newline at end of file
+}
+
+// Hard delete accounts whose deletion grace period is over
+fn spawn_scheduled_user_deletion(user_store: UserStoreType) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(60 * 60));
+        loop {
+            interval.tick().await;
+            if let Err(e) = user_store.write().await.delete_scheduled_users().await {
+                println!("Failed to delete scheduled users: {:?}", e);
+            }
+        }
+    });
+}
+
+// Pick up changes to the email domain rules file without a restart
+fn spawn_email_domain_rules_reload(email_domain_filter: Arc<FileEmailDomainFilter>) {
+    if EMAIL_DOMAIN_RULES_PATH.is_none() {
+        return;
+    }
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(*EMAIL_DOMAIN_RULES_RELOAD_SECONDS));
+        loop {
+            interval.tick().await;
+            match email_domain_filter.reload() {
+                Ok(true) => println!("Reloaded the email domain rules"),
+                Ok(false) => {}
+                Err(e) => println!("Failed to reload the email domain rules, keeping the current ones: {}", e),
+            }
+        }
+    });
+}
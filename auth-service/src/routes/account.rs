@@ -0,0 +1,283 @@
+use axum::{extract::{Host, State}, http::StatusCode, response::IntoResponse, Json};
+use axum_extra::extract::CookieJar;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    app_state::AppState,
+    domain::{data_store::{AuditEntry, AuditEvent}, AuthAPIError, Email, Password},
+    routes::{get_user_tenant, resolve_tenant, PersonalAccessTokenResponse},
+    utils::{auth::{get_claims_user, revoke_user_tokens, validate_auth_cookie}, constants::{ACCOUNT_DELETION_GRACE_PERIOD_DAYS, JWT_COOKIE_NAME}},
+};
+
+pub async fn export_account(
+    State(state): State<AppState>,
+    jar: CookieJar,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let (_, claims) = validate_auth_cookie(&jar, state.banned_token_store.clone()).await?;
+
+    let user = get_claims_user(&claims, state.user_store.clone()).await?;
+    let email = user.get_email();
+    let tenant = get_user_tenant(&state, &user).await?;
+
+    let (account, password_history_count) = {
+        let user_store = state.user_store.read().await;
+        (user_store.get_user_account(&user.get_id()).await?, user_store.get_password_history_count(&user.get_id()).await?)
+    };
+
+    let user_roles = state.role_store.read().await.get_user_roles(&user.get_id()).await?;
+
+    let pending_login_attempt = state.two_fa_code_store.read().await
+        .get_code(&email)
+        .await
+        .is_ok();
+
+    let pending_email_change = state.email_change_store.read().await
+        .get_request(&email)
+        .await
+        .ok()
+        .map(|(new_email, _, _)| new_email.as_ref().to_owned());
+
+    let personal_access_tokens = state.personal_access_token_store.read().await
+        .list_tokens(&user.get_id())
+        .await?
+        .into_iter()
+        .map(PersonalAccessTokenResponse::from)
+        .collect();
+
+    let pending_invitations = state.invitation_store.read().await
+        .list_pending_invitations(&email)
+        .await?;
+    let mut invitations = Vec::with_capacity(pending_invitations.len());
+    for invitation in pending_invitations {
+        let organization = state.tenant_store.read().await
+            .get_tenant(&invitation.tenant_id)
+            .await
+            .map_err(|_| AuthAPIError::UnexpectedError)?;
+        invitations.push(PendingInvitationExport {
+            id: invitation.id.to_string(),
+            organization: organization.name,
+            roles: invitation.roles,
+            created_at: invitation.created_at,
+            expires_at: invitation.expires_at,
+        });
+    }
+
+    let mut audit_log_store = state.audit_log_store.write().await;
+    audit_log_store.add_entry(&user.get_id(), AuditEvent::DataExported).await.map_err(|_| AuthAPIError::UnexpectedError)?;
+    let audit_log = audit_log_store.get_entries(&user.get_id()).await.map_err(|_| AuthAPIError::UnexpectedError)?;
+
+    let response = AccountExportResponse {
+        profile: ProfileExport {
+            id: user.get_id().to_string(),
+            email: user.get_email().as_ref().to_owned(),
+            pending_email_change,
+        },
+        organization: OrganizationExport {
+            id: tenant.id.to_string(),
+            slug: tenant.slug.as_ref().to_owned(),
+            name: tenant.name,
+        },
+        account_state: AccountStateExport {
+            locked: account.locked,
+            password_reset_required: account.password_reset_required,
+            approval_pending: account.approval_pending,
+            password_changed_at: account.password_changed_at,
+            password_history_count,
+            deletion_scheduled_at: account.deletion_scheduled_at,
+        },
+        roles: user_roles.roles,
+        permissions: user_roles.permissions,
+        two_factor_auth: TwoFactorAuthExport {
+            enabled: user.use_requires_2fa(),
+            pending_login_attempt,
+        },
+        // Tokens are stateless, the only session we can describe is the one of this request
+        current_session: SessionExport {
+            issued_at: Some(claims.issued_at()),
+            expires_at: DateTime::from_timestamp(claims.exp as i64, 0),
+        },
+        personal_access_tokens,
+        pending_invitations: invitations,
+        audit_log,
+    };
+
+    Ok((StatusCode::OK, Json(response)))
+}
+
+pub async fn delete_account(
+    State(state): State<AppState>,
+    jar: CookieJar,
+    Json(request): Json<DeleteAccountRequest>,
+) -> Result<(CookieJar, impl IntoResponse), AuthAPIError> {
+    let (_, claims) = validate_auth_cookie(&jar, state.banned_token_store.clone()).await?;
+
+    let user = get_claims_user(&claims, state.user_store.clone()).await?;
+    let email = user.get_email();
+    let password = Password::parse(request.password).map_err(|_| AuthAPIError::IncorrectCredentials)?;
+
+    // Hashing is slow, the write lock is only taken for the deletion
+    state.user_store.read().await.validate_user(&user.get_tenant_id(), &email, &password).await?;
+
+    state.audit_log_store.write().await
+        .add_entry(&user.get_id(), AuditEvent::AccountDeletionRequested)
+        .await
+        .map_err(|_| AuthAPIError::UnexpectedError)?;
+
+    let grace_period = chrono::Duration::try_days(*ACCOUNT_DELETION_GRACE_PERIOD_DAYS)
+        .ok_or(AuthAPIError::UnexpectedError)?;
+    let delete_at = Utc::now() + grace_period;
+
+    let message = {
+        let mut user_store = state.user_store.write().await;
+        if grace_period.is_zero() {
+            user_store.delete_user(&email).await.map_err(|_| AuthAPIError::UnexpectedError)?;
+            "Account deleted"
+        } else {
+            user_store.schedule_user_deletion(&email, delete_at).await.map_err(|_| AuthAPIError::UnexpectedError)?;
+            "Account scheduled for deletion"
+        }
+    };
+
+    // Purge everything kept in Redis for the user and revoke all of its tokens
+    let _ = state.two_fa_code_store.write().await.remove_code(&email).await;
+    let _ = state.disable_2fa_code_store.write().await.remove_code(&email).await;
+    let _ = state.email_change_store.write().await.remove_request(&email).await;
+    revoke_user_tokens(&state, &user.get_id()).await?;
+
+    let jar = jar.remove(JWT_COOKIE_NAME);
+
+    Ok((jar, (StatusCode::OK, Json(DeleteAccountResponse {
+        message: message.to_string(),
+        delete_at,
+    }))))
+}
+
+// During the grace period the user, whose tokens were revoked, cancels the deletion with its credentials and can log in again
+pub async fn cancel_account_deletion(
+    State(state): State<AppState>,
+    host: Option<Host>,
+    Json(request): Json<CancelAccountDeletionRequest>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let email = Email::parse(request.email).map_err(|_| AuthAPIError::InvalidCredentials)?;
+    let password = Password::parse(request.password).map_err(|_| AuthAPIError::InvalidCredentials)?;
+    let tenant = resolve_tenant(&state, request.tenant, host).await?;
+
+    let user = state.user_store.write().await
+        .cancel_user_deletion(&tenant.id, &email, &password)
+        .await?;
+
+    state.audit_log_store.write().await
+        .add_entry(&user.get_id(), AuditEvent::AccountDeletionCancelled)
+        .await
+        .map_err(|_| AuthAPIError::UnexpectedError)?;
+
+    Ok((StatusCode::OK, Json(CancelAccountDeletionResponse {
+        message: "Account deletion cancelled".to_string(),
+    })))
+}
+
+#[derive(Deserialize)]
+pub struct DeleteAccountRequest {
+    pub password: String,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct DeleteAccountResponse {
+    pub message: String,
+    #[serde(rename = "deleteAt")]
+    pub delete_at: DateTime<Utc>,
+}
+
+#[derive(Deserialize)]
+pub struct CancelAccountDeletionRequest {
+    pub email: String,
+    pub password: String,
+    // Slug of the organization, taken from the host when left out
+    #[serde(default)]
+    pub tenant: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CancelAccountDeletionResponse {
+    pub message: String,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct AccountExportResponse {
+    pub profile: ProfileExport,
+    pub organization: OrganizationExport,
+    #[serde(rename = "accountState")]
+    pub account_state: AccountStateExport,
+    pub roles: Vec<String>,
+    pub permissions: Vec<String>,
+    #[serde(rename = "twoFactorAuth")]
+    pub two_factor_auth: TwoFactorAuthExport,
+    #[serde(rename = "currentSession")]
+    pub current_session: SessionExport,
+    #[serde(rename = "personalAccessTokens")]
+    pub personal_access_tokens: Vec<PersonalAccessTokenResponse>,
+    // Invitations to the user's address from any organization, their tokens are not kept
+    #[serde(rename = "pendingInvitations")]
+    pub pending_invitations: Vec<PendingInvitationExport>,
+    #[serde(rename = "auditLog")]
+    pub audit_log: Vec<AuditEntry>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ProfileExport {
+    pub id: String,
+    pub email: String,
+    #[serde(rename = "pendingEmailChange")]
+    pub pending_email_change: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct OrganizationExport {
+    pub id: String,
+    pub slug: String,
+    pub name: String,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct AccountStateExport {
+    pub locked: bool,
+    #[serde(rename = "passwordResetRequired")]
+    pub password_reset_required: bool,
+    #[serde(rename = "approvalPending")]
+    pub approval_pending: bool,
+    #[serde(rename = "passwordChangedAt")]
+    pub password_changed_at: DateTime<Utc>,
+    // Only hashes of the previous passwords are kept
+    #[serde(rename = "passwordHistoryCount")]
+    pub password_history_count: u64,
+    #[serde(rename = "deletionScheduledAt")]
+    pub deletion_scheduled_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct PendingInvitationExport {
+    pub id: String,
+    // Name of the inviting organization
+    pub organization: String,
+    pub roles: Vec<String>,
+    #[serde(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    #[serde(rename = "expiresAt")]
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct TwoFactorAuthExport {
+    pub enabled: bool,
+    #[serde(rename = "pendingLoginAttempt")]
+    pub pending_login_attempt: bool,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct SessionExport {
+    #[serde(rename = "issuedAt")]
+    pub issued_at: Option<DateTime<Utc>>,
+    #[serde(rename = "expiresAt")]
+    pub expires_at: Option<DateTime<Utc>>,
+}
@@ -0,0 +1,301 @@
+use askama::Template;
+use axum::{
+    extract::{Path, Query, State},
+    response::{Html, IntoResponse, Redirect, Response},
+    Form,
+};
+use axum_extra::extract::CookieJar;
+use chrono::{DateTime, Duration, Utc};
+use serde::Deserialize;
+
+use crate::{
+    app_state::AppState,
+    domain::{data_store::{AuditEntry, AuditEvent}, AuthAPIError, UserAccount, MANAGE_USERS_PERMISSION},
+    utils::{
+        auth::{validate_auth_cookie_with_permission, Claims, TOKEN_TTL_SECONDS},
+        csrf::{generate_csrf_token, verify_csrf_token},
+    },
+};
+
+use super::{get_target_account, page_offset, require_password_reset, set_locked};
+
+const PAGE_SIZE: u64 = 20;
+
+// Each of these bans every token the user holds
+const SESSION_ENDING_EVENTS: [AuditEvent; 5] = [
+    AuditEvent::RoleRevoked,
+    AuditEvent::AccountLocked,
+    AuditEvent::PasswordResetForced,
+    AuditEvent::SessionsRevoked,
+    AuditEvent::InvitationAccepted,
+];
+
+#[derive(Template)]
+#[template(path = "admin/users.html")]
+struct UsersTemplate {
+    search: String,
+    users: Vec<ConsoleUser>,
+    total: u64,
+    previous_page: Option<u64>,
+    next_page: Option<u64>,
+}
+
+#[derive(Template)]
+#[template(path = "admin/user.html")]
+struct UserTemplate {
+    user: ConsoleUser,
+    roles: Vec<String>,
+    sessions: Vec<DateTime<Utc>>,
+    audit_log: Vec<AuditEntry>,
+    csrf_token: String,
+}
+
+struct ConsoleUser {
+    id: String,
+    email: String,
+    requires_2fa: bool,
+    locked: bool,
+    status: &'static str,
+    password_changed_at: DateTime<Utc>,
+}
+
+impl From<&UserAccount> for ConsoleUser {
+    fn from(account: &UserAccount) -> Self {
+        let status = if account.deletion_scheduled_at.is_some() {
+            "Deletion scheduled"
+        } else if account.locked {
+            "Locked"
+        } else if account.approval_pending {
+            "Pending approval"
+        } else if account.password_reset_required {
+            "Password reset required"
+        } else {
+            "Active"
+        };
+
+        Self {
+            id: account.user.get_id().to_string(),
+            email: account.user.get_email().as_ref().to_owned(),
+            requires_2fa: account.user.use_requires_2fa(),
+            locked: account.locked,
+            status,
+            password_changed_at: account.password_changed_at,
+        }
+    }
+}
+
+pub async fn console_users(
+    State(state): State<AppState>,
+    jar: CookieJar,
+    Query(query): Query<ConsoleUsersQuery>,
+) -> Result<Response, AuthAPIError> {
+    let claims = match authorize(&jar, &state).await {
+        Ok(claims) => claims,
+        Err(response) => return Ok(response),
+    };
+
+    let page = query.page.unwrap_or(1).max(1);
+    let offset = page_offset(page, PAGE_SIZE)?;
+    let search = query.search.unwrap_or_default().trim().to_owned();
+
+    let (accounts, total) = state.user_store.read().await
+        .list_users(&claims.tenant, Some(search.as_str()).filter(|search| !search.is_empty()), false, offset, PAGE_SIZE)
+        .await?;
+
+    let template = UsersTemplate {
+        search,
+        users: accounts.iter().map(ConsoleUser::from).collect(),
+        total,
+        previous_page: (page > 1).then(|| page - 1),
+        next_page: (offset + PAGE_SIZE < total).then(|| page + 1),
+    };
+
+    render(template)
+}
+
+pub async fn console_user(
+    State(state): State<AppState>,
+    jar: CookieJar,
+    Path(user_id): Path<String>,
+) -> Result<Response, AuthAPIError> {
+    let claims = match authorize(&jar, &state).await {
+        Ok(claims) => claims,
+        Err(response) => return Ok(response),
+    };
+
+    let account = get_target_account(&state, &claims.tenant, user_id).await?;
+    let user_id = account.user.get_id();
+
+    let roles = state.role_store.read().await.get_user_roles(&user_id).await?.roles;
+    let mut audit_log = state.audit_log_store.read().await
+        .get_entries(&user_id)
+        .await
+        .map_err(|_| AuthAPIError::UnexpectedError)?;
+    let sessions = active_sessions(&audit_log, Utc::now());
+    audit_log.reverse();
+
+    let template = UserTemplate {
+        user: ConsoleUser::from(&account),
+        roles,
+        sessions,
+        audit_log,
+        csrf_token: generate_csrf_token(&claims),
+    };
+
+    render(template)
+}
+
+pub async fn console_lock_user(
+    State(state): State<AppState>,
+    jar: CookieJar,
+    Path(user_id): Path<String>,
+    Form(form): Form<ConsoleActionForm>,
+) -> Result<Response, AuthAPIError> {
+    let claims = match authorize_action(&jar, &state, &form).await {
+        Ok(claims) => claims,
+        Err(response) => return Ok(response),
+    };
+
+    let user = get_target_account(&state, &claims.tenant, user_id).await?.user;
+    set_locked(&state, &user, true).await?;
+
+    Ok(redirect_to_user(&user.get_id().to_string()))
+}
+
+pub async fn console_unlock_user(
+    State(state): State<AppState>,
+    jar: CookieJar,
+    Path(user_id): Path<String>,
+    Form(form): Form<ConsoleActionForm>,
+) -> Result<Response, AuthAPIError> {
+    let claims = match authorize_action(&jar, &state, &form).await {
+        Ok(claims) => claims,
+        Err(response) => return Ok(response),
+    };
+
+    let user = get_target_account(&state, &claims.tenant, user_id).await?.user;
+    set_locked(&state, &user, false).await?;
+
+    Ok(redirect_to_user(&user.get_id().to_string()))
+}
+
+pub async fn console_force_password_reset(
+    State(state): State<AppState>,
+    jar: CookieJar,
+    Path(user_id): Path<String>,
+    Form(form): Form<ConsoleActionForm>,
+) -> Result<Response, AuthAPIError> {
+    let claims = match authorize_action(&jar, &state, &form).await {
+        Ok(claims) => claims,
+        Err(response) => return Ok(response),
+    };
+
+    let user = get_target_account(&state, &claims.tenant, user_id).await?.user;
+    require_password_reset(&state, &user).await?;
+
+    Ok(redirect_to_user(&user.get_id().to_string()))
+}
+
+// Visitors without a session are sent to the login page, admins lacking the permission get a 403
+async fn authorize(jar: &CookieJar, state: &AppState) -> Result<Claims, Response> {
+    match validate_auth_cookie_with_permission(jar, state.banned_token_store.clone(), MANAGE_USERS_PERMISSION).await {
+        Ok((_, claims)) => Ok(claims),
+        Err(AuthAPIError::MissingToken | AuthAPIError::InvalidToken) => Err(Redirect::to("/").into_response()),
+        Err(e) => Err(e.into_response()),
+    }
+}
+
+async fn authorize_action(
+    jar: &CookieJar,
+    state: &AppState,
+    form: &ConsoleActionForm,
+) -> Result<Claims, Response> {
+    let claims = authorize(jar, state).await?;
+
+    if !verify_csrf_token(&claims, &form.csrf_token) {
+        return Err(AuthAPIError::Forbidden.into_response());
+    }
+
+    Ok(claims)
+}
+
+fn render(template: impl Template) -> Result<Response, AuthAPIError> {
+    let html = template.render().map_err(|_| AuthAPIError::UnexpectedError)?;
+
+    Ok(Html(html).into_response())
+}
+
+fn redirect_to_user(user_id: &str) -> Response {
+    Redirect::to(&format!("/admin/console/users/{}", user_id)).into_response()
+}
+
+// Tokens are stateless, so sessions are estimated from the audit log: logins that have not
+// expired yet and were not followed by an event that bans all of the user's tokens.
+// A single-session logout is not recorded, such sessions are still listed.
+fn active_sessions(audit_log: &[AuditEntry], now: DateTime<Utc>) -> Vec<DateTime<Utc>> {
+    let oldest_valid_login = now - Duration::seconds(TOKEN_TTL_SECONDS);
+    let mut sessions = Vec::new();
+
+    for entry in audit_log {
+        if entry.event == AuditEvent::Login.as_ref() {
+            sessions.push(entry.created_at);
+        } else if entry.event == AuditEvent::PasswordChanged.as_ref() {
+            // The session that changed the password is handed a fresh token
+            sessions.clear();
+            sessions.push(entry.created_at);
+        } else if SESSION_ENDING_EVENTS.iter().any(|event| entry.event == event.as_ref()) {
+            sessions.clear();
+        }
+    }
+
+    sessions.retain(|started_at| *started_at > oldest_valid_login);
+    sessions.reverse();
+    sessions
+}
+
+#[derive(Deserialize)]
+pub struct ConsoleUsersQuery {
+    pub search: Option<String>,
+    pub page: Option<u64>,
+}
+
+#[derive(Deserialize)]
+pub struct ConsoleActionForm {
+    pub csrf_token: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(event: AuditEvent, created_at: DateTime<Utc>) -> AuditEntry {
+        AuditEntry { event: event.as_ref().to_owned(), created_at, actor_id: None }
+    }
+
+    #[test]
+    fn test_active_sessions_are_recent_logins_after_the_last_revocation() {
+        let now = Utc::now();
+        let minutes_ago = |minutes| now - Duration::minutes(minutes);
+
+        let audit_log = vec![
+            entry(AuditEvent::Login, minutes_ago(30)),
+            entry(AuditEvent::Login, minutes_ago(8)),
+            entry(AuditEvent::SessionsRevoked, minutes_ago(7)),
+            entry(AuditEvent::Login, minutes_ago(6)),
+            entry(AuditEvent::DataExported, minutes_ago(5)),
+            entry(AuditEvent::Login, minutes_ago(2)),
+        ];
+
+        assert_eq!(vec![minutes_ago(2), minutes_ago(6)], active_sessions(&audit_log, now));
+        assert!(active_sessions(&audit_log, now + Duration::minutes(10)).is_empty());
+        assert!(active_sessions(&audit_log[..3], now).is_empty());
+
+        let audit_log = vec![
+            entry(AuditEvent::Login, minutes_ago(4)),
+            entry(AuditEvent::Login, minutes_ago(3)),
+            entry(AuditEvent::PasswordChanged, minutes_ago(1)),
+        ];
+
+        assert_eq!(vec![minutes_ago(1)], active_sessions(&audit_log, now));
+    }
+}
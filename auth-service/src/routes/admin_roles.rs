@@ -0,0 +1,75 @@
+use axum::{extract::{Path, State}, http::StatusCode, response::IntoResponse, Json};
+use axum_extra::extract::CookieJar;
+use serde::Deserialize;
+
+use crate::{
+    app_state::AppState,
+    domain::{data_store::AuditEvent, AuthAPIError, MANAGE_ROLES_PERMISSION},
+    routes::get_target_account,
+    utils::auth::{revoke_user_tokens, validate_auth_cookie_with_permission},
+};
+
+pub async fn get_user_roles(
+    State(state): State<AppState>,
+    jar: CookieJar,
+    Path(user_id): Path<String>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let (_, claims) = validate_auth_cookie_with_permission(&jar, state.banned_token_store.clone(), MANAGE_ROLES_PERMISSION).await?;
+
+    let user_id = get_target_account(&state, &claims.tenant, user_id).await?.user.get_id();
+    let user_roles = state.role_store.read().await.get_user_roles(&user_id).await?;
+
+    Ok((StatusCode::OK, Json(user_roles)))
+}
+
+// The role shows up in the user's tokens from their next login on
+pub async fn assign_role(
+    State(state): State<AppState>,
+    jar: CookieJar,
+    Path(user_id): Path<String>,
+    Json(request): Json<AssignRoleRequest>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let (_, claims) = validate_auth_cookie_with_permission(&jar, state.banned_token_store.clone(), MANAGE_ROLES_PERMISSION).await?;
+
+    let user_id = get_target_account(&state, &claims.tenant, user_id).await?.user.get_id();
+
+    let mut role_store = state.role_store.write().await;
+    role_store.assign_role(&user_id, &request.role).await?;
+    let user_roles = role_store.get_user_roles(&user_id).await?;
+
+    state.audit_log_store.write().await
+        .add_entry(&user_id, AuditEvent::RoleAssigned)
+        .await
+        .map_err(|_| AuthAPIError::UnexpectedError)?;
+
+    Ok((StatusCode::OK, Json(user_roles)))
+}
+
+// Tokens carry the permissions they were issued with, so the user's tokens are revoked for the role to be gone right away
+pub async fn revoke_role(
+    State(state): State<AppState>,
+    jar: CookieJar,
+    Path((user_id, role)): Path<(String, String)>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let (_, claims) = validate_auth_cookie_with_permission(&jar, state.banned_token_store.clone(), MANAGE_ROLES_PERMISSION).await?;
+
+    let user_id = get_target_account(&state, &claims.tenant, user_id).await?.user.get_id();
+
+    let mut role_store = state.role_store.write().await;
+    role_store.revoke_role(&user_id, &role).await?;
+    let user_roles = role_store.get_user_roles(&user_id).await?;
+
+    revoke_user_tokens(&state, &user_id).await?;
+
+    state.audit_log_store.write().await
+        .add_entry(&user_id, AuditEvent::RoleRevoked)
+        .await
+        .map_err(|_| AuthAPIError::UnexpectedError)?;
+
+    Ok((StatusCode::OK, Json(user_roles)))
+}
+
+#[derive(Deserialize)]
+pub struct AssignRoleRequest {
+    pub role: String,
+}
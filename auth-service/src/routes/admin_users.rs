@@ -0,0 +1,308 @@
+use axum::{extract::{Path, Query, State}, http::StatusCode, response::IntoResponse, Json};
+use axum_extra::extract::CookieJar;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    app_state::AppState,
+    domain::{data_store::AuditEvent, AuthAPIError, Email, TenantId, User, UserAccount, UserId, MANAGE_USERS_PERMISSION},
+    utils::auth::{revoke_user_tokens, validate_auth_cookie_with_permission},
+};
+
+const DEFAULT_PAGE_SIZE: u64 = 20;
+const MAX_PAGE_SIZE: u64 = 100;
+
+pub async fn list_users(
+    State(state): State<AppState>,
+    jar: CookieJar,
+    Query(query): Query<ListUsersQuery>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let (_, claims) = validate_auth_cookie_with_permission(&jar, state.banned_token_store.clone(), MANAGE_USERS_PERMISSION).await?;
+
+    let page = query.page.unwrap_or(1).max(1);
+    let per_page = query.per_page.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+    let offset = page_offset(page, per_page)?;
+    let search = query.search.as_deref().map(str::trim).filter(|search| !search.is_empty());
+
+    let (accounts, total) = state.user_store.read().await
+        .list_users(&claims.tenant, search, query.pending, offset, per_page)
+        .await?;
+
+    let response = ListUsersResponse {
+        users: accounts.iter().map(AdminUserResponse::from).collect(),
+        page,
+        per_page,
+        total,
+    };
+
+    Ok((StatusCode::OK, Json(response)))
+}
+
+pub async fn get_user(
+    State(state): State<AppState>,
+    jar: CookieJar,
+    Path(user_id): Path<String>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let (_, claims) = validate_auth_cookie_with_permission(&jar, state.banned_token_store.clone(), MANAGE_USERS_PERMISSION).await?;
+
+    let account = get_target_account(&state, &claims.tenant, user_id).await?;
+
+    Ok((StatusCode::OK, Json(AdminUserResponse::from(&account))))
+}
+
+// The user's sessions are revoked and the next login only allows changing the password
+pub async fn force_password_reset(
+    State(state): State<AppState>,
+    jar: CookieJar,
+    Path(user_id): Path<String>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let (_, claims) = validate_auth_cookie_with_permission(&jar, state.banned_token_store.clone(), MANAGE_USERS_PERMISSION).await?;
+
+    let user = get_target_account(&state, &claims.tenant, user_id).await?.user;
+    require_password_reset(&state, &user).await?;
+
+    respond_with_account(&state, &user).await
+}
+
+pub async fn set_user_2fa(
+    State(state): State<AppState>,
+    jar: CookieJar,
+    Path(user_id): Path<String>,
+    Json(request): Json<SetUser2FARequest>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let (_, claims) = validate_auth_cookie_with_permission(&jar, state.banned_token_store.clone(), MANAGE_USERS_PERMISSION).await?;
+
+    let user = get_target_account(&state, &claims.tenant, user_id).await?.user;
+
+    state.user_store.write().await.update_requires_2fa(&user.get_email(), request.enabled).await?;
+
+    let event = match request.enabled {
+        true => AuditEvent::TwoFactorAuthEnabled,
+        false => AuditEvent::TwoFactorAuthDisabled,
+    };
+    add_audit_entry(&state, &user, event).await?;
+
+    respond_with_account(&state, &user).await
+}
+
+pub async fn lock_user(
+    State(state): State<AppState>,
+    jar: CookieJar,
+    Path(user_id): Path<String>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let (_, claims) = validate_auth_cookie_with_permission(&jar, state.banned_token_store.clone(), MANAGE_USERS_PERMISSION).await?;
+
+    let user = get_target_account(&state, &claims.tenant, user_id).await?.user;
+    set_locked(&state, &user, true).await?;
+
+    respond_with_account(&state, &user).await
+}
+
+pub async fn unlock_user(
+    State(state): State<AppState>,
+    jar: CookieJar,
+    Path(user_id): Path<String>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let (_, claims) = validate_auth_cookie_with_permission(&jar, state.banned_token_store.clone(), MANAGE_USERS_PERMISSION).await?;
+
+    let user = get_target_account(&state, &claims.tenant, user_id).await?.user;
+    set_locked(&state, &user, false).await?;
+
+    respond_with_account(&state, &user).await
+}
+
+pub async fn revoke_sessions(
+    State(state): State<AppState>,
+    jar: CookieJar,
+    Path(user_id): Path<String>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let (_, claims) = validate_auth_cookie_with_permission(&jar, state.banned_token_store.clone(), MANAGE_USERS_PERMISSION).await?;
+
+    let user = get_target_account(&state, &claims.tenant, user_id).await?.user;
+
+    revoke_user_sessions(&state, &user).await?;
+    add_audit_entry(&state, &user, AuditEvent::SessionsRevoked).await?;
+
+    respond_with_account(&state, &user).await
+}
+
+// Only users awaiting approval can be approved or rejected, others are not found
+pub async fn approve_user(
+    State(state): State<AppState>,
+    jar: CookieJar,
+    Path(user_id): Path<String>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let (_, claims) = validate_auth_cookie_with_permission(&jar, state.banned_token_store.clone(), MANAGE_USERS_PERMISSION).await?;
+
+    let user = get_pending_account(&state, &claims.tenant, user_id).await?.user;
+
+    state.user_store.write().await.approve_user(&user.get_email()).await?;
+    add_audit_entry(&state, &user, AuditEvent::AccountApproved).await?;
+
+    notify_user(&state, &user.get_email(), "Your account was approved", "An administrator has approved your account, you can now log in.").await;
+
+    respond_with_account(&state, &user).await
+}
+
+// The account is deleted right away, the address can sign up again
+pub async fn reject_user(
+    State(state): State<AppState>,
+    jar: CookieJar,
+    Path(user_id): Path<String>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let (_, claims) = validate_auth_cookie_with_permission(&jar, state.banned_token_store.clone(), MANAGE_USERS_PERMISSION).await?;
+
+    let account = get_pending_account(&state, &claims.tenant, user_id).await?;
+
+    state.user_store.write().await.delete_user(&account.user.get_email()).await?;
+
+    notify_user(&state, &account.user.get_email(), "Your account was not approved", "An administrator has declined your signup, the account was deleted.").await;
+
+    Ok((StatusCode::OK, Json(AdminUserResponse::from(&account))))
+}
+
+async fn get_pending_account(state: &AppState, tenant_id: &TenantId, user_id: String) -> Result<UserAccount, AuthAPIError> {
+    let account = get_target_account(state, tenant_id, user_id).await?;
+
+    if !account.approval_pending {
+        return Err(AuthAPIError::UserNotFound);
+    }
+
+    Ok(account)
+}
+
+// Number of rows before `page`, which the stores take as a signed 64-bit offset
+pub(crate) fn page_offset(page: u64, per_page: u64) -> Result<u64, AuthAPIError> {
+    (page - 1)
+        .checked_mul(per_page)
+        .filter(|offset| i64::try_from(*offset).is_ok())
+        .ok_or(AuthAPIError::InvalidPage)
+}
+
+// The user an admin acts on, users scheduled for deletion included. Members of other organizations are not found.
+pub(crate) async fn get_target_account(state: &AppState, tenant_id: &TenantId, user_id: String) -> Result<UserAccount, AuthAPIError> {
+    let user_id = UserId::parse(user_id).map_err(|_| AuthAPIError::UserNotFound)?;
+
+    let account = state.user_store.read().await
+        .get_user_account(&user_id)
+        .await
+        .map_err(|_| AuthAPIError::UserNotFound)?;
+
+    if account.user.get_tenant_id() != *tenant_id {
+        return Err(AuthAPIError::UserNotFound);
+    }
+
+    Ok(account)
+}
+
+// Shared with the admin console
+pub(crate) async fn require_password_reset(state: &AppState, user: &User) -> Result<(), AuthAPIError> {
+    state.user_store.write().await.require_password_reset(&user.get_email()).await?;
+    revoke_user_sessions(state, user).await?;
+    add_audit_entry(state, user, AuditEvent::PasswordResetForced).await?;
+
+    notify_user(
+        state,
+        &user.get_email(),
+        "Password reset required",
+        "An administrator has asked you to choose a new password. You will be asked to change it on your next login."
+    ).await;
+
+    Ok(())
+}
+
+// The action is done by the time the user is told, a lost email must not report a failure to the admin
+async fn notify_user(state: &AppState, email: &Email, subject: &str, content: &str) {
+    if let Err(e) = state.email_client.read().await.send_email(email, subject, content).await {
+        println!("Failed to send the \"{}\" notification: {}", subject, e);
+    }
+}
+
+// Locking also ends the sessions the user already has
+pub(crate) async fn set_locked(state: &AppState, user: &User, locked: bool) -> Result<(), AuthAPIError> {
+    state.user_store.write().await.set_locked(&user.get_email(), locked).await?;
+
+    if locked {
+        revoke_user_sessions(state, user).await?;
+        add_audit_entry(state, user, AuditEvent::AccountLocked).await
+    } else {
+        add_audit_entry(state, user, AuditEvent::AccountUnlocked).await
+    }
+}
+
+// Revokes every token of the user and drops a pending 2FA login
+async fn revoke_user_sessions(state: &AppState, user: &User) -> Result<(), AuthAPIError> {
+    let _ = state.two_fa_code_store.write().await.remove_code(&user.get_email()).await;
+
+    revoke_user_tokens(state, &user.get_id()).await
+}
+
+async fn add_audit_entry(state: &AppState, user: &User, event: AuditEvent) -> Result<(), AuthAPIError> {
+    state.audit_log_store.write().await
+        .add_entry(&user.get_id(), event)
+        .await
+        .map_err(|_| AuthAPIError::UnexpectedError)
+}
+
+async fn respond_with_account(state: &AppState, user: &User) -> Result<(StatusCode, Json<AdminUserResponse>), AuthAPIError> {
+    let account = state.user_store.read().await.get_user_account(&user.get_id()).await?;
+
+    Ok((StatusCode::OK, Json(AdminUserResponse::from(&account))))
+}
+
+#[derive(Deserialize)]
+pub struct ListUsersQuery {
+    pub search: Option<String>,
+    pub page: Option<u64>,
+    #[serde(rename = "perPage")]
+    pub per_page: Option<u64>,
+    // Only list the users awaiting approval
+    #[serde(default)]
+    pub pending: bool,
+}
+
+#[derive(Deserialize)]
+pub struct SetUser2FARequest {
+    pub enabled: bool,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ListUsersResponse {
+    pub users: Vec<AdminUserResponse>,
+    pub page: u64,
+    #[serde(rename = "perPage")]
+    pub per_page: u64,
+    pub total: u64,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct AdminUserResponse {
+    pub id: String,
+    pub email: String,
+    #[serde(rename = "requires2FA")]
+    pub requires_2fa: bool,
+    pub locked: bool,
+    #[serde(rename = "passwordResetRequired")]
+    pub password_reset_required: bool,
+    #[serde(rename = "approvalPending")]
+    pub approval_pending: bool,
+    #[serde(rename = "passwordChangedAt")]
+    pub password_changed_at: DateTime<Utc>,
+    #[serde(rename = "deletionScheduledAt")]
+    pub deletion_scheduled_at: Option<DateTime<Utc>>,
+}
+
+impl From<&UserAccount> for AdminUserResponse {
+    fn from(account: &UserAccount) -> Self {
+        Self {
+            id: account.user.get_id().to_string(),
+            email: account.user.get_email().as_ref().to_owned(),
+            requires_2fa: account.user.use_requires_2fa(),
+            locked: account.locked,
+            password_reset_required: account.password_reset_required,
+            approval_pending: account.approval_pending,
+            password_changed_at: account.password_changed_at,
+            deletion_scheduled_at: account.deletion_scheduled_at,
+        }
+    }
+}
@@ -0,0 +1,166 @@
+use axum::{extract::{Query, State}, http::StatusCode, response::IntoResponse, Json};
+use axum_extra::extract::CookieJar;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    app_state::AppState,
+    domain::{data_store::{AuditEvent, EmailChangeToken, UserStoreError}, AuthAPIError, Email, Password},
+    routes::{check_email_domain, get_user_tenant},
+    utils::{auth::{get_claims_user, validate_auth_cookie}, constants::AUTH_SERVICE_URL},
+};
+
+pub async fn change_email(
+    State(state): State<AppState>,
+    jar: CookieJar,
+    Json(request): Json<ChangeEmailRequest>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let (_, claims) = validate_auth_cookie(&jar, state.banned_token_store.clone()).await?;
+
+    let user = get_claims_user(&claims, state.user_store.clone()).await?;
+    let email = user.get_email().clone();
+    let password = Password::parse(request.password).map_err(|_| AuthAPIError::IncorrectCredentials)?;
+    let new_email = Email::parse(request.new_email).map_err(|_| AuthAPIError::InvalidCredentials)?;
+    if new_email == email {
+        return Err(AuthAPIError::EmailUnchanged);
+    }
+
+    {
+        let user_store = state.user_store.read().await;
+
+        user_store.validate_user(&user.get_tenant_id(), &email, &password).await?;
+
+        if !get_user_tenant(&state, &user).await?.settings.allows_email(&new_email) {
+            return Err(AuthAPIError::EmailDomainNotAllowed);
+        }
+        check_email_domain(&state, &new_email)?;
+
+        if user_store.get_user_by_email(&new_email).await.is_ok() {
+            return Err(AuthAPIError::UserAlreadyExists);
+        }
+    }
+
+    let confirmation_token = EmailChangeToken::default();
+    let cancel_token = EmailChangeToken::default();
+
+    let mut email_change_store = state.email_change_store.write().await;
+    email_change_store
+        .add_request(email.clone(), new_email.clone(), confirmation_token.clone(), cancel_token.clone())
+        .await
+        .map_err(|_| AuthAPIError::UnexpectedError)?;
+
+    state.audit_log_store.write().await
+        .add_entry(&user.get_id(), AuditEvent::EmailChangeRequested)
+        .await
+        .map_err(|_| AuthAPIError::UnexpectedError)?;
+
+    let email_client = state.email_client.read().await;
+
+    let confirmation_link = email_change_link("confirm-email-change", &email, &confirmation_token)?;
+    email_client.send_email(
+        &new_email,
+        "Confirm your new email address",
+        &format!("Follow this link to use this address for your account: {}", confirmation_link)
+    ).await.map_err(|_| AuthAPIError::UnexpectedError)?;
+
+    let cancel_link = email_change_link("cancel-email-change", &email, &cancel_token)?;
+    email_client.send_email(
+        &email,
+        "Email address change requested",
+        &format!("A change of your account email to {} was requested. If you did not do this, follow this link to cancel it: {}", new_email.as_ref(), cancel_link)
+    ).await.map_err(|_| AuthAPIError::UnexpectedError)?;
+
+    Ok(StatusCode::OK)
+}
+
+pub async fn confirm_email_change(
+    State(state): State<AppState>,
+    Query(request): Query<EmailChangeLinkQuery>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let email = Email::parse(request.email).map_err(|_| AuthAPIError::InvalidToken)?;
+    let token = EmailChangeToken::parse(request.token).map_err(|_| AuthAPIError::InvalidToken)?;
+
+    let mut email_change_store = state.email_change_store.write().await;
+
+    let (new_email, confirmation_token, _) = email_change_store
+        .get_request(&email)
+        .await
+        .map_err(|_| AuthAPIError::InvalidToken)?;
+
+    if confirmation_token != token {
+        return Err(AuthAPIError::InvalidToken);
+    }
+
+    let mut user_store = state.user_store.write().await;
+    let user = user_store.get_user_by_email(&email).await.map_err(|_| AuthAPIError::InvalidToken)?;
+    user_store.update_email(&email, new_email.clone()).await.map_err(|e| match e {
+        UserStoreError::UserAlreadyExists => AuthAPIError::UserAlreadyExists,
+        _ => AuthAPIError::UnexpectedError,
+    })?;
+
+    state.audit_log_store.write().await
+        .add_entry(&user.get_id(), AuditEvent::EmailChanged)
+        .await
+        .map_err(|_| AuthAPIError::UnexpectedError)?;
+
+    email_change_store.remove_request(&email).await.map_err(|_| AuthAPIError::UnexpectedError)?;
+
+    // Tokens are bound to the user id and stay valid, only the pending 2FA codes are keyed by the old address
+    let _ = state.two_fa_code_store.write().await.remove_code(&email).await;
+    let _ = state.disable_2fa_code_store.write().await.remove_code(&email).await;
+
+    Ok((StatusCode::OK, Json(EmailChangeResponse {
+        message: format!("Your email address has been changed to {}", new_email.as_ref()),
+    })))
+}
+
+pub async fn cancel_email_change(
+    State(state): State<AppState>,
+    Query(request): Query<EmailChangeLinkQuery>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let email = Email::parse(request.email).map_err(|_| AuthAPIError::InvalidToken)?;
+    let token = EmailChangeToken::parse(request.token).map_err(|_| AuthAPIError::InvalidToken)?;
+
+    let mut email_change_store = state.email_change_store.write().await;
+
+    let (_, _, cancel_token) = email_change_store
+        .get_request(&email)
+        .await
+        .map_err(|_| AuthAPIError::InvalidToken)?;
+
+    if cancel_token != token {
+        return Err(AuthAPIError::InvalidToken);
+    }
+
+    email_change_store.remove_request(&email).await.map_err(|_| AuthAPIError::UnexpectedError)?;
+
+    Ok((StatusCode::OK, Json(EmailChangeResponse {
+        message: "Email address change has been cancelled".to_string(),
+    })))
+}
+
+fn email_change_link(path: &str, email: &Email, token: &EmailChangeToken) -> Result<String, AuthAPIError> {
+    let url = reqwest::Url::parse_with_params(
+        &format!("{}/{}", AUTH_SERVICE_URL.as_str(), path),
+        &[("email", email.as_ref()), ("token", token.as_ref())],
+    ).map_err(|_| AuthAPIError::UnexpectedError)?;
+
+    Ok(url.to_string())
+}
+
+#[derive(Deserialize)]
+pub struct ChangeEmailRequest {
+    pub password: String,
+    #[serde(rename = "newEmail")]
+    pub new_email: String,
+}
+
+#[derive(Deserialize)]
+pub struct EmailChangeLinkQuery {
+    pub email: String,
+    pub token: String,
+}
+
+#[derive(Debug, Deserialize, Serialize, PartialEq)]
+pub struct EmailChangeResponse {
+    pub message: String
+}
@@ -0,0 +1,78 @@
+use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
+use axum_extra::extract::CookieJar;
+use serde::Deserialize;
+
+use crate::{
+    app_state::AppState,
+    domain::{data_store::AuditEvent, AuthAPIError, Password, PasswordPolicyViolation},
+    routes::{get_user_tenant, tenant_password_policy},
+    utils::auth::{generate_auth_cookie, get_claims_user, revoke_user_tokens, validate_auth_cookie_with_scope, TokenScope},
+};
+
+pub async fn change_password(
+    State(state): State<AppState>,
+    jar: CookieJar,
+    Json(request): Json<ChangePasswordRequest>,
+) -> Result<(CookieJar, impl IntoResponse), AuthAPIError> {
+    // Users whose password has expired only hold a token restricted to this route
+    let (token, claims) = validate_auth_cookie_with_scope(&jar, state.banned_token_store.clone(), Some(TokenScope::ChangePassword)).await?;
+
+    let user = get_claims_user(&claims, state.user_store.clone()).await?;
+    let email = user.get_email();
+    let current_password = Password::parse(request.current_password).map_err(|_| AuthAPIError::IncorrectCredentials)?;
+    let new_password = Password::parse(request.new_password).map_err(|_| AuthAPIError::InvalidCredentials)?;
+    let password_policy = tenant_password_policy(&state, &get_user_tenant(&state, &user).await?);
+
+    // Hashing is slow, only the update itself holds the write lock every login and signup waits on
+    {
+        let user_store = state.user_store.read().await;
+
+        user_store.validate_user(&user.get_tenant_id(), &email, &current_password).await?;
+
+        let mut violations = password_policy.check(&new_password, &email).err().unwrap_or_default();
+
+        let history_length = password_policy.history_length();
+        if user_store.is_recent_password(&email, &new_password, history_length).await? {
+            violations.push(PasswordPolicyViolation::RecentlyUsed { history_length });
+        }
+
+        if !violations.is_empty() {
+            return Err(AuthAPIError::WeakPassword(violations));
+        }
+    }
+
+    state.user_store.write().await.update_password(&email, new_password).await?;
+
+    state.audit_log_store.write().await
+        .add_entry(&user.get_id(), AuditEvent::PasswordChanged)
+        .await
+        .map_err(|_| AuthAPIError::UnexpectedError)?;
+
+    // Every other session of the user is revoked, the current one gets a fresh token
+    state.banned_token_store.write().await.storing_tokens(token).await.map_err(|_| AuthAPIError::UnexpectedError)?;
+    revoke_user_tokens(&state, &user.get_id()).await?;
+
+    let user_roles = state.role_store.read().await.get_user_roles(&user.get_id()).await?;
+    let auth_cookie = generate_auth_cookie(&user, &user_roles).map_err(|_| AuthAPIError::UnexpectedError)?;
+    let update_jar = jar.add(auth_cookie);
+
+    // The password has changed already, a lost notification must not report a failure
+    let email_client = state.email_client.read().await;
+    if let Err(e) = email_client.send_email(
+        &email,
+        "Password changed",
+        "Your password has been changed. If you did not do this, please reset your password immediately."
+    ).await {
+        println!("Failed to send the password change notification: {}", e);
+    }
+
+    Ok((update_jar, StatusCode::OK))
+}
+
+#[derive(Deserialize)]
+pub struct ChangePasswordRequest {
+    #[serde(rename = "currentPassword")]
+    pub current_password: String,
+    #[serde(rename = "newPassword")]
+    pub new_password: String,
+}
@@ -0,0 +1,54 @@
+use axum::{extract::{Path, State}, http::StatusCode, response::IntoResponse, Json};
+use axum_extra::extract::CookieJar;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    app_state::AppState,
+    domain::{data_store::AuditEvent, AuthAPIError, UserId, IMPERSONATE_USERS_PERMISSION},
+    routes::get_target_account,
+    utils::{auth::{generate_impersonation_cookie, validate_auth_cookie_with_permission}, constants::IMPERSONATION_TTL_SECONDS},
+};
+
+// Replaces the admin's session with a short-lived one of the user, which ends with `/logout` or when it expires.
+// Every impersonation is written to the user's audit log along with the admin.
+pub async fn impersonate_user(
+    State(state): State<AppState>,
+    jar: CookieJar,
+    Path(user_id): Path<String>,
+) -> Result<(CookieJar, impl IntoResponse), AuthAPIError> {
+    let (_, claims) = validate_auth_cookie_with_permission(&jar, state.banned_token_store.clone(), IMPERSONATE_USERS_PERMISSION).await?;
+
+    let admin_id = UserId::parse(claims.sub.clone()).map_err(|_| AuthAPIError::InvalidToken)?;
+    let user = get_target_account(&state, &claims.tenant, user_id).await?.user;
+    if user.get_id() == admin_id {
+        return Err(AuthAPIError::Forbidden);
+    }
+
+    let user_roles = state.role_store.read().await.get_user_roles(&user.get_id()).await?;
+
+    state.audit_log_store.write().await
+        .add_entry_by_actor(&user.get_id(), &admin_id, AuditEvent::Impersonated)
+        .await
+        .map_err(|_| AuthAPIError::UnexpectedError)?;
+
+    let cookie = generate_impersonation_cookie(&user, &user_roles, &admin_id, *IMPERSONATION_TTL_SECONDS)
+        .map_err(|_| AuthAPIError::UnexpectedError)?;
+
+    let response = ImpersonationResponse {
+        user_id: user.get_id().to_string(),
+        email: user.get_email().as_ref().to_owned(),
+        expires_in: *IMPERSONATION_TTL_SECONDS,
+    };
+
+    Ok((jar.add(cookie), (StatusCode::OK, Json(response))))
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ImpersonationResponse {
+    #[serde(rename = "userId")]
+    pub user_id: String,
+    pub email: String,
+    // Seconds until the session ends
+    #[serde(rename = "expiresIn")]
+    pub expires_in: i64,
+}
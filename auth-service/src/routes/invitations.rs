@@ -0,0 +1,281 @@
+use axum::{extract::{Path, State}, http::StatusCode, response::IntoResponse, Json};
+use axum_extra::extract::CookieJar;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    app_state::AppState,
+    domain::{
+        data_store::{AuditEvent, InvitationStoreError, UserStore, UserStoreError},
+        AuthAPIError, Email, Invitation, InvitationId, InvitationStatus, InvitationToken, Password, User, UserId,
+        MANAGE_ROLES_PERMISSION, MANAGE_USERS_PERMISSION,
+    },
+    routes::tenant_password_policy,
+    utils::{auth::{revoke_user_tokens, validate_auth_cookie_with_permission}, constants::{AUTH_SERVICE_URL, INVITATION_TTL_HOURS}},
+};
+
+// Inviting with roles takes the permission to manage roles as well
+pub async fn create_invitation(
+    State(state): State<AppState>,
+    jar: CookieJar,
+    Json(request): Json<CreateInvitationRequest>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let (_, claims) = validate_auth_cookie_with_permission(&jar, state.banned_token_store.clone(), MANAGE_USERS_PERMISSION).await?;
+
+    if !request.roles.is_empty() && !claims.permissions.iter().any(|permission| permission == MANAGE_ROLES_PERMISSION) {
+        return Err(AuthAPIError::Forbidden);
+    }
+
+    let email = Email::parse(request.email).map_err(|_| AuthAPIError::InvalidCredentials)?;
+    let admin_id = UserId::parse(claims.sub.clone()).map_err(|_| AuthAPIError::InvalidToken)?;
+    let tenant = state.tenant_store.read().await.get_tenant(&claims.tenant).await?;
+
+    if !tenant.settings.allows_email(&email) {
+        return Err(AuthAPIError::EmailDomainNotAllowed);
+    }
+
+    if state.user_store.read().await.get_user(&tenant.id, &email).await.is_ok() {
+        return Err(AuthAPIError::UserAlreadyExists);
+    }
+
+    let ttl = chrono::Duration::try_hours(*INVITATION_TTL_HOURS).ok_or(AuthAPIError::UnexpectedError)?;
+    let (invitation, token) = Invitation::new(tenant.id, email.clone(), request.roles, admin_id, ttl);
+    state.invitation_store.write().await.add_invitation(invitation.clone()).await?;
+
+    let link = invitation_link(&token)?;
+    state.email_client.read().await.send_email(
+        &email,
+        &format!("You are invited to join {}", tenant.name),
+        &format!(
+            "You have been invited to join {} on {}. Follow this link to accept the invitation: {}",
+            tenant.name,
+            AUTH_SERVICE_URL.as_str(),
+            link
+        ),
+    ).await.map_err(|_| AuthAPIError::UnexpectedError)?;
+
+    Ok((StatusCode::CREATED, Json(InvitationResponse::from(invitation))))
+}
+
+pub async fn list_invitations(
+    State(state): State<AppState>,
+    jar: CookieJar,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let (_, claims) = validate_auth_cookie_with_permission(&jar, state.banned_token_store.clone(), MANAGE_USERS_PERMISSION).await?;
+
+    let invitations = state.invitation_store.read().await.list_invitations(&claims.tenant).await?;
+
+    Ok((StatusCode::OK, Json(ListInvitationsResponse {
+        invitations: invitations.into_iter().map(InvitationResponse::from).collect(),
+    })))
+}
+
+// Only pending invitations can be revoked
+pub async fn revoke_invitation(
+    State(state): State<AppState>,
+    jar: CookieJar,
+    Path(invitation_id): Path<String>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let (_, claims) = validate_auth_cookie_with_permission(&jar, state.banned_token_store.clone(), MANAGE_USERS_PERMISSION).await?;
+
+    let invitation_id = InvitationId::parse(invitation_id).map_err(|_| AuthAPIError::InvitationNotFound)?;
+
+    let mut invitation_store = state.invitation_store.write().await;
+    let invitation = invitation_store.get_invitation(&invitation_id).await?;
+    if invitation.tenant_id != claims.tenant {
+        return Err(AuthAPIError::InvitationNotFound);
+    }
+
+    invitation_store.revoke_invitation(&invitation_id).await?;
+    let invitation = invitation_store.get_invitation(&invitation_id).await?;
+
+    Ok((StatusCode::OK, Json(InvitationResponse::from(invitation))))
+}
+
+// Creates the account of the invited address, or moves an existing one to the organization. Moving takes the
+// account's password, drops the roles it had in its former organization and ends its sessions.
+pub async fn accept_invitation(
+    State(state): State<AppState>,
+    Json(request): Json<AcceptInvitationRequest>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let token = InvitationToken::parse(request.token).map_err(|_| AuthAPIError::InvalidInvitation)?;
+    let password = Password::parse(request.password).map_err(|_| AuthAPIError::InvalidCredentials)?;
+
+    let invitation = state.invitation_store.read().await
+        .get_invitation_by_token(&token)
+        .await
+        .map_err(|_| AuthAPIError::InvalidInvitation)?;
+    if invitation.status(Utc::now()) != InvitationStatus::Pending {
+        return Err(AuthAPIError::InvalidInvitation);
+    }
+
+    let tenant = state.tenant_store.read().await.get_tenant(&invitation.tenant_id).await?;
+    let email = invitation.email.clone();
+
+    // Hashing is slow, the write lock is only taken once the caller is authenticated and the link claimed
+    let existing_user = {
+        let user_store = state.user_store.read().await;
+        match user_store.get_user_by_email(&email).await {
+            // The invitation stands for an admin's approval
+            Ok(user) => match user_store.validate_user(&user.get_tenant_id(), &email, &password).await {
+                Ok(()) => Some((user, false)),
+                Err(UserStoreError::ApprovalPending) => Some((user, true)),
+                Err(e) => return Err(e.into()),
+            },
+            Err(UserStoreError::UserNotFound) => {
+                tenant_password_policy(&state, &tenant).check(&password, &email).map_err(AuthAPIError::WeakPassword)?;
+                None
+            }
+            Err(e) => return Err(e.into()),
+        }
+    };
+
+    // Claimed before the account changes, so a link used twice at once only goes through once
+    state.invitation_store.write().await
+        .accept_invitation(&invitation.id)
+        .await
+        .map_err(|e| match e {
+            InvitationStoreError::InvitationNotFound => AuthAPIError::InvalidInvitation,
+            e => e.into(),
+        })?;
+
+    let mut user_store = state.user_store.write().await;
+    let result = match existing_user {
+        Some((user, approval_pending)) => join_with_existing_account(&state, &mut *user_store, &user, approval_pending, &invitation)
+            .await
+            .map(|()| (user.get_id(), StatusCode::OK)),
+        None => {
+            let user = User::new(email, password, request.requires_2fa).with_tenant(tenant.id);
+            let user_id = user.get_id();
+            user_store.add_user(user).await
+                .map(|()| (user_id, StatusCode::CREATED))
+                .map_err(AuthAPIError::from)
+        }
+    };
+    drop(user_store);
+
+    // The link stays usable when the account could not be changed
+    let (user_id, status) = match result {
+        Ok(accepted) => accepted,
+        Err(e) => {
+            let _ = state.invitation_store.write().await.release_invitation(&invitation.id).await;
+            return Err(e);
+        }
+    };
+
+    let mut role_store = state.role_store.write().await;
+    for role in &invitation.roles {
+        role_store.assign_role(&user_id, role).await?;
+    }
+
+    state.audit_log_store.write().await
+        .add_entry(&user_id, AuditEvent::InvitationAccepted)
+        .await
+        .map_err(|_| AuthAPIError::UnexpectedError)?;
+
+    Ok((status, Json(AcceptInvitationResponse {
+        message: format!("You have joined {}", tenant.name),
+        tenant: tenant.slug.as_ref().to_owned(),
+    })))
+}
+
+async fn join_with_existing_account(
+    state: &AppState,
+    user_store: &mut dyn UserStore,
+    user: &User,
+    approval_pending: bool,
+    invitation: &Invitation,
+) -> Result<(), AuthAPIError> {
+    if user.get_tenant_id() != invitation.tenant_id {
+        move_to_tenant(state, user_store, user, invitation).await?;
+    }
+    if approval_pending {
+        user_store.approve_user(&user.get_email()).await?;
+    }
+
+    Ok(())
+}
+
+async fn move_to_tenant(
+    state: &AppState,
+    user_store: &mut dyn UserStore,
+    user: &User,
+    invitation: &Invitation,
+) -> Result<(), AuthAPIError> {
+    user_store.update_tenant(&user.get_email(), &invitation.tenant_id).await?;
+
+    let mut role_store = state.role_store.write().await;
+    for role in role_store.get_user_roles(&user.get_id()).await?.roles {
+        role_store.revoke_role(&user.get_id(), &role).await?;
+    }
+
+    // Existing tokens carry the former organization
+    revoke_user_tokens(state, &user.get_id()).await
+}
+
+fn invitation_link(token: &InvitationToken) -> Result<String, AuthAPIError> {
+    let url = reqwest::Url::parse_with_params(
+        &format!("{}/", AUTH_SERVICE_URL.as_str()),
+        &[("invite", token.as_ref())],
+    ).map_err(|_| AuthAPIError::UnexpectedError)?;
+
+    Ok(url.to_string())
+}
+
+#[derive(Deserialize)]
+pub struct CreateInvitationRequest {
+    pub email: String,
+    #[serde(default)]
+    pub roles: Vec<String>,
+}
+
+#[derive(Deserialize)]
+pub struct AcceptInvitationRequest {
+    pub token: String,
+    // The password of the new account, or of the existing account with the invited address
+    pub password: String,
+    // Only used for a new account
+    #[serde(default, rename = "requires2FA")]
+    pub requires_2fa: bool,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct AcceptInvitationResponse {
+    pub message: String,
+    // Slug to log in with
+    pub tenant: String,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ListInvitationsResponse {
+    pub invitations: Vec<InvitationResponse>,
+}
+
+// The token is only ever sent to the invited address
+#[derive(Debug, Deserialize, Serialize)]
+pub struct InvitationResponse {
+    pub id: String,
+    pub email: String,
+    pub roles: Vec<String>,
+    pub status: InvitationStatus,
+    #[serde(rename = "invitedBy")]
+    pub invited_by: Option<String>,
+    #[serde(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    #[serde(rename = "expiresAt")]
+    pub expires_at: DateTime<Utc>,
+}
+
+impl From<Invitation> for InvitationResponse {
+    fn from(invitation: Invitation) -> Self {
+        Self {
+            id: invitation.id.to_string(),
+            status: invitation.status(Utc::now()),
+            email: invitation.email.as_ref().to_owned(),
+            roles: invitation.roles,
+            invited_by: invitation.invited_by.map(|user_id| user_id.to_string()),
+            created_at: invitation.created_at,
+            expires_at: invitation.expires_at,
+        }
+    }
+}
@@ -1,36 +1,48 @@
 use std::string::ToString;
-use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
-use axum_extra::extract::CookieJar;
+use axum::{extract::{Host, State}, http::StatusCode, response::IntoResponse, Json};
+use axum_extra::extract::{cookie::Cookie, CookieJar};
 use serde::{Deserialize, Serialize};
 
-use crate::{app_state::AppState, domain::{AuthAPIError, Email, Password}, utils::auth::generate_auth_cookie};
-use crate::domain::data_store::{LoginAttemptId, TwoFACode};
+use crate::{app_state::AppState, domain::{AuthAPIError, Email, Password, PasswordPolicy, User}, utils::auth::{generate_auth_cookie, generate_restricted_auth_cookie, TokenScope}};
+use crate::routes::{resolve_tenant, tenant_password_policy};
+use crate::domain::data_store::{AuditEvent, LoginAttemptId, RoleStore, TwoFACode, UserStore};
 
 
 pub async fn login(
     State(state): State<AppState>,
+    host: Option<Host>,
     jar: CookieJar,
     Json(request): Json<LoginRequest>,
 ) ->  Result<(CookieJar, impl IntoResponse), AuthAPIError> {
 
     let email = Email::parse(request.email).map_err(|_| AuthAPIError::InvalidCredentials)?;
     let password = Password::parse(request.password).map_err(|_| AuthAPIError::InvalidCredentials)?;
+    let tenant = resolve_tenant(&state, request.tenant, host).await?;
 
     let user_store = state.user_store.read().await;
 
-    if user_store.validate_user(&email, &password).await.is_err() {
-        return Err(AuthAPIError::IncorrectCredentials);
-    }
-    
-    match user_store.get_user(&email).await {
+    user_store.validate_user(&tenant.id, &email, &password).await?;
+
+    match user_store.get_user(&tenant.id, &email).await {
         Ok(user) =>  {
-            let auth_cookie = generate_auth_cookie(&email).map_err(|_| AuthAPIError::IncorrectCredentials)?;
+            state.audit_log_store.write().await
+                .add_entry(&user.get_id(), AuditEvent::Login)
+                .await
+                .map_err(|_| AuthAPIError::UnexpectedError)?;
+
+            // Logins confirmed with 2FA get their token from /verify-2fa, not before
+            if user.use_requires_2fa() || tenant.settings.requires_2fa {
+                let response = handle_2fa(&email, &state).await?;
+                return Ok((jar, response));
+            }
+
+            let password_policy = tenant_password_policy(&state, &tenant);
+            let (auth_cookie, password_expired) = generate_login_cookie(&*user_store, &*state.role_store.read().await, &*password_policy, &user).await?;
             let update_jar = jar.add(auth_cookie);
-            
-            match user.use_requires_2fa() {
+
+            match password_expired {
                 true => {
-                    let response = handle_2fa(&email, &state).await?;
-                    Ok((update_jar, response))
+                    Ok((update_jar, handle_password_expired()))
                 },
                 false => {
                     let response= handle_no_2fa().await?;
@@ -67,11 +79,41 @@ async fn handle_no_2fa() -> Result<(StatusCode, Json<LoginResponse>), AuthAPIErr
     Ok((StatusCode::OK, Json(LoginResponse::RegularAuth)))
 }
 
+pub(crate) fn handle_password_expired() -> (StatusCode, Json<LoginResponse>) {
+    (StatusCode::FORBIDDEN,
+     Json(LoginResponse::PasswordExpired(PasswordExpiredResponse {
+         message: "Password expired, change required".to_string(),
+     })))
+}
+
+// Users whose password has expired, or who were asked by an admin to reset it, only get a token that allows them to change it
+pub(crate) async fn generate_login_cookie(
+    user_store: &dyn UserStore,
+    role_store: &dyn RoleStore,
+    password_policy: &dyn PasswordPolicy,
+    user: &User,
+) -> Result<(Cookie<'static>, bool), AuthAPIError> {
+    let account = user_store.get_user_account(&user.get_id()).await.map_err(|_| AuthAPIError::UnexpectedError)?;
+    let password_expired = account.password_reset_required || password_policy.is_expired(account.password_changed_at);
+
+    let auth_cookie = if password_expired {
+        generate_restricted_auth_cookie(user, TokenScope::ChangePassword)
+    } else {
+        let user_roles = role_store.get_user_roles(&user.get_id()).await?;
+        generate_auth_cookie(user, &user_roles)
+    }.map_err(|_| AuthAPIError::UnexpectedError)?;
+
+    Ok((auth_cookie, password_expired))
+}
+
 
 #[derive(Deserialize)]
 pub struct LoginRequest {
     pub email: String,
-    pub password: String
+    pub password: String,
+    // Slug of the organization, taken from the host when left out
+    #[serde(default)]
+    pub tenant: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -79,6 +121,7 @@ pub struct LoginRequest {
 pub enum LoginResponse {
     RegularAuth,
     TwoFactorAuth(TwoFactorAuthResponse),
+    PasswordExpired(PasswordExpiredResponse),
 }
 
 // If a user requires 2FA, this JSON body should be returned!
@@ -87,4 +130,9 @@ pub struct TwoFactorAuthResponse {
     pub message: String,
     #[serde(rename = "loginAttemptId")]
     pub login_attempt_id: String,
-}
\ No newline at end of file
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PasswordExpiredResponse {
+    pub message: String,
+}
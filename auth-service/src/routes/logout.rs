@@ -3,7 +3,7 @@ use axum_extra::extract::CookieJar;
 
 use crate::{
     app_state::AppState, domain::AuthAPIError, 
-    utils::{auth::validate_token, constants::JWT_COOKIE_NAME}
+    utils::{auth::{validate_token_with_scope, TokenScope}, constants::JWT_COOKIE_NAME}
 };
 
 pub async fn logout(
@@ -17,7 +17,8 @@ pub async fn logout(
 
     let token = cookie.value().to_owned();
     
-    if validate_token(&token, state.banned_token_store.clone()).await.is_err() {
+    // Users whose password has expired can still log out
+    if validate_token_with_scope(&token, state.banned_token_store.clone(), Some(TokenScope::ChangePassword)).await.is_err() {
         return (jar, Err(AuthAPIError::InvalidToken));
     }
 
@@ -0,0 +1,26 @@
+use axum::{extract::State, response::IntoResponse, Json};
+use axum_extra::extract::CookieJar;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    app_state::AppState,
+    domain::{AuthAPIError, READ_METRICS_PERMISSION},
+    services::hashing_pool::HashingPoolMetrics,
+    utils::auth::validate_auth_cookie_with_permission,
+};
+
+pub async fn metrics(
+    State(state): State<AppState>,
+    jar: CookieJar,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    validate_auth_cookie_with_permission(&jar, state.banned_token_store.clone(), READ_METRICS_PERMISSION).await?;
+
+    Ok(Json(MetricsResponse {
+        hashing: state.hashing_pool.metrics(),
+    }))
+}
+
+#[derive(Debug, Serialize, Deserialize, PartialEq)]
+pub struct MetricsResponse {
+    pub hashing: HashingPoolMetrics,
+}
@@ -1,12 +1,38 @@
+mod account;
+mod admin_console;
+mod admin_roles;
+mod admin_users;
+mod change_email;
+mod change_password;
+mod impersonation;
+mod invitations;
 mod login;
 mod logout;
+mod metrics;
+mod personal_access_tokens;
 mod signup;
+mod tenant;
+mod toggle_2fa;
+mod token_exchange;
 mod verify_2fa;
 mod verify_token;
 
 // re-export items from sub-modules
+pub use account::*;
+pub use admin_console::*;
+pub use admin_roles::*;
+pub use admin_users::*;
+pub use change_email::*;
+pub use change_password::*;
+pub use impersonation::*;
+pub use invitations::*;
 pub use login::*;
 pub use logout::*;
+pub use metrics::*;
+pub use personal_access_tokens::*;
 pub use signup::*;
+pub use tenant::*;
+pub use toggle_2fa::*;
+pub use token_exchange::*;
 pub use verify_2fa::*;
 pub use verify_token::*;
\ No newline at end of file
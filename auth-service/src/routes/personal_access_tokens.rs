@@ -0,0 +1,145 @@
+use axum::{extract::{Path, State}, http::StatusCode, response::IntoResponse, Json};
+use axum_extra::extract::CookieJar;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    app_state::AppState,
+    domain::{
+        data_store::AuditEvent, AuthAPIError, PersonalAccessToken, PersonalAccessTokenId, PersonalAccessTokenStatus, UserId,
+        PERSONAL_ACCESS_TOKEN_PREFIX,
+    },
+    utils::{
+        auth::validate_auth_cookie,
+        constants::{DEFAULT_PERSONAL_ACCESS_TOKEN_TTL_DAYS, PERSONAL_ACCESS_TOKEN_MAX_TTL_DAYS},
+    },
+};
+
+const MAX_NAME_LENGTH: usize = 100;
+
+// The token is only part of this response, it cannot be shown again. It can only grant permissions the user has.
+pub async fn create_personal_access_token(
+    State(state): State<AppState>,
+    jar: CookieJar,
+    Json(request): Json<CreatePersonalAccessTokenRequest>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    // Personal access tokens are refused, otherwise a leaked one could be traded for one that outlives it
+    let (_, claims) = validate_auth_cookie(&jar, state.banned_token_store.clone()).await?;
+
+    let name = request.name.trim().to_owned();
+    let ttl_days = request.expires_in_days.unwrap_or(DEFAULT_PERSONAL_ACCESS_TOKEN_TTL_DAYS.min(*PERSONAL_ACCESS_TOKEN_MAX_TTL_DAYS));
+    if name.is_empty() || name.chars().count() > MAX_NAME_LENGTH || !(1..=*PERSONAL_ACCESS_TOKEN_MAX_TTL_DAYS).contains(&ttl_days) {
+        return Err(AuthAPIError::InvalidPersonalAccessToken);
+    }
+
+    if !request.scopes.iter().all(|scope| claims.permissions.contains(scope)) {
+        return Err(AuthAPIError::Forbidden);
+    }
+
+    let user_id = UserId::parse(claims.sub.clone()).map_err(|_| AuthAPIError::InvalidToken)?;
+    let ttl = chrono::Duration::try_days(ttl_days).ok_or(AuthAPIError::UnexpectedError)?;
+    let (personal_access_token, token) = PersonalAccessToken::new(user_id, name, request.scopes, ttl);
+
+    state.personal_access_token_store.write().await.add_token(personal_access_token.clone()).await?;
+    state.audit_log_store.write().await
+        .add_entry(&user_id, AuditEvent::PersonalAccessTokenCreated)
+        .await
+        .map_err(|_| AuthAPIError::UnexpectedError)?;
+
+    Ok((StatusCode::CREATED, Json(CreatePersonalAccessTokenResponse {
+        token,
+        personal_access_token: PersonalAccessTokenResponse::from(personal_access_token),
+    })))
+}
+
+pub async fn list_personal_access_tokens(
+    State(state): State<AppState>,
+    jar: CookieJar,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let (_, claims) = validate_auth_cookie(&jar, state.banned_token_store.clone()).await?;
+
+    let user_id = UserId::parse(claims.sub.clone()).map_err(|_| AuthAPIError::InvalidToken)?;
+    let tokens = state.personal_access_token_store.read().await.list_tokens(&user_id).await?;
+
+    Ok((StatusCode::OK, Json(ListPersonalAccessTokensResponse {
+        tokens: tokens.into_iter().map(PersonalAccessTokenResponse::from).collect(),
+    })))
+}
+
+// Only active tokens of the user can be revoked
+pub async fn revoke_personal_access_token(
+    State(state): State<AppState>,
+    jar: CookieJar,
+    Path(token_id): Path<String>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let (_, claims) = validate_auth_cookie(&jar, state.banned_token_store.clone()).await?;
+
+    let user_id = UserId::parse(claims.sub.clone()).map_err(|_| AuthAPIError::InvalidToken)?;
+    let token_id = PersonalAccessTokenId::parse(token_id).map_err(|_| AuthAPIError::PersonalAccessTokenNotFound)?;
+
+    let mut personal_access_token_store = state.personal_access_token_store.write().await;
+    personal_access_token_store.revoke_token(&user_id, &token_id).await?;
+    let token = personal_access_token_store.list_tokens(&user_id).await?
+        .into_iter()
+        .find(|token| token.id == token_id)
+        .ok_or(AuthAPIError::PersonalAccessTokenNotFound)?;
+
+    state.audit_log_store.write().await
+        .add_entry(&user_id, AuditEvent::PersonalAccessTokenRevoked)
+        .await
+        .map_err(|_| AuthAPIError::UnexpectedError)?;
+
+    Ok((StatusCode::OK, Json(PersonalAccessTokenResponse::from(token))))
+}
+
+#[derive(Deserialize)]
+pub struct CreatePersonalAccessTokenRequest {
+    pub name: String,
+    #[serde(default)]
+    pub scopes: Vec<String>,
+    #[serde(rename = "expiresInDays")]
+    pub expires_in_days: Option<i64>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct CreatePersonalAccessTokenResponse {
+    pub token: String,
+    #[serde(flatten)]
+    pub personal_access_token: PersonalAccessTokenResponse,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct ListPersonalAccessTokensResponse {
+    pub tokens: Vec<PersonalAccessTokenResponse>,
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct PersonalAccessTokenResponse {
+    pub id: String,
+    pub name: String,
+    pub scopes: Vec<String>,
+    // Start of the token, to tell which one it is
+    pub prefix: String,
+    pub status: PersonalAccessTokenStatus,
+    #[serde(rename = "createdAt")]
+    pub created_at: DateTime<Utc>,
+    #[serde(rename = "expiresAt")]
+    pub expires_at: DateTime<Utc>,
+    #[serde(rename = "lastUsedAt")]
+    pub last_used_at: Option<DateTime<Utc>>,
+}
+
+impl From<PersonalAccessToken> for PersonalAccessTokenResponse {
+    fn from(token: PersonalAccessToken) -> Self {
+        Self {
+            id: token.id.to_string(),
+            status: token.status(Utc::now()),
+            prefix: format!("{}{}", PERSONAL_ACCESS_TOKEN_PREFIX, token.prefix),
+            name: token.name,
+            scopes: token.scopes,
+            created_at: token.created_at,
+            expires_at: token.expires_at,
+            last_used_at: token.last_used_at,
+        }
+    }
+}
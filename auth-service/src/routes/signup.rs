@@ -1,31 +1,97 @@
 use axum::{
-    extract::State, http::StatusCode, 
+    extract::{Host, State}, http::StatusCode, 
     response::{IntoResponse, Json}  
 };
 use serde::{Deserialize, Serialize};
 use crate::{
     app_state::AppState,
-    domain::{AuthAPIError, Email, Password, User},
+    domain::{data_store::{AuditEvent, UserStoreError}, AuthAPIError, Email, Password, SignupMode, User},
+    routes::{resolve_tenant, tenant_password_policy},
 };
 
 pub async fn signup(
     State(state): State<AppState>,
+    host: Option<Host>,
     Json(request): Json<SignupRequest>,
 ) -> Result<impl IntoResponse, AuthAPIError> {
 
     let email = Email::parse(request.email).map_err(|_| AuthAPIError::InvalidCredentials)?;
     let password = Password::parse(request.password).map_err(|_| AuthAPIError::InvalidCredentials)?;
+    let tenant = resolve_tenant(&state, request.tenant, host).await?;
+
+    match tenant.settings.signup_mode {
+        SignupMode::Disabled => return Err(AuthAPIError::SignupDisabled),
+        SignupMode::InviteOnly => return Err(AuthAPIError::InvitationRequired),
+        SignupMode::DomainRestricted if !tenant.settings.allows_signup_email(&email) => {
+            return Err(AuthAPIError::EmailDomainNotAllowed);
+        }
+        _ => {}
+    }
+
+    if !tenant.settings.allows_email(&email) {
+        return Err(AuthAPIError::EmailDomainNotAllowed);
+    }
+
+    check_email_domain(&state, &email)?;
+
+    tenant_password_policy(&state, &tenant).check(&password, &email).map_err(AuthAPIError::WeakPassword)?;
 
     let mut user_store = state.user_store.write().await;
-    let user = User::new(email, password, request.requires_2fa);
+    let user = User::new(email.clone(), password, request.requires_2fa).with_tenant(tenant.id);
+    let user_id = user.get_id();
+    let approval_required = tenant.settings.signup_mode == SignupMode::PendingApproval;
+
+    let result = match approval_required {
+        true => user_store.add_pending_user(user).await,
+        false => user_store.add_user(user).await,
+    };
+    match result {
+        Ok(()) => {}
+        Err(UserStoreError::UserAlreadyExists) => {
+            // Emails are unique across organizations, yet one must not learn who belongs to another
+            let member = user_store.get_email_tenant(&email).await.map_or(true, |tenant_id| tenant_id == tenant.id);
+            if member && !state.enumeration_safe_signup {
+                return Err(AuthAPIError::UserAlreadyExists);
+            }
+            notify_existing_owner(&email, &state).await;
+            return Ok(signup_response(approval_required));
+        }
+        Err(e) => return Err(e.into()),
+    }
+
+    let mut audit_log_store = state.audit_log_store.write().await;
+    audit_log_store.add_entry(&user_id, AuditEvent::Signup).await.map_err(|_| AuthAPIError::UnexpectedError)?;
 
-    user_store.add_user(user).await.map_err(|_| AuthAPIError::UserAlreadyExists)?;
+    Ok(signup_response(approval_required))
+}
+
+fn signup_response(approval_required: bool) -> (StatusCode, Json<SignupResponse>) {
+    let message = match approval_required {
+        true => "User created successfully! An administrator has to approve the account before you can log in.",
+        false => "User created successfully!",
+    };
+
+    (StatusCode::CREATED, Json(SignupResponse {
+        message: message.to_string(),
+    }))
+}
+
+// Shared with the email change
+pub(crate) fn check_email_domain(state: &AppState, email: &Email) -> Result<(), AuthAPIError> {
+    match &state.email_domain_filter {
+        Some(filter) if !filter.is_allowed(email) => Err(AuthAPIError::EmailDomainBlocked),
+        _ => Ok(()),
+    }
+}
 
-    let response = Json(SignupResponse {
-        message: "User created successfully!".to_string(),
-    });
+// A failure is not reported, the response has to look the same as for a new account
+async fn notify_existing_owner(email: &Email, state: &AppState) {
+    let content = "Someone tried to sign up with this email address. \
+        If it was you, you already have an account and can log in or change your password. \
+        Otherwise you can ignore this email.";
 
-    Ok((StatusCode::CREATED, response))
+    let email_client = state.email_client.read().await;
+    let _ = email_client.send_email(email, "Sign up attempt with your email", content).await;
 }
 
 #[derive(Deserialize)]
@@ -34,6 +100,9 @@ pub struct SignupRequest {
     pub password: String,
     #[serde(rename = "requires2FA")]
     pub requires_2fa: bool,
+    // Slug of the organization, taken from the host when left out
+    #[serde(default)]
+    pub tenant: Option<String>,
 }
 
 #[derive(Debug, Deserialize, Serialize, PartialEq)]
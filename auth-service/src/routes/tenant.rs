@@ -0,0 +1,108 @@
+use axum::{extract::{Host, State}, http::StatusCode, response::IntoResponse, Json};
+use axum_extra::extract::CookieJar;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    app_state::{AppState, PasswordPolicyType},
+    domain::{AuthAPIError, Tenant, TenantId, TenantSettings, TenantSlug, User, MANAGE_TENANT_PERMISSION},
+    utils::auth::validate_auth_cookie_with_permission,
+};
+
+pub async fn get_tenant(
+    State(state): State<AppState>,
+    jar: CookieJar,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let (_, claims) = validate_auth_cookie_with_permission(&jar, state.banned_token_store.clone(), MANAGE_TENANT_PERMISSION).await?;
+
+    let tenant = state.tenant_store.read().await.get_tenant(&claims.tenant).await?;
+
+    Ok((StatusCode::OK, Json(TenantResponse::from(tenant))))
+}
+
+// Settings are replaced as a whole, fields left out get their default
+pub async fn update_tenant_settings(
+    State(state): State<AppState>,
+    jar: CookieJar,
+    Json(settings): Json<TenantSettings>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let (_, claims) = validate_auth_cookie_with_permission(&jar, state.banned_token_store.clone(), MANAGE_TENANT_PERMISSION).await?;
+
+    let settings = settings.parse().map_err(|_| AuthAPIError::InvalidTenantSettings)?;
+
+    let mut tenant_store = state.tenant_store.write().await;
+    tenant_store.update_settings(&claims.tenant, settings).await?;
+    let tenant = tenant_store.get_tenant(&claims.tenant).await?;
+
+    Ok((StatusCode::OK, Json(TenantResponse::from(tenant))))
+}
+
+// The organization a visitor logs in to or signs up with: the slug they typed, else the one whose
+// login host they are on, else the default organization
+pub(crate) async fn resolve_tenant(state: &AppState, slug: Option<String>, host: Option<Host>) -> Result<Tenant, AuthAPIError> {
+    let tenant_store = state.tenant_store.read().await;
+
+    if let Some(slug) = slug.filter(|slug| !slug.trim().is_empty()) {
+        let slug = TenantSlug::parse(slug).map_err(|_| AuthAPIError::TenantNotFound)?;
+        return Ok(tenant_store.get_tenant_by_slug(slug.as_ref()).await?);
+    }
+
+    if let Some(Host(host)) = host {
+        if let Ok(tenant) = tenant_store.get_tenant_by_host(&strip_port(&host).to_lowercase()).await {
+            return Ok(tenant);
+        }
+    }
+
+    Ok(tenant_store.get_tenant(&TenantId::default()).await?)
+}
+
+pub(crate) async fn get_user_tenant(state: &AppState, user: &User) -> Result<Tenant, AuthAPIError> {
+    state.tenant_store.read().await
+        .get_tenant(&user.get_tenant_id())
+        .await
+        .map_err(|_| AuthAPIError::UnexpectedError)
+}
+
+pub(crate) fn tenant_password_policy(state: &AppState, tenant: &Tenant) -> PasswordPolicyType {
+    state.password_policy.with_overrides(&tenant.settings.password_policy)
+}
+
+fn strip_port(host: &str) -> &str {
+    match host.rsplit_once(':') {
+        Some((name, port)) if !port.is_empty() && port.chars().all(|c| c.is_ascii_digit()) => name,
+        _ => host,
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize)]
+pub struct TenantResponse {
+    pub id: String,
+    pub slug: String,
+    pub name: String,
+    pub host: Option<String>,
+    pub settings: TenantSettings,
+}
+
+impl From<Tenant> for TenantResponse {
+    fn from(tenant: Tenant) -> Self {
+        Self {
+            id: tenant.id.to_string(),
+            slug: tenant.slug.as_ref().to_owned(),
+            name: tenant.name,
+            host: tenant.host,
+            settings: tenant.settings,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_strip_port() {
+        assert_eq!("login.acme.test", strip_port("login.acme.test:3000"));
+        assert_eq!("login.acme.test", strip_port("login.acme.test"));
+        assert_eq!("[::1]", strip_port("[::1]:3000"));
+        assert_eq!("[::1]", strip_port("[::1]"));
+    }
+}
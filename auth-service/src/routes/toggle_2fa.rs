@@ -0,0 +1,128 @@
+use axum::{extract::State, http::StatusCode, response::{IntoResponse, Response}, Json};
+use axum_extra::extract::CookieJar;
+use serde::Deserialize;
+
+use crate::{
+    app_state::AppState,
+    domain::{data_store::{AuditEvent, LoginAttemptId, TwoFACode}, AuthAPIError, Email, Password, User},
+    routes::{get_user_tenant, TwoFactorAuthResponse},
+    utils::auth::{get_claims_user, validate_auth_cookie},
+};
+
+pub async fn enable_2fa(
+    State(state): State<AppState>,
+    jar: CookieJar,
+    Json(request): Json<Enable2FARequest>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    let (_, claims) = validate_auth_cookie(&jar, state.banned_token_store.clone()).await?;
+
+    let user = get_claims_user(&claims, state.user_store.clone()).await?;
+    let email = user.get_email();
+    let password = Password::parse(request.password).map_err(|_| AuthAPIError::IncorrectCredentials)?;
+
+    // Hashing is slow, the write lock is only taken for the update
+    state.user_store.read().await.validate_user(&user.get_tenant_id(), &email, &password).await?;
+
+    state.user_store.write().await.update_requires_2fa(&email, true).await.map_err(|_| AuthAPIError::UnexpectedError)?;
+
+    notify_2fa_change(&state, &user, AuditEvent::TwoFactorAuthEnabled).await?;
+
+    Ok(StatusCode::OK)
+}
+
+pub async fn disable_2fa(
+    State(state): State<AppState>,
+    jar: CookieJar,
+    Json(request): Json<Disable2FARequest>,
+) -> Result<Response, AuthAPIError> {
+    let (_, claims) = validate_auth_cookie(&jar, state.banned_token_store.clone()).await?;
+
+    let user = get_claims_user(&claims, state.user_store.clone()).await?;
+    let email = user.get_email();
+    let password = Password::parse(request.password).map_err(|_| AuthAPIError::IncorrectCredentials)?;
+
+    state.user_store.read().await.validate_user(&user.get_tenant_id(), &email, &password).await?;
+
+    if get_user_tenant(&state, &user).await?.settings.requires_2fa {
+        return Err(AuthAPIError::TwoFactorAuthRequired);
+    }
+
+    if !user.use_requires_2fa() {
+        return Ok(StatusCode::OK.into_response());
+    }
+
+    // Without a code we send one first, the client then repeats the request with it
+    let (login_attempt_id, two_fa_code) = match (request.login_attempt_id, request.two_fa_code) {
+        (Some(login_attempt_id), Some(two_fa_code)) => (login_attempt_id, two_fa_code),
+        _ => return send_2fa_code(&state, &email).await,
+    };
+
+    let request_login_attempt_id = LoginAttemptId::parse(login_attempt_id).map_err(|_| AuthAPIError::InvalidCredentials)?;
+    let request_two_fa_code = TwoFACode::parse(two_fa_code).map_err(|_| AuthAPIError::InvalidCredentials)?;
+
+    {
+        let mut two_fa_code_store = state.disable_2fa_code_store.write().await;
+
+        let (state_login_attempt_id, state_two_fa_code) = two_fa_code_store.get_code(&email).await.map_err(|_| AuthAPIError::IncorrectCredentials)?;
+
+        if state_login_attempt_id != request_login_attempt_id || state_two_fa_code != request_two_fa_code {
+            return Err(AuthAPIError::IncorrectCredentials);
+        }
+
+        two_fa_code_store.remove_code(&email).await.map_err(|_| AuthAPIError::UnexpectedError)?;
+    }
+
+    state.user_store.write().await.update_requires_2fa(&email, false).await.map_err(|_| AuthAPIError::UnexpectedError)?;
+
+    notify_2fa_change(&state, &user, AuditEvent::TwoFactorAuthDisabled).await?;
+
+    Ok(StatusCode::OK.into_response())
+}
+
+async fn send_2fa_code(state: &AppState, email: &Email) -> Result<Response, AuthAPIError> {
+    let login_attempt_id = LoginAttemptId::default();
+    let two_fa_code = TwoFACode::default();
+
+    let email_client = state.email_client.read().await;
+    email_client.send_email(email, "2FA Code", two_fa_code.as_ref()).await.map_err(|_| AuthAPIError::UnexpectedError)?;
+
+    let mut two_fa_code_store = state.disable_2fa_code_store.write().await;
+    two_fa_code_store.add_code(email.clone(), login_attempt_id.clone(), two_fa_code).await.map_err(|_| AuthAPIError::UnexpectedError)?;
+
+    Ok((StatusCode::PARTIAL_CONTENT,
+        Json(TwoFactorAuthResponse {
+            message: "2FA required".to_string(),
+            login_attempt_id: login_attempt_id.as_ref().to_string()
+        })).into_response())
+}
+
+async fn notify_2fa_change(state: &AppState, user: &User, event: AuditEvent) -> Result<(), AuthAPIError> {
+    state.audit_log_store.write().await
+        .add_entry(&user.get_id(), event)
+        .await
+        .map_err(|_| AuthAPIError::UnexpectedError)?;
+
+    let content = match event {
+        AuditEvent::TwoFactorAuthEnabled => "Two-factor authentication has been enabled for your account.",
+        _ => "Two-factor authentication has been disabled for your account. If you did not do this, please change your password immediately.",
+    };
+
+    let email_client = state.email_client.read().await;
+    email_client.send_email(&user.get_email(), "Two-factor authentication settings changed", content)
+        .await
+        .map_err(|_| AuthAPIError::UnexpectedError)
+}
+
+#[derive(Deserialize)]
+pub struct Enable2FARequest {
+    pub password: String,
+}
+
+#[derive(Deserialize)]
+pub struct Disable2FARequest {
+    pub password: String,
+    #[serde(rename = "loginAttemptId")]
+    pub login_attempt_id: Option<String>,
+    #[serde(rename = "2FACode")]
+    pub two_fa_code: Option<String>,
+}
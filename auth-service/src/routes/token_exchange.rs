@@ -0,0 +1,141 @@
+use axum::{extract::State, http::{header, HeaderMap, StatusCode}, response::IntoResponse, Form, Json};
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    app_state::AppState,
+    domain::{AuthAPIError, TokenExchangeClient},
+    utils::{auth::{generate_exchanged_token, validate_token}, constants::TOKEN_EXCHANGE_TTL_SECONDS},
+};
+
+pub const TOKEN_EXCHANGE_GRANT_TYPE: &str = "urn:ietf:params:oauth:grant-type:token-exchange";
+pub const ACCESS_TOKEN_TYPE: &str = "urn:ietf:params:oauth:token-type:access_token";
+pub const JWT_TOKEN_TYPE: &str = "urn:ietf:params:oauth:token-type:jwt";
+
+// RFC 8693 token exchange: a service holding the token of a user gets a token to call another service on their behalf.
+// The new token is restricted to that service, grants only the requested scopes of the user, names the calling
+// client in its `act` claim and expires sooner.
+pub async fn exchange_token(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Form(request): Form<TokenExchangeRequest>,
+) -> Result<impl IntoResponse, AuthAPIError> {
+    if request.grant_type.as_deref() != Some(TOKEN_EXCHANGE_GRANT_TYPE) {
+        return Err(match request.grant_type {
+            Some(_) => AuthAPIError::UnsupportedGrantType,
+            None => AuthAPIError::InvalidTokenRequest,
+        });
+    }
+
+    let client = authenticate_client(&state, &headers, &request)?;
+
+    let (Some(subject_token), Some(audience)) = (request.subject_token, request.audience) else {
+        return Err(AuthAPIError::InvalidTokenRequest);
+    };
+    if ![Some(ACCESS_TOKEN_TYPE), Some(JWT_TOKEN_TYPE)].contains(&request.subject_token_type.as_deref()) {
+        return Err(AuthAPIError::InvalidTokenRequest);
+    }
+    if request.requested_token_type.as_deref().is_some_and(|token_type| token_type != ACCESS_TOKEN_TYPE && token_type != JWT_TOKEN_TYPE) {
+        return Err(AuthAPIError::InvalidTokenRequest);
+    }
+
+    if !client.may_call(&audience) {
+        return Err(AuthAPIError::InvalidTarget);
+    }
+
+    // Tokens already restricted to an audience are not valid here, so exchanged tokens cannot be exchanged again.
+    // Neither can the tokens of an impersonating admin, who must not reach other services as the user.
+    let subject = validate_token(&subject_token, state.banned_token_store.clone())
+        .await
+        .map_err(|_| AuthAPIError::InvalidGrant)?;
+    if subject.act.is_some() {
+        return Err(AuthAPIError::InvalidGrant);
+    }
+
+    // Leaving the scope out asks for a token granting nothing but the identity of the user
+    let mut scopes: Vec<String> = request.scope.unwrap_or_default().split_whitespace().map(str::to_string).collect();
+    scopes.sort_unstable();
+    scopes.dedup();
+    if !scopes.iter().all(|scope| subject.permissions.contains(scope)) {
+        return Err(AuthAPIError::InvalidScope);
+    }
+
+    let (access_token, claims) = generate_exchanged_token(&subject, client, &audience, scopes, *TOKEN_EXCHANGE_TTL_SECONDS)
+        .map_err(|_| AuthAPIError::UnexpectedError)?;
+
+    let response = TokenExchangeResponse {
+        access_token,
+        issued_token_type: ACCESS_TOKEN_TYPE.to_string(),
+        token_type: "Bearer".to_string(),
+        expires_in: claims.exp.saturating_sub(claims.iat) as i64,
+        scope: claims.permissions.join(" "),
+    };
+
+    Ok((StatusCode::OK, [(header::CACHE_CONTROL, "no-store")], Json(response)))
+}
+
+// Clients authenticate with HTTP Basic or with their credentials in the form, not both
+fn authenticate_client<'a>(state: &'a AppState, headers: &HeaderMap, request: &TokenExchangeRequest) -> Result<&'a TokenExchangeClient, AuthAPIError> {
+    let basic_credentials = match headers.get(header::AUTHORIZATION) {
+        Some(authorization) => Some(parse_basic_credentials(authorization.to_str().ok()).ok_or(AuthAPIError::InvalidClient)?),
+        None => None,
+    };
+
+    let (client_id, client_secret) = match (basic_credentials, &request.client_id, &request.client_secret) {
+        (Some(credentials), None, None) => credentials,
+        (None, Some(client_id), Some(client_secret)) => (client_id.clone(), client_secret.clone()),
+        (None, None, None) => return Err(AuthAPIError::InvalidClient),
+        _ => return Err(AuthAPIError::InvalidTokenRequest),
+    };
+
+    state.token_exchange_clients.iter()
+        .find(|client| client.id() == client_id && client.verify_secret(&client_secret))
+        .ok_or(AuthAPIError::InvalidClient)
+}
+
+fn parse_basic_credentials(authorization: Option<&str>) -> Option<(String, String)> {
+    let encoded = authorization?.strip_prefix("Basic ")?;
+    let decoded = String::from_utf8(STANDARD.decode(encoded.trim()).ok()?).ok()?;
+    let (client_id, client_secret) = decoded.split_once(':')?;
+
+    Some((client_id.to_string(), client_secret.to_string()))
+}
+
+// Every field is optional so that missing ones are answered with `invalid_request` rather than a rejection of the form
+#[derive(Debug, Deserialize)]
+pub struct TokenExchangeRequest {
+    pub grant_type: Option<String>,
+    pub subject_token: Option<String>,
+    pub subject_token_type: Option<String>,
+    pub requested_token_type: Option<String>,
+    pub audience: Option<String>,
+    // Space separated permissions of the user to carry over
+    pub scope: Option<String>,
+    pub client_id: Option<String>,
+    pub client_secret: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TokenExchangeResponse {
+    pub access_token: String,
+    pub issued_token_type: String,
+    pub token_type: String,
+    pub expires_in: i64,
+    pub scope: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_basic_credentials() {
+        let authorization = format!("Basic {}", STANDARD.encode("orders:s3c:ret"));
+        assert_eq!(parse_basic_credentials(Some(&authorization)), Some(("orders".to_string(), "s3c:ret".to_string())));
+
+        assert_eq!(parse_basic_credentials(None), None);
+        assert_eq!(parse_basic_credentials(Some("Bearer token")), None);
+        assert_eq!(parse_basic_credentials(Some("Basic not base64!")), None);
+        assert_eq!(parse_basic_credentials(Some(&format!("Basic {}", STANDARD.encode("orders")))), None);
+    }
+}
@@ -5,7 +5,7 @@ use serde::Deserialize;
 use crate::app_state::AppState;
 use crate::domain::{AuthAPIError, Email};
 use crate::domain::data_store::{LoginAttemptId, TwoFACode};
-use crate::utils::auth::generate_auth_cookie;
+use crate::routes::{generate_login_cookie, get_user_tenant, handle_password_expired, tenant_password_policy};
 
 pub async fn verify_2fa(
     State(state): State<AppState>,
@@ -26,9 +26,20 @@ pub async fn verify_2fa(
 
     two_fa_code_store.remove_code(&email).await.map_err(|_| AuthAPIError::IncorrectCredentials)?;
 
-    let auth_cookie = generate_auth_cookie(&email).map_err(|_| AuthAPIError::IncorrectCredentials)?;
+    let user_store = state.user_store.read().await;
+    let user = user_store
+        .get_user_by_email(&email)
+        .await
+        .map_err(|_| AuthAPIError::IncorrectCredentials)?;
+
+    let password_policy = tenant_password_policy(&state, &get_user_tenant(&state, &user).await?);
+    let (auth_cookie, password_expired) = generate_login_cookie(&*user_store, &*state.role_store.read().await, &*password_policy, &user).await?;
     let update_jar = jar.add(auth_cookie);
 
+    if password_expired {
+        return Ok((update_jar, handle_password_expired().into_response()));
+    }
+
     Ok((update_jar, StatusCode::OK.into_response()))
 }
 
@@ -1,21 +1,65 @@
-use axum::{extract::State, http::StatusCode, response::IntoResponse, Json};
-use serde::Deserialize;
+use axum::{extract::{rejection::JsonRejection, State}, http::{HeaderMap, StatusCode}, response::{IntoResponse, Response}, Json};
+use axum_extra::extract::CookieJar;
+use serde::{Deserialize, Serialize};
 
-use crate::{app_state::AppState, domain::AuthAPIError, utils::auth::validate_token};
+use crate::{
+    app_state::AppState,
+    domain::{AuthAPIError, PERSONAL_ACCESS_TOKEN_PREFIX},
+    utils::{auth::{bearer_token, validate_personal_access_token, validate_token, validate_token_for_audience, Actor}, constants::JWT_COOKIE_NAME},
+};
 
+// The token is taken from the body, or from `Authorization: Bearer` which then takes its place.
+// Personal access tokens are accepted either way.
 pub async fn verify_token(
     State(state): State<AppState>,
-    Json(request): Json<LoginRequest>
-) -> Result<impl IntoResponse, AuthAPIError> {
+    headers: HeaderMap,
+    jar: CookieJar,
+    request: Result<Json<LoginRequest>, JsonRejection>,
+) -> Result<Response, AuthAPIError> {
+    let (token, audience) = match (bearer_token(&headers), request) {
+        // The bearer middleware has put the token in the cookie, already swapped for a JWT if it was a personal access token
+        (Some(_), Ok(Json(request))) => (jar.get(JWT_COOKIE_NAME).map(|cookie| cookie.value().to_owned()), request.audience),
+        (Some(_), Err(_)) => (jar.get(JWT_COOKIE_NAME).map(|cookie| cookie.value().to_owned()), None),
+        (None, Ok(Json(request))) => (Some(request.token), request.audience),
+        (None, Err(rejection)) => return Ok(rejection.into_response()),
+    };
+    let token = token.ok_or(AuthAPIError::MissingToken)?;
 
-    if validate_token(&request.token, state.banned_token_store).await.is_err() {
-        return Err(AuthAPIError::InvalidToken);
-    }
+    // Services pass their audience to accept the tokens exchanged for them as well
+    let claims = if token.starts_with(PERSONAL_ACCESS_TOKEN_PREFIX) {
+        validate_personal_access_token(&token, &state).await?
+    } else {
+        match &audience {
+            Some(audience) => validate_token_for_audience(&token, state.banned_token_store, audience).await,
+            None => validate_token(&token, state.banned_token_store).await,
+        }
+        .map_err(|_| AuthAPIError::InvalidToken)?
+    };
 
-    Ok(StatusCode::OK.into_response())
+    // Other services authorize their users with the organization, roles and permissions of the token
+    let response = Json(VerifyTokenResponse {
+        tenant: claims.tenant.to_string(),
+        roles: claims.roles,
+        permissions: claims.permissions,
+        act: claims.act,
+    });
+
+    Ok((StatusCode::OK, response).into_response())
 }
 
 #[derive(Deserialize)]
 pub struct LoginRequest {
-    pub token: String
-}
\ No newline at end of file
+    pub token: String,
+    #[serde(default)]
+    pub audience: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct VerifyTokenResponse {
+    pub tenant: String,
+    pub roles: Vec<String>,
+    pub permissions: Vec<String>,
+    // Set when an admin is impersonating the user, or to the client the token was exchanged for
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub act: Option<Actor>,
+}
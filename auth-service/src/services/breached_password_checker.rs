@@ -0,0 +1,157 @@
+use std::{f64::consts::LN_2, fs, path::Path};
+
+use sha1::{Digest, Sha1};
+
+use crate::domain::{BreachedPasswordChecker, Password};
+
+const FILTER_MAGIC: &[u8; 4] = b"PWBF";
+const HEADER_LENGTH: usize = FILTER_MAGIC.len() + 4 + 8;
+
+pub type PasswordDigest = [u8; 20];
+
+// Bloom filter over the SHA-1 digests of breached passwords, the same hashes Pwned Passwords publishes.
+// On disk it is the magic bytes, the number of hash functions (u32 LE), the number of bits (u64 LE) and the bits.
+pub struct BloomFilter {
+    bits: Vec<u8>,
+    num_bits: u64,
+    num_hashes: u32,
+}
+
+impl BloomFilter {
+    pub fn new(expected_items: usize, false_positive_rate: f64) -> Self {
+        let items = expected_items.max(1) as f64;
+        let num_bits = (-items * false_positive_rate.ln() / (LN_2 * LN_2)).ceil().max(8.0) as u64;
+        let num_hashes = (num_bits as f64 / items * LN_2).round().max(1.0) as u32;
+
+        Self {
+            bits: vec![0; num_bits.div_ceil(8) as usize],
+            num_bits,
+            num_hashes,
+        }
+    }
+
+    pub fn insert(&mut self, digest: &PasswordDigest) {
+        for index in self.bit_indexes(digest) {
+            self.bits[(index / 8) as usize] |= 1 << (index % 8);
+        }
+    }
+
+    pub fn contains(&self, digest: &PasswordDigest) -> bool {
+        self.bit_indexes(digest).all(|index| self.bits[(index / 8) as usize] & (1 << (index % 8)) != 0)
+    }
+
+    // SHA-1 output is already uniformly distributed, so two halves of it are enough for double hashing
+    fn bit_indexes(&self, digest: &PasswordDigest) -> impl Iterator<Item = u64> {
+        let h1 = u64::from_le_bytes(digest[0..8].try_into().expect("digest is 20 bytes"));
+        let h2 = u64::from_le_bytes(digest[8..16].try_into().expect("digest is 20 bytes")) | 1;
+        let num_bits = self.num_bits;
+
+        (0..self.num_hashes as u64).map(move |i| h1.wrapping_add(i.wrapping_mul(h2)) % num_bits)
+    }
+
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(HEADER_LENGTH + self.bits.len());
+        bytes.extend_from_slice(FILTER_MAGIC);
+        bytes.extend_from_slice(&self.num_hashes.to_le_bytes());
+        bytes.extend_from_slice(&self.num_bits.to_le_bytes());
+        bytes.extend_from_slice(&self.bits);
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, String> {
+        if bytes.len() < HEADER_LENGTH || &bytes[0..4] != FILTER_MAGIC {
+            return Err("Not a breached passwords filter".to_string());
+        }
+
+        let num_hashes = u32::from_le_bytes(bytes[4..8].try_into().map_err(|_| "Invalid filter header".to_string())?);
+        let num_bits = u64::from_le_bytes(bytes[8..16].try_into().map_err(|_| "Invalid filter header".to_string())?);
+        let bits = bytes[HEADER_LENGTH..].to_vec();
+
+        if num_hashes == 0 || num_bits == 0 || bits.len() as u64 != num_bits.div_ceil(8) {
+            return Err("Breached passwords filter is truncated or corrupted".to_string());
+        }
+
+        Ok(Self { bits, num_bits, num_hashes })
+    }
+}
+
+pub fn password_digest(password: &str) -> PasswordDigest {
+    Sha1::digest(password.as_bytes()).into()
+}
+
+// Lines of a Pwned Passwords dump look like "<SHA-1 hex>:<count>"
+pub fn parse_hash_line(line: &str) -> Option<PasswordDigest> {
+    let hash = line.split(':').next()?.trim();
+    let mut digest = [0; 20];
+    hex::decode_to_slice(hash, &mut digest).ok()?;
+    Some(digest)
+}
+
+pub struct BloomFilterBreachedPasswordChecker {
+    filter: BloomFilter,
+}
+
+impl BloomFilterBreachedPasswordChecker {
+    pub fn new(filter: BloomFilter) -> Self {
+        Self { filter }
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, String> {
+        let bytes = fs::read(path.as_ref())
+            .map_err(|e| format!("Failed to read {}: {}", path.as_ref().display(), e))?;
+
+        Ok(Self::new(BloomFilter::from_bytes(&bytes)?))
+    }
+}
+
+impl BreachedPasswordChecker for BloomFilterBreachedPasswordChecker {
+    fn is_breached(&self, password: &Password) -> bool {
+        self.filter.contains(&password_digest(password.as_ref()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build_filter(passwords: &[&str]) -> BloomFilter {
+        let mut filter = BloomFilter::new(passwords.len(), 0.001);
+        for password in passwords {
+            filter.insert(&password_digest(password));
+        }
+        filter
+    }
+
+    #[test]
+    fn test_breached_passwords_are_found() {
+        let checker = BloomFilterBreachedPasswordChecker::new(build_filter(&["Tr0ub4dor&3", "correct horse battery staple"]));
+
+        let breached = Password::parse("Tr0ub4dor&3".to_string()).unwrap();
+        let safe = Password::parse("S3cure-Passw0rd!".to_string()).unwrap();
+
+        assert!(checker.is_breached(&breached));
+        assert!(!checker.is_breached(&safe));
+    }
+
+    #[test]
+    fn test_filter_survives_serialization() {
+        let filter = build_filter(&["Tr0ub4dor&3"]);
+        let filter = BloomFilter::from_bytes(&filter.to_bytes()).unwrap();
+
+        assert!(filter.contains(&password_digest("Tr0ub4dor&3")));
+
+        let mut truncated = build_filter(&["Tr0ub4dor&3"]).to_bytes();
+        truncated.pop();
+        assert!(BloomFilter::from_bytes(&truncated).is_err());
+        assert!(BloomFilter::from_bytes(b"not a filter").is_err());
+    }
+
+    #[test]
+    fn test_parse_hash_line() {
+        // SHA-1 of "password"
+        let digest = parse_hash_line("5BAA61E4C9B93F3F0682250B6CF8331B7EE68FD8:10434004").unwrap();
+        assert_eq!(digest, password_digest("password"));
+
+        assert!(parse_hash_line("not a hash").is_none());
+    }
+}
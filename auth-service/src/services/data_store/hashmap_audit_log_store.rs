@@ -0,0 +1,72 @@
+use std::collections::HashMap;
+
+use chrono::Utc;
+
+use crate::domain::{
+    data_store::{AuditEntry, AuditEvent, AuditLogStore, AuditLogStoreError},
+    UserId,
+};
+
+#[derive(Default)]
+pub struct HashmapAuditLogStore {
+    entries: HashMap<UserId, Vec<AuditEntry>>,
+}
+
+#[async_trait::async_trait]
+impl AuditLogStore for HashmapAuditLogStore {
+    async fn add_entry(&mut self, user_id: &UserId, event: AuditEvent) -> Result<(), AuditLogStoreError> {
+        let entry = AuditEntry {
+            event: event.as_ref().to_owned(),
+            created_at: Utc::now(),
+            actor_id: None,
+        };
+
+        self.entries.entry(*user_id).or_default().push(entry);
+
+        Ok(())
+    }
+
+    async fn add_entry_by_actor(&mut self, user_id: &UserId, actor_id: &UserId, event: AuditEvent) -> Result<(), AuditLogStoreError> {
+        let entry = AuditEntry {
+            event: event.as_ref().to_owned(),
+            created_at: Utc::now(),
+            actor_id: Some(*actor_id),
+        };
+
+        self.entries.entry(*user_id).or_default().push(entry);
+
+        Ok(())
+    }
+
+    async fn get_entries(&self, user_id: &UserId) -> Result<Vec<AuditEntry>, AuditLogStoreError> {
+        Ok(self.entries.get(user_id).cloned().unwrap_or_default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_audit_log_store() {
+        let mut audit_log_store = HashmapAuditLogStore::default();
+
+        let user_id = UserId::default();
+
+        audit_log_store.add_entry(&user_id, AuditEvent::Signup).await.unwrap();
+        audit_log_store.add_entry(&user_id, AuditEvent::Login).await.unwrap();
+
+        let entries = audit_log_store.get_entries(&user_id).await.unwrap();
+        assert_eq!(2, entries.len());
+        assert_eq!("signup", entries[0].event);
+        assert_eq!("login", entries[1].event);
+        assert_eq!(None, entries[1].actor_id);
+
+        let admin_id = UserId::default();
+        audit_log_store.add_entry_by_actor(&user_id, &admin_id, AuditEvent::Impersonated).await.unwrap();
+        assert_eq!(Some(admin_id), audit_log_store.get_entries(&user_id).await.unwrap()[2].actor_id);
+
+        let another_user_id = UserId::default();
+        assert!(audit_log_store.get_entries(&another_user_id).await.unwrap().is_empty());
+    }
+}
@@ -0,0 +1,64 @@
+use std::collections::HashMap;
+
+use crate::domain::{
+    data_store::{EmailChangeStore, EmailChangeStoreError, EmailChangeToken},
+    email::Email,
+};
+
+#[derive(Default)]
+pub struct HashmapEmailChangeStore {
+    requests: HashMap<Email, (Email, EmailChangeToken, EmailChangeToken)>,
+}
+
+#[async_trait::async_trait]
+impl EmailChangeStore for HashmapEmailChangeStore {
+    async fn add_request(&mut self, email: Email, new_email: Email, confirmation_token: EmailChangeToken, cancel_token: EmailChangeToken) -> Result<(), EmailChangeStoreError> {
+        self.requests.insert(email, (new_email, confirmation_token, cancel_token));
+
+        Ok(())
+    }
+
+    async fn remove_request(&mut self, email: &Email) -> Result<(), EmailChangeStoreError> {
+        self.requests.remove(email).ok_or(EmailChangeStoreError::RequestNotFound).map(|_| ())
+    }
+
+    async fn get_request(&self, email: &Email) -> Result<(Email, EmailChangeToken, EmailChangeToken), EmailChangeStoreError> {
+        match self.requests.get(email) {
+            Some(result) => Ok(result.clone()),
+            None => Err(EmailChangeStoreError::RequestNotFound),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fake::Fake;
+    use fake::faker::internet::en::SafeEmail;
+    use super::*;
+
+    #[tokio::test]
+    async fn test_email_change_store() {
+        let mut email_change_store = HashmapEmailChangeStore::default();
+
+        let email = Email::parse(SafeEmail().fake()).unwrap();
+        let new_email = Email::parse(SafeEmail().fake()).unwrap();
+        let confirmation_token = EmailChangeToken::default();
+        let cancel_token = EmailChangeToken::default();
+
+        let add_result = email_change_store.add_request(email.clone(), new_email.clone(), confirmation_token.clone(), cancel_token.clone()).await;
+        assert!(add_result.is_ok());
+        assert_eq!(email_change_store.requests.len(), 1);
+
+        let get_result = email_change_store.get_request(&email).await.unwrap();
+        assert_eq!(new_email, get_result.0);
+        assert_eq!(confirmation_token, get_result.1);
+        assert_eq!(cancel_token, get_result.2);
+
+        let get_with_wrong_email_result = email_change_store.get_request(&new_email).await;
+        assert_eq!(EmailChangeStoreError::RequestNotFound, get_with_wrong_email_result.unwrap_err());
+
+        let remove_result = email_change_store.remove_request(&email).await;
+        assert!(remove_result.is_ok());
+        assert_eq!(email_change_store.requests.len(), 0);
+    }
+}
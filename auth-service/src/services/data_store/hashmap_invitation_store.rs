@@ -0,0 +1,153 @@
+use std::collections::HashMap;
+
+use chrono::Utc;
+
+use crate::domain::{
+    data_store::{InvitationStore, InvitationStoreError},
+    Email, Invitation, InvitationId, InvitationStatus, InvitationToken, TenantId,
+};
+
+// Roles are not known here, so invitations can carry any role
+#[derive(Default)]
+pub struct HashmapInvitationStore {
+    invitations: HashMap<InvitationId, Invitation>,
+}
+
+impl HashmapInvitationStore {
+    fn get_pending(&mut self, id: &InvitationId) -> Result<&mut Invitation, InvitationStoreError> {
+        self.invitations
+            .get_mut(id)
+            .filter(|invitation| invitation.status(Utc::now()) == InvitationStatus::Pending)
+            .ok_or(InvitationStoreError::InvitationNotFound)
+    }
+}
+
+#[async_trait::async_trait]
+impl InvitationStore for HashmapInvitationStore {
+    async fn add_invitation(&mut self, invitation: Invitation) -> Result<(), InvitationStoreError> {
+        let now = Utc::now();
+        for existing in self.invitations.values_mut() {
+            if existing.tenant_id == invitation.tenant_id && existing.email == invitation.email && existing.status(now) == InvitationStatus::Pending {
+                existing.revoked_at = Some(now);
+            }
+        }
+
+        self.invitations.insert(invitation.id, invitation);
+        Ok(())
+    }
+
+    async fn get_invitation(&self, id: &InvitationId) -> Result<Invitation, InvitationStoreError> {
+        self.invitations.get(id).cloned().ok_or(InvitationStoreError::InvitationNotFound)
+    }
+
+    async fn get_invitation_by_token(&self, token: &InvitationToken) -> Result<Invitation, InvitationStoreError> {
+        self.invitations
+            .values()
+            .find(|invitation| invitation.token_hash == token.hash())
+            .cloned()
+            .ok_or(InvitationStoreError::InvitationNotFound)
+    }
+
+    async fn list_invitations(&self, tenant_id: &TenantId) -> Result<Vec<Invitation>, InvitationStoreError> {
+        let mut invitations: Vec<Invitation> = self.invitations
+            .values()
+            .filter(|invitation| invitation.tenant_id == *tenant_id)
+            .cloned()
+            .collect();
+        invitations.sort_by_key(|invitation| std::cmp::Reverse(invitation.created_at));
+
+        Ok(invitations)
+    }
+
+    async fn list_pending_invitations(&self, email: &Email) -> Result<Vec<Invitation>, InvitationStoreError> {
+        let now = Utc::now();
+        let mut invitations: Vec<Invitation> = self.invitations
+            .values()
+            .filter(|invitation| invitation.email == *email && invitation.status(now) == InvitationStatus::Pending)
+            .cloned()
+            .collect();
+        invitations.sort_by_key(|invitation| std::cmp::Reverse(invitation.created_at));
+
+        Ok(invitations)
+    }
+
+    async fn accept_invitation(&mut self, id: &InvitationId) -> Result<(), InvitationStoreError> {
+        self.get_pending(id)?.accepted_at = Some(Utc::now());
+        Ok(())
+    }
+
+    async fn release_invitation(&mut self, id: &InvitationId) -> Result<(), InvitationStoreError> {
+        match self.invitations.get_mut(id) {
+            Some(invitation) if invitation.accepted_at.is_some() => {
+                invitation.accepted_at = None;
+                Ok(())
+            }
+            _ => Err(InvitationStoreError::InvitationNotFound),
+        }
+    }
+
+    async fn revoke_invitation(&mut self, id: &InvitationId) -> Result<(), InvitationStoreError> {
+        self.get_pending(id)?.revoked_at = Some(Utc::now());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use super::*;
+    use crate::domain::{Email, UserId};
+
+    fn invitation(email: &str, ttl: Duration) -> Invitation {
+        let email = Email::parse(email.to_string()).unwrap();
+        Invitation::new(TenantId::default(), email, vec!["admin".to_string()], UserId::default(), ttl).0
+    }
+
+    #[tokio::test]
+    async fn test_invitations_are_used_once() {
+        let mut invitation_store = HashmapInvitationStore::default();
+        let email = Email::parse("alice@example.com".to_string()).unwrap();
+        let (invitation, token) = Invitation::new(TenantId::default(), email, Vec::new(), UserId::default(), Duration::hours(1));
+        invitation_store.add_invitation(invitation.clone()).await.unwrap();
+
+        assert_eq!(invitation, invitation_store.get_invitation_by_token(&token).await.unwrap());
+        assert_eq!(
+            Err(InvitationStoreError::InvitationNotFound),
+            invitation_store.get_invitation_by_token(&InvitationToken::default()).await,
+        );
+
+        // A released claim can be accepted again
+        invitation_store.accept_invitation(&invitation.id).await.unwrap();
+        invitation_store.release_invitation(&invitation.id).await.unwrap();
+        assert_eq!(Err(InvitationStoreError::InvitationNotFound), invitation_store.release_invitation(&invitation.id).await);
+
+        invitation_store.accept_invitation(&invitation.id).await.unwrap();
+        assert_eq!(Err(InvitationStoreError::InvitationNotFound), invitation_store.accept_invitation(&invitation.id).await);
+        assert_eq!(Err(InvitationStoreError::InvitationNotFound), invitation_store.revoke_invitation(&invitation.id).await);
+
+        let accepted = invitation_store.get_invitation(&invitation.id).await.unwrap();
+        assert_eq!(InvitationStatus::Accepted, accepted.status(Utc::now()));
+    }
+
+    #[tokio::test]
+    async fn test_expired_and_replaced_invitations_cannot_be_accepted() {
+        let mut invitation_store = HashmapInvitationStore::default();
+        let expired = invitation("alice@example.com", Duration::zero());
+        invitation_store.add_invitation(expired.clone()).await.unwrap();
+        assert_eq!(Err(InvitationStoreError::InvitationNotFound), invitation_store.accept_invitation(&expired.id).await);
+
+        let first = invitation("bob@example.com", Duration::hours(1));
+        let second = invitation("bob@example.com", Duration::hours(1));
+        invitation_store.add_invitation(first.clone()).await.unwrap();
+        invitation_store.add_invitation(second.clone()).await.unwrap();
+
+        let first = invitation_store.get_invitation(&first.id).await.unwrap();
+        assert_eq!(InvitationStatus::Revoked, first.status(Utc::now()));
+        assert_eq!(InvitationStatus::Expired, invitation_store.get_invitation(&expired.id).await.unwrap().status(Utc::now()));
+
+        invitation_store.accept_invitation(&second.id).await.unwrap();
+        assert_eq!(3, invitation_store.list_invitations(&TenantId::default()).await.unwrap().len());
+        assert!(invitation_store.list_invitations(&TenantId::new()).await.unwrap().is_empty());
+    }
+}
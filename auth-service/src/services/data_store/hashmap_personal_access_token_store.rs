@@ -0,0 +1,105 @@
+use std::collections::HashMap;
+
+use chrono::Utc;
+
+use crate::domain::{
+    data_store::{PersonalAccessTokenStore, PersonalAccessTokenStoreError},
+    PersonalAccessToken, PersonalAccessTokenId, PersonalAccessTokenStatus, UserId,
+};
+
+#[derive(Default)]
+pub struct HashmapPersonalAccessTokenStore {
+    tokens: HashMap<PersonalAccessTokenId, PersonalAccessToken>,
+}
+
+#[async_trait::async_trait]
+impl PersonalAccessTokenStore for HashmapPersonalAccessTokenStore {
+    async fn add_token(&mut self, token: PersonalAccessToken) -> Result<(), PersonalAccessTokenStoreError> {
+        self.tokens.insert(token.id, token);
+        Ok(())
+    }
+
+    async fn get_token_by_prefix(&self, prefix: &str) -> Result<PersonalAccessToken, PersonalAccessTokenStoreError> {
+        self.tokens
+            .values()
+            .find(|token| token.prefix == prefix)
+            .cloned()
+            .ok_or(PersonalAccessTokenStoreError::TokenNotFound)
+    }
+
+    async fn list_tokens(&self, user_id: &UserId) -> Result<Vec<PersonalAccessToken>, PersonalAccessTokenStoreError> {
+        let mut tokens: Vec<PersonalAccessToken> = self.tokens
+            .values()
+            .filter(|token| token.user_id == *user_id)
+            .cloned()
+            .collect();
+        tokens.sort_by_key(|token| std::cmp::Reverse(token.created_at));
+
+        Ok(tokens)
+    }
+
+    async fn revoke_token(&mut self, user_id: &UserId, id: &PersonalAccessTokenId) -> Result<(), PersonalAccessTokenStoreError> {
+        let token = self.tokens
+            .get_mut(id)
+            .filter(|token| token.user_id == *user_id && token.status(Utc::now()) == PersonalAccessTokenStatus::Active)
+            .ok_or(PersonalAccessTokenStoreError::TokenNotFound)?;
+
+        token.revoked_at = Some(Utc::now());
+        Ok(())
+    }
+
+    async fn revoke_user_tokens(&mut self, user_id: &UserId) -> Result<(), PersonalAccessTokenStoreError> {
+        let now = Utc::now();
+        for token in self.tokens.values_mut() {
+            if token.user_id == *user_id && token.status(now) == PersonalAccessTokenStatus::Active {
+                token.revoked_at = Some(now);
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn record_use(&mut self, id: &PersonalAccessTokenId) -> Result<(), PersonalAccessTokenStoreError> {
+        let token = self.tokens.get_mut(id).ok_or(PersonalAccessTokenStoreError::TokenNotFound)?;
+
+        token.last_used_at = Some(Utc::now());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::Duration;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_tokens_are_revoked_once_by_their_owner() {
+        let mut token_store = HashmapPersonalAccessTokenStore::default();
+        let user_id = UserId::default();
+        let (token, _) = PersonalAccessToken::new(user_id, "ci".to_string(), vec![], Duration::days(1));
+        token_store.add_token(token.clone()).await.unwrap();
+
+        assert_eq!(token, token_store.get_token_by_prefix(&token.prefix).await.unwrap());
+        assert_eq!(vec![token.clone()], token_store.list_tokens(&user_id).await.unwrap());
+        assert!(token_store.list_tokens(&UserId::default()).await.unwrap().is_empty());
+
+        assert_eq!(Err(PersonalAccessTokenStoreError::TokenNotFound), token_store.revoke_token(&UserId::default(), &token.id).await);
+        token_store.revoke_token(&user_id, &token.id).await.unwrap();
+        assert_eq!(Err(PersonalAccessTokenStoreError::TokenNotFound), token_store.revoke_token(&user_id, &token.id).await);
+
+        let revoked = token_store.get_token_by_prefix(&token.prefix).await.unwrap();
+        assert_eq!(PersonalAccessTokenStatus::Revoked, revoked.status(Utc::now()));
+    }
+
+    #[tokio::test]
+    async fn test_record_use() {
+        let mut token_store = HashmapPersonalAccessTokenStore::default();
+        let (token, _) = PersonalAccessToken::new(UserId::default(), "ci".to_string(), vec![], Duration::days(1));
+        token_store.add_token(token.clone()).await.unwrap();
+
+        token_store.record_use(&token.id).await.unwrap();
+        assert!(token_store.get_token_by_prefix(&token.prefix).await.unwrap().last_used_at.is_some());
+        assert_eq!(Err(PersonalAccessTokenStoreError::TokenNotFound), token_store.record_use(&PersonalAccessTokenId::new()).await);
+    }
+}
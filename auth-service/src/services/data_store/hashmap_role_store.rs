@@ -0,0 +1,93 @@
+use std::collections::{BTreeSet, HashMap};
+
+use crate::domain::{
+    data_store::{RoleStore, RoleStoreError},
+    UserId, UserRoles, ADMIN_ROLE, MANAGE_ROLES_PERMISSION, MANAGE_TENANT_PERMISSION, MANAGE_USERS_PERMISSION, READ_METRICS_PERMISSION,
+};
+
+// Users are not known here, so roles can be assigned to any user id
+pub struct HashmapRoleStore {
+    role_permissions: HashMap<String, Vec<String>>,
+    user_roles: HashMap<UserId, BTreeSet<String>>,
+}
+
+impl Default for HashmapRoleStore {
+    // The same roles the migrations create
+    fn default() -> Self {
+        let role_permissions = HashMap::from([(
+            ADMIN_ROLE.to_string(),
+            vec![
+                MANAGE_ROLES_PERMISSION.to_string(),
+                MANAGE_TENANT_PERMISSION.to_string(),
+                MANAGE_USERS_PERMISSION.to_string(),
+                READ_METRICS_PERMISSION.to_string(),
+            ],
+        )]);
+
+        Self { role_permissions, user_roles: HashMap::new() }
+    }
+}
+
+#[async_trait::async_trait]
+impl RoleStore for HashmapRoleStore {
+    async fn get_user_roles(&self, user_id: &UserId) -> Result<UserRoles, RoleStoreError> {
+        let roles = self.user_roles.get(user_id).cloned().unwrap_or_default();
+        let permissions: BTreeSet<String> = roles
+            .iter()
+            .filter_map(|role| self.role_permissions.get(role))
+            .flatten()
+            .cloned()
+            .collect();
+
+        Ok(UserRoles {
+            roles: roles.into_iter().collect(),
+            permissions: permissions.into_iter().collect(),
+        })
+    }
+
+    async fn assign_role(&mut self, user_id: &UserId, role: &str) -> Result<(), RoleStoreError> {
+        if !self.role_permissions.contains_key(role) {
+            return Err(RoleStoreError::RoleNotFound);
+        }
+
+        self.user_roles.entry(*user_id).or_default().insert(role.to_string());
+        Ok(())
+    }
+
+    async fn revoke_role(&mut self, user_id: &UserId, role: &str) -> Result<(), RoleStoreError> {
+        let revoked = self.user_roles
+            .get_mut(user_id)
+            .is_some_and(|roles| roles.remove(role));
+
+        match revoked {
+            true => Ok(()),
+            false => Err(RoleStoreError::RoleNotAssigned),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_role_store() {
+        let mut role_store = HashmapRoleStore::default();
+        let user_id = UserId::default();
+
+        assert_eq!(UserRoles::default(), role_store.get_user_roles(&user_id).await.unwrap());
+
+        role_store.assign_role(&user_id, ADMIN_ROLE).await.unwrap();
+        role_store.assign_role(&user_id, ADMIN_ROLE).await.unwrap();
+
+        let user_roles = role_store.get_user_roles(&user_id).await.unwrap();
+        assert_eq!(vec![ADMIN_ROLE.to_string()], user_roles.roles);
+        assert!(user_roles.has_permission(MANAGE_ROLES_PERMISSION));
+
+        assert_eq!(RoleStoreError::RoleNotFound, role_store.assign_role(&user_id, "unknown").await.unwrap_err());
+
+        role_store.revoke_role(&user_id, ADMIN_ROLE).await.unwrap();
+        assert_eq!(UserRoles::default(), role_store.get_user_roles(&user_id).await.unwrap());
+        assert_eq!(RoleStoreError::RoleNotAssigned, role_store.revoke_role(&user_id, ADMIN_ROLE).await.unwrap_err());
+    }
+}
@@ -0,0 +1,101 @@
+use std::collections::HashMap;
+
+use crate::domain::{
+    data_store::{TenantStore, TenantStoreError},
+    Tenant, TenantId, TenantSettings, TenantSlug, DEFAULT_TENANT_SLUG,
+};
+
+pub struct HashmapTenantStore {
+    tenants: HashMap<TenantId, Tenant>,
+}
+
+impl Default for HashmapTenantStore {
+    // The default organization the migrations create
+    fn default() -> Self {
+        let default_tenant = Tenant {
+            id: TenantId::default(),
+            slug: TenantSlug::parse(DEFAULT_TENANT_SLUG.to_string()).expect("Invalid default tenant slug"),
+            name: "Default".to_string(),
+            host: None,
+            settings: TenantSettings::default(),
+        };
+
+        Self { tenants: HashMap::from([(default_tenant.id, default_tenant)]) }
+    }
+}
+
+#[async_trait::async_trait]
+impl TenantStore for HashmapTenantStore {
+    async fn add_tenant(&mut self, tenant: Tenant) -> Result<(), TenantStoreError> {
+        let taken = self.tenants.values().any(|existing| {
+            existing.id == tenant.id || existing.slug == tenant.slug || (tenant.host.is_some() && existing.host == tenant.host)
+        });
+        if taken {
+            return Err(TenantStoreError::TenantAlreadyExists);
+        }
+
+        self.tenants.insert(tenant.id, tenant);
+        Ok(())
+    }
+
+    async fn get_tenant(&self, id: &TenantId) -> Result<Tenant, TenantStoreError> {
+        self.tenants.get(id).cloned().ok_or(TenantStoreError::TenantNotFound)
+    }
+
+    async fn get_tenant_by_slug(&self, slug: &str) -> Result<Tenant, TenantStoreError> {
+        self.tenants
+            .values()
+            .find(|tenant| tenant.slug.as_ref() == slug)
+            .cloned()
+            .ok_or(TenantStoreError::TenantNotFound)
+    }
+
+    async fn get_tenant_by_host(&self, host: &str) -> Result<Tenant, TenantStoreError> {
+        self.tenants
+            .values()
+            .find(|tenant| tenant.host.as_deref() == Some(host))
+            .cloned()
+            .ok_or(TenantStoreError::TenantNotFound)
+    }
+
+    async fn update_settings(&mut self, id: &TenantId, settings: TenantSettings) -> Result<(), TenantStoreError> {
+        let tenant = self.tenants.get_mut(id).ok_or(TenantStoreError::TenantNotFound)?;
+        tenant.settings = settings;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tenant(slug: &str, host: Option<&str>) -> Tenant {
+        Tenant::new(TenantSlug::parse(slug.to_string()).unwrap(), slug.to_string(), host.map(str::to_string))
+    }
+
+    #[tokio::test]
+    async fn test_tenant_store() {
+        let mut tenant_store = HashmapTenantStore::default();
+        let default_tenant = tenant_store.get_tenant_by_slug(DEFAULT_TENANT_SLUG).await.unwrap();
+        assert_eq!(default_tenant.id, TenantId::default());
+
+        let acme = tenant("acme", Some("login.acme.test"));
+        tenant_store.add_tenant(acme.clone()).await.unwrap();
+        assert_eq!(acme, tenant_store.get_tenant(&acme.id).await.unwrap());
+        assert_eq!(acme, tenant_store.get_tenant_by_host("login.acme.test").await.unwrap());
+
+        let result = tenant_store.add_tenant(tenant("acme", None)).await;
+        assert_eq!(Err(TenantStoreError::TenantAlreadyExists), result);
+        let result = tenant_store.add_tenant(tenant("other", Some("login.acme.test"))).await;
+        assert_eq!(Err(TenantStoreError::TenantAlreadyExists), result);
+        tenant_store.add_tenant(tenant("other", None)).await.unwrap();
+
+        let settings = TenantSettings { requires_2fa: true, ..TenantSettings::default() };
+        tenant_store.update_settings(&acme.id, settings.clone()).await.unwrap();
+        assert_eq!(settings, tenant_store.get_tenant_by_slug("acme").await.unwrap().settings);
+
+        assert_eq!(Err(TenantStoreError::TenantNotFound), tenant_store.get_tenant_by_slug("unknown").await);
+        assert_eq!(Err(TenantStoreError::TenantNotFound), tenant_store.get_tenant_by_host("unknown.test").await);
+        assert_eq!(Err(TenantStoreError::TenantNotFound), tenant_store.get_tenant(&TenantId::new()).await);
+    }
+}
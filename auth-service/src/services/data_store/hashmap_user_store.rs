@@ -1,52 +1,333 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
-use crate::domain::{Email, Password, User};
+use chrono::{DateTime, Utc};
+
+use crate::app_state::PasswordHasherType;
+use crate::domain::{Email, Password, PasswordHasherError, TenantId, User, UserAccount, UserId};
 use crate::domain::data_store::{UserStore, UserStoreError};
 
-#[derive(Default)]
+// Users keep the hash of their password, like in every other store
 pub struct HashmapUserStore {
-    users: HashMap<Email, User>
+    users: HashMap<Email, User>,
+    scheduled_deletions: HashMap<Email, DateTime<Utc>>,
+    password_history: HashMap<UserId, Vec<Password>>,
+    password_changed_at: HashMap<UserId, DateTime<Utc>>,
+    locked: HashSet<UserId>,
+    password_reset_required: HashSet<UserId>,
+    approval_pending: HashSet<UserId>,
+    password_hasher: PasswordHasherType,
+}
+
+impl HashmapUserStore {
+    pub fn new(password_hasher: PasswordHasherType) -> Self {
+        Self {
+            users: HashMap::new(),
+            scheduled_deletions: HashMap::new(),
+            password_history: HashMap::new(),
+            password_changed_at: HashMap::new(),
+            locked: HashSet::new(),
+            password_reset_required: HashSet::new(),
+            approval_pending: HashSet::new(),
+            password_hasher,
+        }
+    }
+
+    fn account_of(&self, user: &User) -> Result<UserAccount, UserStoreError> {
+        let user_id = user.get_id();
+
+        Ok(UserAccount {
+            user: user.clone(),
+            locked: self.locked.contains(&user_id),
+            password_reset_required: self.password_reset_required.contains(&user_id),
+            approval_pending: self.approval_pending.contains(&user_id),
+            password_changed_at: self.password_changed_at.get(&user_id).copied().ok_or(UserStoreError::UnexpectedError)?,
+            deletion_scheduled_at: self.scheduled_deletions.get(&user.get_email()).copied(),
+        })
+    }
 }
 
 #[async_trait::async_trait]
 impl UserStore for HashmapUserStore {
-    async fn add_user(&mut self, user: User) -> Result<(), UserStoreError> {       
+    async fn add_user(&mut self, user: User) -> Result<(), UserStoreError> {
+        // Hashed before the lookup, so taken emails cost the same time as new ones
+        let password_hash = self.password_hasher.hash_password(&user.get_password()).await?;
         if self.users.contains_key(&user.get_email()) {
             return Err(UserStoreError::UserAlreadyExists);
         }
 
+        let password_hash = Password::parse(password_hash).map_err(|_| UserStoreError::UnexpectedError)?;
+        let user = User::with_id(user.get_id(), user.get_email(), password_hash, user.use_requires_2fa())
+            .with_tenant(user.get_tenant_id());
+        self.password_changed_at.insert(user.get_id(), Utc::now());
         self.users.insert(user.get_email(), user);
 
         Ok(())
     }
 
-    async fn get_user(&self, email: &Email) -> Result<User, UserStoreError> {   
+    async fn add_pending_user(&mut self, user: User) -> Result<(), UserStoreError> {
+        let user_id = user.get_id();
+        self.add_user(user).await?;
+        self.approval_pending.insert(user_id);
+
+        Ok(())
+    }
+
+    async fn approve_user(&mut self, email: &Email) -> Result<(), UserStoreError> {
+        let user_id = self.users.get(email).ok_or(UserStoreError::UserNotFound)?.get_id();
+        self.approval_pending.remove(&user_id);
+
+        Ok(())
+    }
+
+    async fn get_user(&self, tenant_id: &TenantId, email: &Email) -> Result<User, UserStoreError> {
+        let user = self.get_user_by_email(email).await?;
+
+        if user.get_tenant_id() != *tenant_id {
+            return Err(UserStoreError::UserNotFound);
+        }
+
+        Ok(user)
+    }
+
+    async fn get_user_by_email(&self, email: &Email) -> Result<User, UserStoreError> {
+        if self.scheduled_deletions.contains_key(email) {
+            return Err(UserStoreError::UserNotFound);
+        }
+
         match self.users.get(email) {
             Some(user) => Ok(user.clone()),
             None => Err(UserStoreError::UserNotFound)
         }
     }
 
-    async fn validate_user(&self, email: &Email, password: &Password) -> Result<(), UserStoreError> {
+    async fn get_email_tenant(&self, email: &Email) -> Result<TenantId, UserStoreError> {
         match self.users.get(email) {
+            Some(user) => Ok(user.get_tenant_id()),
+            None => Err(UserStoreError::UserNotFound)
+        }
+    }
+
+    async fn get_user_by_id(&self, id: &UserId) -> Result<User, UserStoreError> {
+        match self.users.values().find(|user| &user.get_id() == id) {
+            Some(user) => self.get_user_by_email(&user.get_email()).await,
+            None => Err(UserStoreError::UserNotFound)
+        }
+    }
+
+    async fn validate_user(&self, tenant_id: &TenantId, email: &Email, password: &Password) -> Result<(), UserStoreError> {
+        // Unknown emails must not answer faster than wrong passwords, or timing would reveal registered accounts
+        let user = match self.get_user(tenant_id, email).await {
+            Ok(user) => user,
+            Err(e) => {
+                self.password_hasher.verify_dummy_password(password).await?;
+                return Err(e);
+            }
+        };
+
+        // Hashes here are always made with the current settings, so unlike stored ones they never need an upgrade
+        self.password_hasher.verify_password(user.get_password().as_ref(), password).await?;
+
+        if self.locked.contains(&user.get_id()) {
+            return Err(UserStoreError::AccountLocked);
+        }
+
+        if self.approval_pending.contains(&user.get_id()) {
+            return Err(UserStoreError::ApprovalPending);
+        }
+
+        Ok(())
+    }
+
+    async fn update_password(&mut self, email: &Email, password: Password) -> Result<(), UserStoreError> {
+        let password_hash = self.password_hasher.hash_password(&password).await?;
+        let password = Password::parse(password_hash).map_err(|_| UserStoreError::UnexpectedError)?;
+
+        match self.users.get_mut(email) {
             Some(user) => {
-                if user.get_password().eq(password) {
-                    Ok(())
-                } else {
-                    Err(UserStoreError::InvalidCredentials)
-                }
+                self.password_history.entry(user.get_id()).or_default().push(user.get_password());
+                self.password_changed_at.insert(user.get_id(), Utc::now());
+                self.password_reset_required.remove(&user.get_id());
+                *user = User::with_id(user.get_id(), user.get_email(), password, user.use_requires_2fa())
+                    .with_tenant(user.get_tenant_id());
+                Ok(())
             }
             None => Err(UserStoreError::UserNotFound),
         }
     }
+
+    async fn is_recent_password(&self, email: &Email, password: &Password, count: usize) -> Result<bool, UserStoreError> {
+        let user = self.get_user_by_email(email).await?;
+        let history = self.password_history.get(&user.get_id()).map(Vec::as_slice).unwrap_or_default();
+
+        for previous in std::iter::once(&user.get_password()).chain(history.iter().rev()).take(count) {
+            match self.password_hasher.verify_password(previous.as_ref(), password).await {
+                Ok(()) => return Ok(true),
+                Err(PasswordHasherError::IncorrectPassword) => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+
+        Ok(false)
+    }
+
+    async fn get_password_history_count(&self, id: &UserId) -> Result<u64, UserStoreError> {
+        Ok(self.password_history.get(id).map_or(0, Vec::len) as u64)
+    }
+
+    async fn get_password_changed_at(&self, email: &Email) -> Result<DateTime<Utc>, UserStoreError> {
+        let user = self.get_user_by_email(email).await?;
+        self.password_changed_at.get(&user.get_id()).copied().ok_or(UserStoreError::UnexpectedError)
+    }
+
+    async fn update_email(&mut self, email: &Email, new_email: Email) -> Result<(), UserStoreError> {
+        if self.users.contains_key(&new_email) {
+            return Err(UserStoreError::UserAlreadyExists);
+        }
+
+        match self.users.remove(email) {
+            Some(user) => {
+                let user = User::with_id(user.get_id(), new_email.clone(), user.get_password(), user.use_requires_2fa())
+                    .with_tenant(user.get_tenant_id());
+                self.users.insert(new_email, user);
+                Ok(())
+            }
+            None => Err(UserStoreError::UserNotFound),
+        }
+    }
+
+    async fn update_requires_2fa(&mut self, email: &Email, requires_2fa: bool) -> Result<(), UserStoreError> {
+        match self.users.get_mut(email) {
+            Some(user) => {
+                *user = User::with_id(user.get_id(), user.get_email(), user.get_password(), requires_2fa)
+                    .with_tenant(user.get_tenant_id());
+                Ok(())
+            }
+            None => Err(UserStoreError::UserNotFound),
+        }
+    }
+
+    async fn delete_user(&mut self, email: &Email) -> Result<(), UserStoreError> {
+        self.scheduled_deletions.remove(email);
+        self.users.remove(email).ok_or(UserStoreError::UserNotFound).map(|_| ())
+    }
+
+    async fn schedule_user_deletion(&mut self, email: &Email, delete_at: DateTime<Utc>) -> Result<(), UserStoreError> {
+        if !self.users.contains_key(email) {
+            return Err(UserStoreError::UserNotFound);
+        }
+
+        self.scheduled_deletions.insert(email.clone(), delete_at);
+        Ok(())
+    }
+
+    async fn cancel_user_deletion(&mut self, tenant_id: &TenantId, email: &Email, password: &Password) -> Result<User, UserStoreError> {
+        let scheduled_user = self.users
+            .get(email)
+            .filter(|user| user.get_tenant_id() == *tenant_id)
+            .filter(|_| self.scheduled_deletions.get(email).is_some_and(|delete_at| *delete_at > Utc::now()));
+
+        // Answers as slowly for accounts that are not scheduled for deletion as for wrong passwords
+        let Some(user) = scheduled_user.cloned() else {
+            self.password_hasher.verify_dummy_password(password).await?;
+            return Err(UserStoreError::UserNotFound);
+        };
+
+        self.password_hasher.verify_password(user.get_password().as_ref(), password).await?;
+        self.scheduled_deletions.remove(email);
+
+        Ok(user)
+    }
+
+    async fn delete_scheduled_users(&mut self) -> Result<u64, UserStoreError> {
+        let now = Utc::now();
+        let expired: Vec<Email> = self.scheduled_deletions
+            .iter()
+            .filter(|(_, delete_at)| **delete_at <= now)
+            .map(|(email, _)| email.clone())
+            .collect();
+
+        for email in expired.iter() {
+            self.delete_user(email).await?;
+        }
+
+        Ok(expired.len() as u64)
+    }
+
+    async fn list_users(&self, tenant_id: &TenantId, search: Option<&str>, pending_only: bool, offset: u64, limit: u64) -> Result<(Vec<UserAccount>, u64), UserStoreError> {
+        let search = search.map(str::to_lowercase);
+        let mut users: Vec<&User> = self.users
+            .values()
+            .filter(|user| user.get_tenant_id() == *tenant_id)
+            .filter(|user| !pending_only || self.approval_pending.contains(&user.get_id()))
+            .filter(|user| match &search {
+                Some(search) => user.get_email().as_ref().to_lowercase().contains(search),
+                None => true,
+            })
+            .collect();
+        users.sort_by_key(|user| user.get_email().as_ref().to_owned());
+
+        let total = users.len() as u64;
+        let accounts = users
+            .into_iter()
+            .skip(offset as usize)
+            .take(limit as usize)
+            .map(|user| self.account_of(user))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok((accounts, total))
+    }
+
+    async fn get_user_account(&self, id: &UserId) -> Result<UserAccount, UserStoreError> {
+        match self.users.values().find(|user| &user.get_id() == id) {
+            Some(user) => self.account_of(user),
+            None => Err(UserStoreError::UserNotFound),
+        }
+    }
+
+    async fn set_locked(&mut self, email: &Email, locked: bool) -> Result<(), UserStoreError> {
+        let user_id = self.users.get(email).ok_or(UserStoreError::UserNotFound)?.get_id();
+
+        if locked {
+            self.locked.insert(user_id);
+        } else {
+            self.locked.remove(&user_id);
+        }
+
+        Ok(())
+    }
+
+    async fn require_password_reset(&mut self, email: &Email) -> Result<(), UserStoreError> {
+        let user_id = self.users.get(email).ok_or(UserStoreError::UserNotFound)?.get_id();
+        self.password_reset_required.insert(user_id);
+
+        Ok(())
+    }
+
+    async fn update_tenant(&mut self, email: &Email, tenant_id: &TenantId) -> Result<(), UserStoreError> {
+        let user = self.users.get_mut(email).ok_or(UserStoreError::UserNotFound)?;
+        *user = user.clone().with_tenant(*tenant_id);
+
+        Ok(())
+    }
 }
 
 
 #[cfg(test)]
 mod tests {
+    use std::sync::Arc;
+
+    use argon2::Params;
     use fake::{faker::internet::en::SafeEmail, Fake};
+    use crate::services::{hashing_pool::HashingPool, password_hasher::Argon2PasswordHasher};
     use super::*;
 
+    // Cheap parameters keep the tests fast, the store does not depend on them
+    fn user_store() -> HashmapUserStore {
+        let params = Params::new(1024, 1, 1, None).unwrap();
+        let hashing_pool = Arc::new(HashingPool::new(2, 16));
+        HashmapUserStore::new(Arc::new(Argon2PasswordHasher::new(params, Vec::new(), hashing_pool)))
+    }
+
     #[tokio::test]
     async fn test_add_user() {   
         let email = Email::parse(SafeEmail().fake()).unwrap();
@@ -54,12 +335,17 @@ mod tests {
 
         let user = User::new(email.clone(), password.clone(), true);
 
-        let mut hashmap_user_store = HashmapUserStore::default();
+        let mut hashmap_user_store = user_store();
         let result = hashmap_user_store.add_user(user).await;
 
         assert!(result.is_ok());
         assert_eq!(1, hashmap_user_store.users.len());
 
+        // Only the hash of the password is kept
+        let stored_password = hashmap_user_store.get_user(&TenantId::default(), &email).await.unwrap().get_password();
+        assert_ne!(password, stored_password);
+        assert!(stored_password.as_ref().starts_with("$argon2id$"));
+
         let same_user = User::new(email, password, true);
 
         let result = hashmap_user_store.add_user(same_user);
@@ -73,19 +359,37 @@ mod tests {
 
         let user = User::new(email.clone(), password.clone(), true);
 
-        let mut hashmap_user_store = HashmapUserStore::default();
+        let mut hashmap_user_store = user_store();
         hashmap_user_store.add_user(user).await;
 
-        let user1 = hashmap_user_store.get_user(&email).await;
+        let user1 = hashmap_user_store.get_user(&TenantId::default(), &email).await;
         assert!(user1.is_ok());
         assert_eq!(&email, &user1.unwrap().get_email());
 
         let another_email = Email::parse(SafeEmail().fake()).unwrap();
-        let user2 = hashmap_user_store.get_user(&another_email).await;
+        let user2 = hashmap_user_store.get_user(&TenantId::default(), &another_email).await;
         assert!(user2.is_err());
         assert_eq!(UserStoreError::UserNotFound, user2.unwrap_err());
     }
 
+    #[tokio::test]
+    async fn test_get_user_by_id() {
+        let email = Email::parse(SafeEmail().fake()).unwrap();
+        let password = Password::parse("12345678".to_string()).unwrap();
+
+        let user = User::new(email.clone(), password, true);
+        let user_id = user.get_id();
+
+        let mut hashmap_user_store = user_store();
+        hashmap_user_store.add_user(user).await.unwrap();
+
+        let user = hashmap_user_store.get_user_by_id(&user_id).await.unwrap();
+        assert_eq!(email, user.get_email());
+
+        let result = hashmap_user_store.get_user_by_id(&UserId::default()).await;
+        assert_eq!(UserStoreError::UserNotFound, result.unwrap_err());
+    }
+
     #[tokio::test]
     async fn test_validate_user() {
         let email = Email::parse(SafeEmail().fake()).unwrap();
@@ -93,22 +397,276 @@ mod tests {
 
         let user = User::new(email.clone(), password.clone(), true);
         
-        let mut hashmap_user_store = HashmapUserStore::default();
+        let mut hashmap_user_store = user_store();
         hashmap_user_store.add_user(user).await;
 
         assert_eq!(1, hashmap_user_store.users.len());
 
-        let result = hashmap_user_store.validate_user(&email, &password).await;
+        let result = hashmap_user_store.validate_user(&TenantId::default(), &email, &password).await;
         assert!(result.is_ok());
         
         let wrong_password = Password::parse("87654321".to_string()).unwrap();
-        let result = hashmap_user_store.validate_user(&email, &wrong_password).await;
+        let result = hashmap_user_store.validate_user(&TenantId::default(), &email, &wrong_password).await;
         assert!(result.is_err());
         assert_eq!(UserStoreError::InvalidCredentials, result.unwrap_err());
 
         let wrong_email = Email::parse(SafeEmail().fake()).unwrap();
-        let result = hashmap_user_store.validate_user(&wrong_email, &password).await;
+        let result = hashmap_user_store.validate_user(&TenantId::default(), &wrong_email, &password).await;
         assert!(result.is_err());
         assert_eq!(UserStoreError::UserNotFound, result.unwrap_err());
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_update_password() {
+        let email = Email::parse(SafeEmail().fake()).unwrap();
+        let password = Password::parse("12345678".to_string()).unwrap();
+
+        let user = User::new(email.clone(), password.clone(), true);
+
+        let mut hashmap_user_store = user_store();
+        hashmap_user_store.add_user(user).await.unwrap();
+
+        let new_password = Password::parse("87654321".to_string()).unwrap();
+        let result = hashmap_user_store.update_password(&email, new_password.clone()).await;
+        assert!(result.is_ok());
+
+        let result = hashmap_user_store.validate_user(&TenantId::default(), &email, &password).await;
+        assert_eq!(UserStoreError::InvalidCredentials, result.unwrap_err());
+
+        let result = hashmap_user_store.validate_user(&TenantId::default(), &email, &new_password).await;
+        assert!(result.is_ok());
+
+        let wrong_email = Email::parse(SafeEmail().fake()).unwrap();
+        let result = hashmap_user_store.update_password(&wrong_email, new_password).await;
+        assert_eq!(UserStoreError::UserNotFound, result.unwrap_err());
+    }
+
+    #[tokio::test]
+    async fn test_is_recent_password() {
+        let email = Email::parse(SafeEmail().fake()).unwrap();
+        let passwords: Vec<Password> = ["password_1", "password_2", "password_3"]
+            .iter()
+            .map(|password| Password::parse(password.to_string()).unwrap())
+            .collect();
+
+        let mut hashmap_user_store = user_store();
+        hashmap_user_store.add_user(User::new(email.clone(), passwords[0].clone(), false)).await.unwrap();
+        let changed_at = hashmap_user_store.get_password_changed_at(&email).await.unwrap();
+
+        hashmap_user_store.update_password(&email, passwords[1].clone()).await.unwrap();
+        hashmap_user_store.update_password(&email, passwords[2].clone()).await.unwrap();
+        assert!(hashmap_user_store.get_password_changed_at(&email).await.unwrap() >= changed_at);
+
+        // The current password counts as the first of the last passwords
+        assert!(hashmap_user_store.is_recent_password(&email, &passwords[2], 1).await.unwrap());
+        assert!(!hashmap_user_store.is_recent_password(&email, &passwords[1], 1).await.unwrap());
+        assert!(hashmap_user_store.is_recent_password(&email, &passwords[1], 2).await.unwrap());
+        assert!(!hashmap_user_store.is_recent_password(&email, &passwords[0], 2).await.unwrap());
+        assert!(hashmap_user_store.is_recent_password(&email, &passwords[0], 3).await.unwrap());
+        assert!(!hashmap_user_store.is_recent_password(&email, &passwords[0], 0).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_update_email() {
+        let email = Email::parse(SafeEmail().fake()).unwrap();
+        let password = Password::parse("12345678".to_string()).unwrap();
+
+        let user = User::new(email.clone(), password.clone(), true);
+        let user_id = user.get_id();
+
+        let mut hashmap_user_store = user_store();
+        hashmap_user_store.add_user(user).await.unwrap();
+
+        let taken_email = Email::parse(SafeEmail().fake()).unwrap();
+        hashmap_user_store.add_user(User::new(taken_email.clone(), password.clone(), false)).await.unwrap();
+
+        let result = hashmap_user_store.update_email(&email, taken_email).await;
+        assert_eq!(UserStoreError::UserAlreadyExists, result.unwrap_err());
+
+        let new_email = Email::parse(SafeEmail().fake()).unwrap();
+        let result = hashmap_user_store.update_email(&email, new_email.clone()).await;
+        assert!(result.is_ok());
+
+        assert_eq!(UserStoreError::UserNotFound, hashmap_user_store.get_user(&TenantId::default(), &email).await.unwrap_err());
+
+        let user = hashmap_user_store.get_user(&TenantId::default(), &new_email).await.unwrap();
+        assert!(user.use_requires_2fa());
+        assert_eq!(user_id, user.get_id());
+        assert!(hashmap_user_store.validate_user(&TenantId::default(), &new_email, &password).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_delete_user() {
+        let email = Email::parse(SafeEmail().fake()).unwrap();
+        let password = Password::parse("12345678".to_string()).unwrap();
+
+        let mut hashmap_user_store = user_store();
+        hashmap_user_store.add_user(User::new(email.clone(), password, true)).await.unwrap();
+
+        assert!(hashmap_user_store.delete_user(&email).await.is_ok());
+        assert_eq!(0, hashmap_user_store.users.len());
+        assert_eq!(UserStoreError::UserNotFound, hashmap_user_store.delete_user(&email).await.unwrap_err());
+    }
+
+    #[tokio::test]
+    async fn test_schedule_user_deletion() {
+        let email = Email::parse(SafeEmail().fake()).unwrap();
+        let password = Password::parse("12345678".to_string()).unwrap();
+
+        let mut hashmap_user_store = user_store();
+        hashmap_user_store.add_user(User::new(email.clone(), password.clone(), true)).await.unwrap();
+
+        let delete_at = Utc::now() + chrono::Duration::days(30);
+        hashmap_user_store.schedule_user_deletion(&email, delete_at).await.unwrap();
+
+        assert_eq!(UserStoreError::UserNotFound, hashmap_user_store.get_user(&TenantId::default(), &email).await.unwrap_err());
+        assert_eq!(UserStoreError::UserNotFound, hashmap_user_store.validate_user(&TenantId::default(), &email, &password).await.unwrap_err());
+
+        assert_eq!(0, hashmap_user_store.delete_scheduled_users().await.unwrap());
+        assert_eq!(1, hashmap_user_store.users.len());
+
+        let wrong_password = Password::parse("87654321".to_string()).unwrap();
+        assert!(hashmap_user_store.cancel_user_deletion(&TenantId::default(), &email, &wrong_password).await.is_err());
+        assert!(hashmap_user_store.cancel_user_deletion(&TenantId::new(), &email, &password).await.is_err());
+        hashmap_user_store.cancel_user_deletion(&TenantId::default(), &email, &password).await.unwrap();
+        assert!(hashmap_user_store.get_user(&TenantId::default(), &email).await.is_ok());
+        assert_eq!(UserStoreError::UserNotFound, hashmap_user_store.cancel_user_deletion(&TenantId::default(), &email, &password).await.unwrap_err());
+
+        hashmap_user_store.schedule_user_deletion(&email, Utc::now()).await.unwrap();
+        assert_eq!(1, hashmap_user_store.delete_scheduled_users().await.unwrap());
+        assert_eq!(0, hashmap_user_store.users.len());
+    }
+
+    #[tokio::test]
+    async fn test_update_requires_2fa() {
+        let email = Email::parse(SafeEmail().fake()).unwrap();
+        let password = Password::parse("12345678".to_string()).unwrap();
+
+        let mut hashmap_user_store = user_store();
+        hashmap_user_store.add_user(User::new(email.clone(), password, false)).await.unwrap();
+
+        hashmap_user_store.update_requires_2fa(&email, true).await.unwrap();
+        assert!(hashmap_user_store.get_user(&TenantId::default(), &email).await.unwrap().use_requires_2fa());
+
+        hashmap_user_store.update_requires_2fa(&email, false).await.unwrap();
+        assert!(!hashmap_user_store.get_user(&TenantId::default(), &email).await.unwrap().use_requires_2fa());
+
+        let wrong_email = Email::parse(SafeEmail().fake()).unwrap();
+        let result = hashmap_user_store.update_requires_2fa(&wrong_email, true).await;
+        assert_eq!(UserStoreError::UserNotFound, result.unwrap_err());
+    }
+
+    #[tokio::test]
+    async fn test_list_users() {
+        let password = Password::parse("12345678".to_string()).unwrap();
+
+        let mut hashmap_user_store = user_store();
+        for email in ["carol@example.com", "alice@example.com", "bob@example.org"] {
+            let user = User::new(Email::parse(email.to_string()).unwrap(), password.clone(), false);
+            hashmap_user_store.add_user(user).await.unwrap();
+        }
+
+        let (accounts, total) = hashmap_user_store.list_users(&TenantId::default(), None, false, 1, 10).await.unwrap();
+        assert_eq!(3, total);
+        let emails: Vec<String> = accounts.iter().map(|account| account.user.get_email().as_ref().to_owned()).collect();
+        assert_eq!(vec!["bob@example.org", "carol@example.com"], emails);
+
+        let (accounts, total) = hashmap_user_store.list_users(&TenantId::default(), Some("EXAMPLE.COM"), false, 0, 1).await.unwrap();
+        assert_eq!(2, total);
+        assert_eq!("alice@example.com", accounts[0].user.get_email().as_ref());
+    }
+
+    #[tokio::test]
+    async fn test_lookups_are_scoped_to_the_tenant() {
+        let email = Email::parse(SafeEmail().fake()).unwrap();
+        let password = Password::parse("12345678".to_string()).unwrap();
+        let tenant_id = TenantId::new();
+
+        let mut hashmap_user_store = user_store();
+        hashmap_user_store.add_user(User::new(email.clone(), password.clone(), false).with_tenant(tenant_id)).await.unwrap();
+
+        assert_eq!(tenant_id, hashmap_user_store.get_user(&tenant_id, &email).await.unwrap().get_tenant_id());
+        assert_eq!(UserStoreError::UserNotFound, hashmap_user_store.get_user(&TenantId::default(), &email).await.unwrap_err());
+        assert!(hashmap_user_store.validate_user(&tenant_id, &email, &password).await.is_ok());
+        assert_eq!(UserStoreError::UserNotFound, hashmap_user_store.validate_user(&TenantId::default(), &email, &password).await.unwrap_err());
+
+        assert_eq!(1, hashmap_user_store.list_users(&tenant_id, None, false, 0, 10).await.unwrap().1);
+        assert_eq!(0, hashmap_user_store.list_users(&TenantId::default(), None, false, 0, 10).await.unwrap().1);
+
+        hashmap_user_store.update_tenant(&email, &TenantId::default()).await.unwrap();
+        assert!(hashmap_user_store.validate_user(&TenantId::default(), &email, &password).await.is_ok());
+        assert_eq!(0, hashmap_user_store.list_users(&tenant_id, None, false, 0, 10).await.unwrap().1);
+
+        // Emails stay unique across organizations
+        let result = hashmap_user_store.add_user(User::new(email, password, false)).await;
+        assert_eq!(UserStoreError::UserAlreadyExists, result.unwrap_err());
+    }
+
+    #[tokio::test]
+    async fn test_locked_account() {
+        let email = Email::parse(SafeEmail().fake()).unwrap();
+        let password = Password::parse("12345678".to_string()).unwrap();
+
+        let mut hashmap_user_store = user_store();
+        let user = User::new(email.clone(), password.clone(), false);
+        let user_id = user.get_id();
+        hashmap_user_store.add_user(user).await.unwrap();
+
+        hashmap_user_store.set_locked(&email, true).await.unwrap();
+        assert!(hashmap_user_store.get_user_account(&user_id).await.unwrap().locked);
+
+        // The lock is only revealed to someone who knows the password
+        let wrong_password = Password::parse("87654321".to_string()).unwrap();
+        assert_eq!(UserStoreError::InvalidCredentials, hashmap_user_store.validate_user(&TenantId::default(), &email, &wrong_password).await.unwrap_err());
+        assert_eq!(UserStoreError::AccountLocked, hashmap_user_store.validate_user(&TenantId::default(), &email, &password).await.unwrap_err());
+
+        hashmap_user_store.set_locked(&email, false).await.unwrap();
+        assert!(hashmap_user_store.validate_user(&TenantId::default(), &email, &password).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_pending_approval() {
+        let email = Email::parse(SafeEmail().fake()).unwrap();
+        let password = Password::parse("12345678".to_string()).unwrap();
+
+        let mut hashmap_user_store = user_store();
+        let user = User::new(email.clone(), password.clone(), false);
+        let user_id = user.get_id();
+        hashmap_user_store.add_pending_user(user).await.unwrap();
+        hashmap_user_store.add_user(User::new(Email::parse(SafeEmail().fake()).unwrap(), password.clone(), false)).await.unwrap();
+
+        assert!(hashmap_user_store.get_user_account(&user_id).await.unwrap().approval_pending);
+        let wrong_password = Password::parse("87654321".to_string()).unwrap();
+        assert_eq!(UserStoreError::InvalidCredentials, hashmap_user_store.validate_user(&TenantId::default(), &email, &wrong_password).await.unwrap_err());
+        assert_eq!(UserStoreError::ApprovalPending, hashmap_user_store.validate_user(&TenantId::default(), &email, &password).await.unwrap_err());
+
+        let (accounts, total) = hashmap_user_store.list_users(&TenantId::default(), None, true, 0, 10).await.unwrap();
+        assert_eq!(1, total);
+        assert_eq!(user_id, accounts[0].user.get_id());
+
+        hashmap_user_store.approve_user(&email).await.unwrap();
+        assert!(hashmap_user_store.validate_user(&TenantId::default(), &email, &password).await.is_ok());
+        assert_eq!(0, hashmap_user_store.list_users(&TenantId::default(), None, true, 0, 10).await.unwrap().1);
+    }
+
+    #[tokio::test]
+    async fn test_require_password_reset() {
+        let email = Email::parse(SafeEmail().fake()).unwrap();
+        let password = Password::parse("12345678".to_string()).unwrap();
+
+        let mut hashmap_user_store = user_store();
+        let user = User::new(email.clone(), password, false);
+        let user_id = user.get_id();
+        hashmap_user_store.add_user(user).await.unwrap();
+
+        hashmap_user_store.require_password_reset(&email).await.unwrap();
+        assert!(hashmap_user_store.get_user_account(&user_id).await.unwrap().password_reset_required);
+
+        let new_password = Password::parse("87654321".to_string()).unwrap();
+        hashmap_user_store.update_password(&email, new_password).await.unwrap();
+        assert!(!hashmap_user_store.get_user_account(&user_id).await.unwrap().password_reset_required);
+
+        let wrong_email = Email::parse(SafeEmail().fake()).unwrap();
+        assert_eq!(UserStoreError::UserNotFound, hashmap_user_store.require_password_reset(&wrong_email).await.unwrap_err());
+    }
+}
@@ -1,10 +1,13 @@
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+
+use chrono::{DateTime, Utc};
 
 use crate::domain::data_store::{BannedTokenStore, BannedTokenStoreError};
 
 #[derive(Default)]
 pub struct HashsetBannedTokenStore {
-    tokens: HashSet<String>
+    tokens: HashSet<String>,
+    users_banned_at: HashMap<String, i64>
 }
 
 #[async_trait::async_trait]
@@ -18,6 +21,18 @@ impl BannedTokenStore for HashsetBannedTokenStore {
         let result = self.tokens.contains(token);
         Ok(result)
     }
+
+    async fn ban_user_tokens(&mut self, sub: &str) -> Result<(), BannedTokenStoreError> {
+        self.users_banned_at.insert(sub.to_owned(), Utc::now().timestamp_micros());
+        Ok(())
+    }
+
+    async fn user_token_is_banned(&self, sub: &str, issued_at: DateTime<Utc>) -> Result<bool, BannedTokenStoreError> {
+        match self.users_banned_at.get(sub) {
+            Some(banned_at) => Ok(issued_at.timestamp_micros() < *banned_at),
+            None => Ok(false)
+        }
+    }
 }
 
 #[cfg(test)]
@@ -48,4 +63,23 @@ mod test {
 
         assert!(token_is_banned);
     }
+
+    #[tokio::test]
+    async fn banned_user_tokens() {
+        let mut banned_tokens_store = HashsetBannedTokenStore::default();
+
+        let sub = "user@example.com";
+        // Issued a moment before the ban, most likely within the same second
+        let issued_at = Utc::now() - chrono::Duration::milliseconds(1);
+
+        assert!(!banned_tokens_store.user_token_is_banned(sub, issued_at).await.unwrap());
+
+        banned_tokens_store.ban_user_tokens(sub).await.unwrap();
+
+        assert!(banned_tokens_store.user_token_is_banned(sub, issued_at).await.unwrap());
+        assert!(!banned_tokens_store.user_token_is_banned("another@example.com", issued_at).await.unwrap());
+
+        let issued_now = Utc::now();
+        assert!(!banned_tokens_store.user_token_is_banned(sub, issued_now).await.unwrap());
+    }
 }
\ No newline at end of file
@@ -1,6 +1,18 @@
 pub mod hashmap_user_store;
 pub mod hashset_token_store;
 pub mod hashmap_two_fa_code_store;
+pub mod hashmap_email_change_store;
+pub mod hashmap_audit_log_store;
+pub mod hashmap_role_store;
+pub mod hashmap_tenant_store;
+pub mod hashmap_invitation_store;
+pub mod hashmap_personal_access_token_store;
 pub mod postgres_user_store;
+pub mod postgres_audit_log_store;
+pub mod postgres_role_store;
+pub mod postgres_tenant_store;
+pub mod postgres_invitation_store;
+pub mod postgres_personal_access_token_store;
 pub mod redis_banned_token_store;
-pub mod redis_two_fa_code_store;
\ No newline at end of file
+pub mod redis_two_fa_code_store;
+pub mod redis_email_change_store;
\ No newline at end of file
@@ -0,0 +1,71 @@
+use sqlx::PgPool;
+
+use crate::domain::{
+    data_store::{AuditEntry, AuditEvent, AuditLogStore, AuditLogStoreError},
+    UserId,
+};
+
+pub struct PostgresAuditLogStore {
+    pool: PgPool,
+}
+
+impl PostgresAuditLogStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait::async_trait]
+impl AuditLogStore for PostgresAuditLogStore {
+    async fn add_entry(&mut self, user_id: &UserId, event: AuditEvent) -> Result<(), AuditLogStoreError> {
+        sqlx::query!(r#"
+            INSERT INTO audit_log (user_id, event)
+            VALUES ($1, $2)
+            "#,
+            user_id.as_uuid(),
+            event.as_ref()
+          )
+            .execute(&self.pool)
+            .await
+            .map_err(|_| AuditLogStoreError::UnexpectedError)?;
+
+        Ok(())
+    }
+
+    async fn add_entry_by_actor(&mut self, user_id: &UserId, actor_id: &UserId, event: AuditEvent) -> Result<(), AuditLogStoreError> {
+        sqlx::query!(r#"
+            INSERT INTO audit_log (user_id, actor_id, event)
+            VALUES ($1, $2, $3)
+            "#,
+            user_id.as_uuid(),
+            actor_id.as_uuid(),
+            event.as_ref()
+          )
+            .execute(&self.pool)
+            .await
+            .map_err(|_| AuditLogStoreError::UnexpectedError)?;
+
+        Ok(())
+    }
+
+    async fn get_entries(&self, user_id: &UserId) -> Result<Vec<AuditEntry>, AuditLogStoreError> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT event, created_at, actor_id FROM audit_log
+            WHERE user_id = $1
+            ORDER BY id
+            "#,
+            user_id.as_uuid()
+          )
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|_| AuditLogStoreError::UnexpectedError)?;
+
+        let entries = rows
+            .into_iter()
+            .map(|row| AuditEntry { event: row.event, created_at: row.created_at, actor_id: row.actor_id.map(Into::into) })
+            .collect();
+
+        Ok(entries)
+    }
+}
@@ -0,0 +1,229 @@
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+
+use crate::domain::{
+    data_store::{InvitationStore, InvitationStoreError},
+    Email, Invitation, InvitationId, InvitationToken, TenantId,
+};
+
+pub struct PostgresInvitationStore {
+    pool: PgPool,
+}
+
+impl PostgresInvitationStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+struct InvitationRow {
+    id: uuid::Uuid,
+    tenant_id: uuid::Uuid,
+    email: String,
+    token_hash: String,
+    invited_by: Option<uuid::Uuid>,
+    created_at: DateTime<Utc>,
+    expires_at: DateTime<Utc>,
+    accepted_at: Option<DateTime<Utc>>,
+    revoked_at: Option<DateTime<Utc>>,
+    roles: Vec<String>,
+}
+
+impl TryFrom<InvitationRow> for Invitation {
+    type Error = InvitationStoreError;
+
+    fn try_from(row: InvitationRow) -> Result<Self, Self::Error> {
+        Ok(Invitation {
+            id: row.id.into(),
+            tenant_id: row.tenant_id.into(),
+            email: Email::parse(row.email).map_err(|_| InvitationStoreError::UnexpectedError)?,
+            roles: row.roles,
+            token_hash: row.token_hash,
+            invited_by: row.invited_by.map(Into::into),
+            created_at: row.created_at,
+            expires_at: row.expires_at,
+            accepted_at: row.accepted_at,
+            revoked_at: row.revoked_at,
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl InvitationStore for PostgresInvitationStore {
+    async fn add_invitation(&mut self, invitation: Invitation) -> Result<(), InvitationStoreError> {
+        let mut transaction = self.pool.begin().await.map_err(|_| InvitationStoreError::UnexpectedError)?;
+
+        sqlx::query!(r#"
+            UPDATE invitations SET revoked_at = NOW()
+            WHERE tenant_id = $1 AND email = $2 AND accepted_at IS NULL AND revoked_at IS NULL AND expires_at > NOW()
+            "#,
+            invitation.tenant_id.as_uuid(),
+            invitation.email.as_ref()
+          )
+            .execute(&mut *transaction)
+            .await
+            .map_err(|_| InvitationStoreError::UnexpectedError)?;
+
+        sqlx::query!(r#"
+            INSERT INTO invitations (id, tenant_id, email, token_hash, invited_by, created_at, expires_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            "#,
+            invitation.id.as_uuid(),
+            invitation.tenant_id.as_uuid(),
+            invitation.email.as_ref(),
+            invitation.token_hash,
+            invitation.invited_by.map(|user_id| user_id.as_uuid()),
+            invitation.created_at,
+            invitation.expires_at
+          )
+            .execute(&mut *transaction)
+            .await
+            .map_err(|_| InvitationStoreError::UnexpectedError)?;
+
+        sqlx::query!(r#"
+            INSERT INTO invitation_roles (invitation_id, role)
+            SELECT $1, UNNEST($2::TEXT[])
+            "#,
+            invitation.id.as_uuid(),
+            &invitation.roles
+          )
+            .execute(&mut *transaction)
+            .await
+            .map_err(|e| match e {
+                sqlx::Error::Database(db_error) if db_error.constraint() == Some("invitation_roles_role_fkey") => InvitationStoreError::RoleNotFound,
+                _ => InvitationStoreError::UnexpectedError,
+            })?;
+
+        transaction.commit().await.map_err(|_| InvitationStoreError::UnexpectedError)
+    }
+
+    async fn get_invitation(&self, id: &InvitationId) -> Result<Invitation, InvitationStoreError> {
+        sqlx::query_as!(
+            InvitationRow,
+            r#"
+            SELECT id, tenant_id, email, token_hash, invited_by, created_at, expires_at, accepted_at, revoked_at,
+                ARRAY(SELECT role FROM invitation_roles WHERE invitation_id = invitations.id ORDER BY role) AS "roles!"
+            FROM invitations
+            WHERE id = $1
+            "#,
+            id.as_uuid()
+          )
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|_| InvitationStoreError::UnexpectedError)?
+            .ok_or(InvitationStoreError::InvitationNotFound)?
+            .try_into()
+    }
+
+    async fn get_invitation_by_token(&self, token: &InvitationToken) -> Result<Invitation, InvitationStoreError> {
+        sqlx::query_as!(
+            InvitationRow,
+            r#"
+            SELECT id, tenant_id, email, token_hash, invited_by, created_at, expires_at, accepted_at, revoked_at,
+                ARRAY(SELECT role FROM invitation_roles WHERE invitation_id = invitations.id ORDER BY role) AS "roles!"
+            FROM invitations
+            WHERE token_hash = $1
+            "#,
+            token.hash()
+          )
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|_| InvitationStoreError::UnexpectedError)?
+            .ok_or(InvitationStoreError::InvitationNotFound)?
+            .try_into()
+    }
+
+    async fn list_invitations(&self, tenant_id: &TenantId) -> Result<Vec<Invitation>, InvitationStoreError> {
+        sqlx::query_as!(
+            InvitationRow,
+            r#"
+            SELECT id, tenant_id, email, token_hash, invited_by, created_at, expires_at, accepted_at, revoked_at,
+                ARRAY(SELECT role FROM invitation_roles WHERE invitation_id = invitations.id ORDER BY role) AS "roles!"
+            FROM invitations
+            WHERE tenant_id = $1
+            ORDER BY created_at DESC
+            "#,
+            tenant_id.as_uuid()
+          )
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|_| InvitationStoreError::UnexpectedError)?
+            .into_iter()
+            .map(Invitation::try_from)
+            .collect()
+    }
+
+    async fn list_pending_invitations(&self, email: &Email) -> Result<Vec<Invitation>, InvitationStoreError> {
+        sqlx::query_as!(
+            InvitationRow,
+            r#"
+            SELECT id, tenant_id, email, token_hash, invited_by, created_at, expires_at, accepted_at, revoked_at,
+                ARRAY(SELECT role FROM invitation_roles WHERE invitation_id = invitations.id ORDER BY role) AS "roles!"
+            FROM invitations
+            WHERE email = $1 AND accepted_at IS NULL AND revoked_at IS NULL AND expires_at > NOW()
+            ORDER BY created_at DESC
+            "#,
+            email.as_ref()
+          )
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|_| InvitationStoreError::UnexpectedError)?
+            .into_iter()
+            .map(Invitation::try_from)
+            .collect()
+    }
+
+    async fn accept_invitation(&mut self, id: &InvitationId) -> Result<(), InvitationStoreError> {
+        let result = sqlx::query!(r#"
+            UPDATE invitations SET accepted_at = NOW()
+            WHERE id = $1 AND accepted_at IS NULL AND revoked_at IS NULL AND expires_at > NOW()
+            "#,
+            id.as_uuid()
+          )
+            .execute(&self.pool)
+            .await
+            .map_err(|_| InvitationStoreError::UnexpectedError)?;
+
+        if result.rows_affected() == 0 {
+            return Err(InvitationStoreError::InvitationNotFound);
+        }
+
+        Ok(())
+    }
+
+    async fn release_invitation(&mut self, id: &InvitationId) -> Result<(), InvitationStoreError> {
+        let result = sqlx::query!(r#"
+            UPDATE invitations SET accepted_at = NULL
+            WHERE id = $1 AND accepted_at IS NOT NULL
+            "#,
+            id.as_uuid()
+          )
+            .execute(&self.pool)
+            .await
+            .map_err(|_| InvitationStoreError::UnexpectedError)?;
+
+        if result.rows_affected() == 0 {
+            return Err(InvitationStoreError::InvitationNotFound);
+        }
+
+        Ok(())
+    }
+
+    async fn revoke_invitation(&mut self, id: &InvitationId) -> Result<(), InvitationStoreError> {
+        let result = sqlx::query!(r#"
+            UPDATE invitations SET revoked_at = NOW()
+            WHERE id = $1 AND accepted_at IS NULL AND revoked_at IS NULL AND expires_at > NOW()
+            "#,
+            id.as_uuid()
+          )
+            .execute(&self.pool)
+            .await
+            .map_err(|_| InvitationStoreError::UnexpectedError)?;
+
+        if result.rows_affected() == 0 {
+            return Err(InvitationStoreError::InvitationNotFound);
+        }
+
+        Ok(())
+    }
+}
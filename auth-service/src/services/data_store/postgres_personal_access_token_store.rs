@@ -0,0 +1,155 @@
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+
+use crate::domain::{
+    data_store::{PersonalAccessTokenStore, PersonalAccessTokenStoreError},
+    PersonalAccessToken, PersonalAccessTokenId, UserId,
+};
+
+pub struct PostgresPersonalAccessTokenStore {
+    pool: PgPool,
+}
+
+impl PostgresPersonalAccessTokenStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+struct PersonalAccessTokenRow {
+    id: uuid::Uuid,
+    user_id: uuid::Uuid,
+    name: String,
+    scopes: Vec<String>,
+    prefix: String,
+    token_hash: String,
+    created_at: DateTime<Utc>,
+    expires_at: DateTime<Utc>,
+    last_used_at: Option<DateTime<Utc>>,
+    revoked_at: Option<DateTime<Utc>>,
+}
+
+impl From<PersonalAccessTokenRow> for PersonalAccessToken {
+    fn from(row: PersonalAccessTokenRow) -> Self {
+        PersonalAccessToken {
+            id: row.id.into(),
+            user_id: row.user_id.into(),
+            name: row.name,
+            scopes: row.scopes,
+            prefix: row.prefix,
+            token_hash: row.token_hash,
+            created_at: row.created_at,
+            expires_at: row.expires_at,
+            last_used_at: row.last_used_at,
+            revoked_at: row.revoked_at,
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl PersonalAccessTokenStore for PostgresPersonalAccessTokenStore {
+    async fn add_token(&mut self, token: PersonalAccessToken) -> Result<(), PersonalAccessTokenStoreError> {
+        sqlx::query!(r#"
+            INSERT INTO personal_access_tokens (id, user_id, name, scopes, prefix, token_hash, created_at, expires_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            "#,
+            token.id.as_uuid(),
+            token.user_id.as_uuid(),
+            token.name,
+            &token.scopes,
+            token.prefix,
+            token.token_hash,
+            token.created_at,
+            token.expires_at
+          )
+            .execute(&self.pool)
+            .await
+            .map_err(|_| PersonalAccessTokenStoreError::UnexpectedError)?;
+
+        Ok(())
+    }
+
+    async fn get_token_by_prefix(&self, prefix: &str) -> Result<PersonalAccessToken, PersonalAccessTokenStoreError> {
+        sqlx::query_as!(
+            PersonalAccessTokenRow,
+            r#"
+            SELECT id, user_id, name, scopes, prefix, token_hash, created_at, expires_at, last_used_at, revoked_at
+            FROM personal_access_tokens
+            WHERE prefix = $1
+            "#,
+            prefix
+          )
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|_| PersonalAccessTokenStoreError::UnexpectedError)?
+            .map(PersonalAccessToken::from)
+            .ok_or(PersonalAccessTokenStoreError::TokenNotFound)
+    }
+
+    async fn list_tokens(&self, user_id: &UserId) -> Result<Vec<PersonalAccessToken>, PersonalAccessTokenStoreError> {
+        let rows = sqlx::query_as!(
+            PersonalAccessTokenRow,
+            r#"
+            SELECT id, user_id, name, scopes, prefix, token_hash, created_at, expires_at, last_used_at, revoked_at
+            FROM personal_access_tokens
+            WHERE user_id = $1
+            ORDER BY created_at DESC
+            "#,
+            user_id.as_uuid()
+          )
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|_| PersonalAccessTokenStoreError::UnexpectedError)?;
+
+        Ok(rows.into_iter().map(PersonalAccessToken::from).collect())
+    }
+
+    async fn revoke_token(&mut self, user_id: &UserId, id: &PersonalAccessTokenId) -> Result<(), PersonalAccessTokenStoreError> {
+        let result = sqlx::query!(r#"
+            UPDATE personal_access_tokens SET revoked_at = NOW()
+            WHERE id = $1 AND user_id = $2 AND revoked_at IS NULL AND expires_at > NOW()
+            "#,
+            id.as_uuid(),
+            user_id.as_uuid()
+          )
+            .execute(&self.pool)
+            .await
+            .map_err(|_| PersonalAccessTokenStoreError::UnexpectedError)?;
+
+        if result.rows_affected() == 0 {
+            return Err(PersonalAccessTokenStoreError::TokenNotFound);
+        }
+
+        Ok(())
+    }
+
+    async fn revoke_user_tokens(&mut self, user_id: &UserId) -> Result<(), PersonalAccessTokenStoreError> {
+        sqlx::query!(r#"
+            UPDATE personal_access_tokens SET revoked_at = NOW()
+            WHERE user_id = $1 AND revoked_at IS NULL AND expires_at > NOW()
+            "#,
+            user_id.as_uuid()
+          )
+            .execute(&self.pool)
+            .await
+            .map_err(|_| PersonalAccessTokenStoreError::UnexpectedError)?;
+
+        Ok(())
+    }
+
+    async fn record_use(&mut self, id: &PersonalAccessTokenId) -> Result<(), PersonalAccessTokenStoreError> {
+        let result = sqlx::query!(
+            "UPDATE personal_access_tokens SET last_used_at = NOW() WHERE id = $1",
+            id.as_uuid()
+          )
+            .execute(&self.pool)
+            .await
+            .map_err(|_| PersonalAccessTokenStoreError::UnexpectedError)?;
+
+        if result.rows_affected() == 0 {
+            return Err(PersonalAccessTokenStoreError::TokenNotFound);
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,85 @@
+use sqlx::PgPool;
+
+use crate::domain::{
+    data_store::{RoleStore, RoleStoreError},
+    UserId, UserRoles,
+};
+
+pub struct PostgresRoleStore {
+    pool: PgPool,
+}
+
+impl PostgresRoleStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait::async_trait]
+impl RoleStore for PostgresRoleStore {
+    async fn get_user_roles(&self, user_id: &UserId) -> Result<UserRoles, RoleStoreError> {
+        let roles = sqlx::query_scalar!(r#"
+            SELECT role FROM user_roles
+            WHERE user_id = $1
+            ORDER BY role
+            "#,
+            user_id.as_uuid()
+          )
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|_| RoleStoreError::UnexpectedError)?;
+
+        let permissions = sqlx::query_scalar!(r#"
+            SELECT DISTINCT role_permissions.permission FROM role_permissions
+            JOIN user_roles ON user_roles.role = role_permissions.role
+            WHERE user_roles.user_id = $1
+            ORDER BY role_permissions.permission
+            "#,
+            user_id.as_uuid()
+          )
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|_| RoleStoreError::UnexpectedError)?;
+
+        Ok(UserRoles { roles, permissions })
+    }
+
+    async fn assign_role(&mut self, user_id: &UserId, role: &str) -> Result<(), RoleStoreError> {
+        sqlx::query!(r#"
+            INSERT INTO user_roles (user_id, role)
+            VALUES ($1, $2)
+            ON CONFLICT DO NOTHING
+            "#,
+            user_id.as_uuid(),
+            role
+          )
+            .execute(&self.pool)
+            .await
+            .map_err(|e| match e {
+                sqlx::Error::Database(db_error) if db_error.constraint() == Some("user_roles_role_fkey") => RoleStoreError::RoleNotFound,
+                sqlx::Error::Database(db_error) if db_error.constraint() == Some("user_roles_user_id_fkey") => RoleStoreError::UserNotFound,
+                _ => RoleStoreError::UnexpectedError,
+            })?;
+
+        Ok(())
+    }
+
+    async fn revoke_role(&mut self, user_id: &UserId, role: &str) -> Result<(), RoleStoreError> {
+        let result = sqlx::query!(r#"
+            DELETE FROM user_roles
+            WHERE user_id = $1 AND role = $2
+            "#,
+            user_id.as_uuid(),
+            role
+          )
+            .execute(&self.pool)
+            .await
+            .map_err(|_| RoleStoreError::UnexpectedError)?;
+
+        if result.rows_affected() == 0 {
+            return Err(RoleStoreError::RoleNotAssigned);
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,173 @@
+use sqlx::PgPool;
+
+use crate::domain::{
+    data_store::{TenantStore, TenantStoreError},
+    PasswordPolicyOverrides, SignupMode, Tenant, TenantId, TenantSettings, TenantSlug,
+};
+
+pub struct PostgresTenantStore {
+    pool: PgPool,
+}
+
+impl PostgresTenantStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+struct TenantRow {
+    id: uuid::Uuid,
+    slug: String,
+    name: String,
+    host: Option<String>,
+    requires_2fa: bool,
+    allowed_email_domains: Vec<String>,
+    password_min_length: Option<i32>,
+    password_min_score: Option<i16>,
+    password_history_length: Option<i32>,
+    password_max_age_days: Option<i32>,
+    signup_mode: String,
+    signup_email_domains: Vec<String>,
+}
+
+impl TryFrom<TenantRow> for Tenant {
+    type Error = TenantStoreError;
+
+    fn try_from(row: TenantRow) -> Result<Self, Self::Error> {
+        let password_policy = PasswordPolicyOverrides {
+            min_length: row.password_min_length.map(usize::try_from).transpose().map_err(|_| TenantStoreError::UnexpectedError)?,
+            min_score: row.password_min_score.map(u8::try_from).transpose().map_err(|_| TenantStoreError::UnexpectedError)?,
+            history_length: row.password_history_length.map(usize::try_from).transpose().map_err(|_| TenantStoreError::UnexpectedError)?,
+            max_age_days: row.password_max_age_days.map(i64::from),
+        };
+
+        Ok(Tenant {
+            id: row.id.into(),
+            slug: TenantSlug::parse(row.slug).map_err(|_| TenantStoreError::UnexpectedError)?,
+            name: row.name,
+            host: row.host,
+            settings: TenantSettings {
+                requires_2fa: row.requires_2fa,
+                allowed_email_domains: row.allowed_email_domains,
+                password_policy,
+                signup_mode: SignupMode::parse(&row.signup_mode).map_err(|_| TenantStoreError::UnexpectedError)?,
+                signup_email_domains: row.signup_email_domains,
+            },
+        })
+    }
+}
+
+#[async_trait::async_trait]
+impl TenantStore for PostgresTenantStore {
+    async fn add_tenant(&mut self, tenant: Tenant) -> Result<(), TenantStoreError> {
+        sqlx::query!(r#"
+            INSERT INTO tenants (id, slug, name, host)
+            VALUES ($1, $2, $3, $4)
+            "#,
+            tenant.id.as_uuid(),
+            tenant.slug.as_ref(),
+            tenant.name,
+            tenant.host
+          )
+            .execute(&self.pool)
+            .await
+            .map_err(|e| match e {
+                sqlx::Error::Database(db_error) if db_error.is_unique_violation() => TenantStoreError::TenantAlreadyExists,
+                _ => TenantStoreError::UnexpectedError,
+            })?;
+
+        self.update_settings(&tenant.id, tenant.settings).await
+    }
+
+    async fn get_tenant(&self, id: &TenantId) -> Result<Tenant, TenantStoreError> {
+        sqlx::query_as!(
+            TenantRow,
+            r#"
+            SELECT id, slug, name, host, requires_2fa, allowed_email_domains,
+                password_min_length, password_min_score, password_history_length, password_max_age_days,
+                signup_mode, signup_email_domains
+            FROM tenants
+            WHERE id = $1
+            "#,
+            id.as_uuid()
+          )
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|_| TenantStoreError::UnexpectedError)?
+            .ok_or(TenantStoreError::TenantNotFound)?
+            .try_into()
+    }
+
+    async fn get_tenant_by_slug(&self, slug: &str) -> Result<Tenant, TenantStoreError> {
+        sqlx::query_as!(
+            TenantRow,
+            r#"
+            SELECT id, slug, name, host, requires_2fa, allowed_email_domains,
+                password_min_length, password_min_score, password_history_length, password_max_age_days,
+                signup_mode, signup_email_domains
+            FROM tenants
+            WHERE slug = $1
+            "#,
+            slug
+          )
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|_| TenantStoreError::UnexpectedError)?
+            .ok_or(TenantStoreError::TenantNotFound)?
+            .try_into()
+    }
+
+    async fn get_tenant_by_host(&self, host: &str) -> Result<Tenant, TenantStoreError> {
+        sqlx::query_as!(
+            TenantRow,
+            r#"
+            SELECT id, slug, name, host, requires_2fa, allowed_email_domains,
+                password_min_length, password_min_score, password_history_length, password_max_age_days,
+                signup_mode, signup_email_domains
+            FROM tenants
+            WHERE host = $1
+            "#,
+            host
+          )
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|_| TenantStoreError::UnexpectedError)?
+            .ok_or(TenantStoreError::TenantNotFound)?
+            .try_into()
+    }
+
+    async fn update_settings(&mut self, id: &TenantId, settings: TenantSettings) -> Result<(), TenantStoreError> {
+        let policy = settings.password_policy;
+        let min_length = policy.min_length.map(i32::try_from).transpose().map_err(|_| TenantStoreError::UnexpectedError)?;
+        let min_score = policy.min_score.map(i16::from);
+        let history_length = policy.history_length.map(i32::try_from).transpose().map_err(|_| TenantStoreError::UnexpectedError)?;
+        let max_age_days = policy.max_age_days.map(i32::try_from).transpose().map_err(|_| TenantStoreError::UnexpectedError)?;
+
+        let result = sqlx::query!(r#"
+            UPDATE tenants
+            SET requires_2fa = $2, allowed_email_domains = $3, password_min_length = $4,
+                password_min_score = $5, password_history_length = $6, password_max_age_days = $7,
+                signup_mode = $8, signup_email_domains = $9
+            WHERE id = $1
+            "#,
+            id.as_uuid(),
+            settings.requires_2fa,
+            &settings.allowed_email_domains,
+            min_length,
+            min_score,
+            history_length,
+            max_age_days,
+            settings.signup_mode.as_ref(),
+            &settings.signup_email_domains
+          )
+            .execute(&self.pool)
+            .await
+            .map_err(|_| TenantStoreError::UnexpectedError)?;
+
+        if result.rows_affected() == 0 {
+            return Err(TenantStoreError::TenantNotFound);
+        }
+
+        Ok(())
+    }
+}
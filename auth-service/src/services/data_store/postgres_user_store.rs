@@ -1,42 +1,89 @@
-use std::error::Error;
-
+use crate::app_state::PasswordHasherType;
 use crate::domain::{
     data_store::{UserStore, UserStoreError},
-    Email, Password, User,
+    Email, Password, PasswordHasherError, TenantId, User, UserAccount, UserId,
 };
-use argon2::{password_hash::SaltString, Algorithm, Argon2, Params, PasswordHash, PasswordHasher, PasswordVerifier, Version};
-use argon2::password_hash::rand_core::OsRng;
+use crate::services::password_hasher::is_supported_password_hash;
+use chrono::{DateTime, Utc};
 use sqlx::PgPool;
+use uuid::Uuid;
 
 pub struct PostgresUserStore {
     pool: PgPool,
+    password_hasher: PasswordHasherType,
 }
 
 impl PostgresUserStore {
-    pub fn new(pool: PgPool) -> Self {
-        Self { pool }
+    pub fn new(pool: PgPool, password_hasher: PasswordHasherType) -> Self {
+        Self { pool, password_hasher }
     }
-}
 
-#[async_trait::async_trait]
-impl UserStore for PostgresUserStore {
-    async fn add_user(&mut self, user: User) -> Result<(), UserStoreError> {
-        let user_from_db = self.get_user(&user.get_email()).await;
-        if user_from_db.is_ok() {
-            return Err(UserStoreError::UserAlreadyExists);
+    // Users migrated from older systems keep the hash in their password until their first login upgrades it
+    pub async fn import_user(&self, user: User) -> Result<(), UserStoreError> {
+        if !is_supported_password_hash(user.get_password().as_ref()) {
+            return Err(UserStoreError::UnexpectedError);
         }
 
-        let password_hash = compute_password_hash(user.get_password().as_ref().to_string())
+        let (email, password_hash) = (user.get_email(), user.get_password());
+        let result = sqlx::query!(r#"
+            INSERT INTO users (id, email, password_hash, requires_2fa, tenant_id)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT DO NOTHING
+            "#,
+            user.get_id().as_uuid(),
+            email.as_ref(),
+            password_hash.as_ref(),
+            user.use_requires_2fa(),
+            user.get_tenant_id().as_uuid()
+          )
+            .execute(&self.pool)
             .await
             .map_err(|_| UserStoreError::UnexpectedError)?;
 
+        if result.rows_affected() == 0 {
+            return Err(UserStoreError::UserAlreadyExists);
+        }
+
+        Ok(())
+    }
+
+    async fn insert_user(&self, user: User, approval_pending: bool) -> Result<(), UserStoreError> {
+        // Taken emails are only detected by the insert, so they cost the same hashing time as new ones
+        let password_hash = self.password_hasher.hash_password(&user.get_password()).await?;
+
         sqlx::query!(r#"
-            INSERT INTO users (email, password_hash, requires_2fa)
-            VALUES ($1, $2, $3)
+            INSERT INTO users (id, email, password_hash, requires_2fa, tenant_id, approval_pending)
+            VALUES ($1, $2, $3, $4, $5, $6)
             "#,
+            user.get_id().as_uuid(),
             user.get_email().as_ref().to_string(),
             password_hash,
-            user.use_requires_2fa()
+            user.use_requires_2fa(),
+            user.get_tenant_id().as_uuid(),
+            approval_pending
+          )
+            .execute(&self.pool)
+            .await
+            .map_err(|e| match e {
+                sqlx::Error::Database(db_error) if db_error.is_unique_violation() => UserStoreError::UserAlreadyExists,
+                _ => UserStoreError::UnexpectedError,
+            })?;
+
+        Ok(())
+    }
+
+    // Replace the hash unless the password was changed in the meantime
+    async fn rehash_password(&self, user: &User, password: &Password) -> Result<(), UserStoreError> {
+        let password_hash = self.password_hasher.hash_password(password).await?;
+        let old_password = user.get_password();
+
+        sqlx::query!(r#"
+            UPDATE users SET password_hash = $3
+            WHERE id = $1 AND password_hash = $2
+            "#,
+            user.get_id().as_uuid(),
+            old_password.as_ref(),
+            password_hash
           )
             .execute(&self.pool)
             .await
@@ -44,11 +91,51 @@ impl UserStore for PostgresUserStore {
 
         Ok(())
     }
+}
 
-    async fn get_user(&self, email: &Email) -> Result<User, UserStoreError> {
+#[async_trait::async_trait]
+impl UserStore for PostgresUserStore {
+    async fn add_user(&mut self, user: User) -> Result<(), UserStoreError> {
+        self.insert_user(user, false).await
+    }
+
+    async fn add_pending_user(&mut self, user: User) -> Result<(), UserStoreError> {
+        self.insert_user(user, true).await
+    }
+
+    async fn approve_user(&mut self, email: &Email) -> Result<(), UserStoreError> {
+        let result = sqlx::query!(r#"
+            UPDATE users SET approval_pending = FALSE
+            WHERE email = $1
+            "#,
+            email.as_ref()
+          )
+            .execute(&self.pool)
+            .await
+            .map_err(|_| UserStoreError::UnexpectedError)?;
+
+        if result.rows_affected() == 0 {
+            return Err(UserStoreError::UserNotFound);
+        }
+
+        Ok(())
+    }
+
+    async fn get_user(&self, tenant_id: &TenantId, email: &Email) -> Result<User, UserStoreError> {
+        let user = self.get_user_by_email(email).await?;
+
+        if user.get_tenant_id() != *tenant_id {
+            return Err(UserStoreError::UserNotFound);
+        }
+
+        Ok(user)
+    }
+
+    async fn get_user_by_email(&self, email: &Email) -> Result<User, UserStoreError> {
         let record = sqlx::query!(
            r#"
-           SELECT * FROM users WHERE email = $1
+           SELECT id, email, password_hash, requires_2fa, tenant_id FROM users
+           WHERE email = $1 AND deletion_scheduled_at IS NULL
            "#,
            email.as_ref()
         )
@@ -59,53 +146,428 @@ impl UserStore for PostgresUserStore {
         let email = Email::parse(record.email).map_err(|_| UserStoreError::UnexpectedError)?;
         let password = Password::parse(record.password_hash).map_err(|_| UserStoreError::UnexpectedError)?;
 
-        let user = User::new(email, password, record.requires_2fa);
+        let user = User::with_id(record.id.into(), email, password, record.requires_2fa).with_tenant(record.tenant_id.into());
         Ok(user)
     }
 
-    async fn validate_user(&self, email: &Email, password: &Password) -> Result<(), UserStoreError> {
-        let user = self.get_user(email).await?;
+    async fn get_email_tenant(&self, email: &Email) -> Result<TenantId, UserStoreError> {
+        let tenant_id = sqlx::query_scalar!(
+           r#"
+           SELECT tenant_id FROM users
+           WHERE email = $1
+           "#,
+           email.as_ref()
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|_| UserStoreError::UserNotFound)?;
+
+        Ok(tenant_id.into())
+    }
+
+    async fn get_user_by_id(&self, id: &UserId) -> Result<User, UserStoreError> {
+        let record = sqlx::query!(
+           r#"
+           SELECT id, email, password_hash, requires_2fa, tenant_id FROM users
+           WHERE id = $1 AND deletion_scheduled_at IS NULL
+           "#,
+           id.as_uuid()
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|_| UserStoreError::UserNotFound)?;
+
+        let email = Email::parse(record.email).map_err(|_| UserStoreError::UnexpectedError)?;
+        let password = Password::parse(record.password_hash).map_err(|_| UserStoreError::UnexpectedError)?;
+
+        let user = User::with_id(record.id.into(), email, password, record.requires_2fa).with_tenant(record.tenant_id.into());
+        Ok(user)
+    }
+
+    async fn validate_user(&self, tenant_id: &TenantId, email: &Email, password: &Password) -> Result<(), UserStoreError> {
+        // Unknown emails must not answer faster than wrong passwords, or timing would reveal registered accounts
+        let user = match self.get_user(tenant_id, email).await {
+            Ok(user) => user,
+            Err(e) => {
+                self.password_hasher.verify_dummy_password(password).await?;
+                return Err(e);
+            }
+        };
 
         if user.get_email().as_ref() != email.as_ref() {
             return Err(UserStoreError::InvalidCredentials);
         }
 
-        if verify_password_hash(user.get_password().as_ref().to_string(), password.as_ref().to_string()).await.is_err() {
-            return Err(UserStoreError::InvalidCredentials);
+        self.password_hasher.verify_password(user.get_password().as_ref(), password).await?;
+
+        // Hashes with outdated parameters or an old pepper are upgraded while we know the password, a failure only delays that
+        if self.password_hasher.needs_rehash(user.get_password().as_ref()) {
+            let _ = self.rehash_password(&user, password).await;
+        }
+
+        let account = self.get_user_account(&user.get_id()).await?;
+        if account.locked {
+            return Err(UserStoreError::AccountLocked);
+        }
+        if account.approval_pending {
+            return Err(UserStoreError::ApprovalPending);
         }
 
         Ok(())
     }
-}
 
+    async fn update_password(&mut self, email: &Email, password: Password) -> Result<(), UserStoreError> {
+        let password_hash = self.password_hasher.hash_password(&password).await?;
 
-async fn verify_password_hash(
-    expected_password_hash: String,
-    password_candidate: String,
-) -> Result<(), Box<dyn Error + Send + Sync>> {
-    tokio::task::spawn_blocking(move || {
-        let expected_password_hash: PasswordHash<'_> = PasswordHash::new(&expected_password_hash)?;
+        let result = sqlx::query!(r#"
+            WITH previous AS (
+                INSERT INTO password_history (user_id, password_hash)
+                SELECT id, password_hash FROM users WHERE email = $1
+            )
+            UPDATE users SET password_hash = $2, password_changed_at = NOW(), password_reset_required = FALSE
+            WHERE email = $1
+            "#,
+            email.as_ref(),
+            password_hash
+          )
+            .execute(&self.pool)
+            .await
+            .map_err(|_| UserStoreError::UnexpectedError)?;
 
-        Argon2::default()
-            .verify_password(password_candidate.as_bytes(), &expected_password_hash)
-            .map_err(|e| Box::new(e))?;
+        if result.rows_affected() == 0 {
+            return Err(UserStoreError::UserNotFound);
+        }
 
         Ok(())
-    }).await?
-}
+    }
+
+    async fn is_recent_password(&self, email: &Email, password: &Password, count: usize) -> Result<bool, UserStoreError> {
+        if count == 0 {
+            return Ok(false);
+        }
 
-async fn compute_password_hash(password: String) -> Result<String, Box<dyn Error + Send + Sync>> {
-    tokio::task::spawn_blocking(move || {
-        let salt: SaltString = SaltString::generate(&mut OsRng);
-        let password_hash = Argon2::new(
-            Algorithm::Argon2id,
-            Version::V0x13,
-            Params::new(15000, 2, 1, None)?,
+        let user = self.get_user_by_email(email).await?;
+        let previous_hashes = sqlx::query_scalar!(r#"
+            SELECT password_history.password_hash FROM password_history
+            JOIN users ON users.id = password_history.user_id
+            WHERE users.email = $1
+            ORDER BY password_history.created_at DESC, password_history.id DESC
+            LIMIT $2
+            "#,
+            email.as_ref(),
+            (count - 1) as i64
+          )
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|_| UserStoreError::UnexpectedError)?;
+
+        for password_hash in std::iter::once(user.get_password().as_ref().to_string()).chain(previous_hashes) {
+            match self.password_hasher.verify_password(&password_hash, password).await {
+                Ok(()) => return Ok(true),
+                Err(PasswordHasherError::IncorrectPassword) => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+
+        Ok(false)
+    }
+
+    async fn get_password_history_count(&self, id: &UserId) -> Result<u64, UserStoreError> {
+        let count = sqlx::query_scalar!(r#"
+            SELECT COUNT(*) AS "count!" FROM password_history
+            WHERE user_id = $1
+            "#,
+            id.as_uuid()
+          )
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|_| UserStoreError::UnexpectedError)?;
+
+        Ok(count as u64)
+    }
+
+    async fn get_password_changed_at(&self, email: &Email) -> Result<DateTime<Utc>, UserStoreError> {
+        sqlx::query_scalar!(r#"
+            SELECT password_changed_at FROM users
+            WHERE email = $1 AND deletion_scheduled_at IS NULL
+            "#,
+            email.as_ref()
+          )
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|_| UserStoreError::UserNotFound)
+    }
+
+    async fn update_email(&mut self, email: &Email, new_email: Email) -> Result<(), UserStoreError> {
+        let result = sqlx::query!(r#"
+            UPDATE users SET email = $2
+            WHERE email = $1
+            "#,
+            email.as_ref(),
+            new_email.as_ref()
+          )
+            .execute(&self.pool)
+            .await
+            .map_err(|e| match e {
+                sqlx::Error::Database(db_error) if db_error.is_unique_violation() => UserStoreError::UserAlreadyExists,
+                _ => UserStoreError::UnexpectedError,
+            })?;
+
+        if result.rows_affected() == 0 {
+            return Err(UserStoreError::UserNotFound);
+        }
+
+        Ok(())
+    }
+
+    async fn update_requires_2fa(&mut self, email: &Email, requires_2fa: bool) -> Result<(), UserStoreError> {
+        let result = sqlx::query!(r#"
+            UPDATE users SET requires_2fa = $2
+            WHERE email = $1
+            "#,
+            email.as_ref(),
+            requires_2fa
+          )
+            .execute(&self.pool)
+            .await
+            .map_err(|_| UserStoreError::UnexpectedError)?;
+
+        if result.rows_affected() == 0 {
+            return Err(UserStoreError::UserNotFound);
+        }
+
+        Ok(())
+    }
+
+    async fn delete_user(&mut self, email: &Email) -> Result<(), UserStoreError> {
+        let result = sqlx::query!(r#"
+            DELETE FROM users WHERE email = $1
+            "#,
+            email.as_ref()
+          )
+            .execute(&self.pool)
+            .await
+            .map_err(|_| UserStoreError::UnexpectedError)?;
+
+        if result.rows_affected() == 0 {
+            return Err(UserStoreError::UserNotFound);
+        }
+
+        Ok(())
+    }
+
+    async fn schedule_user_deletion(&mut self, email: &Email, delete_at: DateTime<Utc>) -> Result<(), UserStoreError> {
+        let result = sqlx::query!(r#"
+            UPDATE users SET deletion_scheduled_at = $2
+            WHERE email = $1
+            "#,
+            email.as_ref(),
+            delete_at
+          )
+            .execute(&self.pool)
+            .await
+            .map_err(|_| UserStoreError::UnexpectedError)?;
+
+        if result.rows_affected() == 0 {
+            return Err(UserStoreError::UserNotFound);
+        }
+
+        Ok(())
+    }
+
+    async fn cancel_user_deletion(&mut self, tenant_id: &TenantId, email: &Email, password: &Password) -> Result<User, UserStoreError> {
+        let record = sqlx::query!(
+           r#"
+           SELECT id, email, password_hash, requires_2fa, tenant_id FROM users
+           WHERE email = $1 AND tenant_id = $2 AND deletion_scheduled_at > NOW()
+           "#,
+           email.as_ref(),
+           tenant_id.as_uuid()
         )
-            .hash_password(password.as_bytes(), &salt)
-            .map_err(|e| Box::new(e))?
-            .to_string();
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|_| UserStoreError::UnexpectedError)?;
+
+        // Answers as slowly for accounts that are not scheduled for deletion as for wrong passwords
+        let Some(record) = record else {
+            self.password_hasher.verify_dummy_password(password).await?;
+            return Err(UserStoreError::UserNotFound);
+        };
+
+        self.password_hasher.verify_password(&record.password_hash, password).await?;
+
+        sqlx::query!(r#"
+            UPDATE users SET deletion_scheduled_at = NULL
+            WHERE id = $1
+            "#,
+            record.id
+          )
+            .execute(&self.pool)
+            .await
+            .map_err(|_| UserStoreError::UnexpectedError)?;
+
+        let email = Email::parse(record.email).map_err(|_| UserStoreError::UnexpectedError)?;
+        let password = Password::parse(record.password_hash).map_err(|_| UserStoreError::UnexpectedError)?;
+
+        Ok(User::with_id(record.id.into(), email, password, record.requires_2fa).with_tenant(record.tenant_id.into()))
+    }
+
+    async fn delete_scheduled_users(&mut self) -> Result<u64, UserStoreError> {
+        let result = sqlx::query!(r#"
+            DELETE FROM users WHERE deletion_scheduled_at <= NOW()
+            "#
+          )
+            .execute(&self.pool)
+            .await
+            .map_err(|_| UserStoreError::UnexpectedError)?;
+
+        Ok(result.rows_affected())
+    }
+
+    async fn list_users(&self, tenant_id: &TenantId, search: Option<&str>, pending_only: bool, offset: u64, limit: u64) -> Result<(Vec<UserAccount>, u64), UserStoreError> {
+        let pattern = search.map(|search| format!("%{}%", escape_like(search)));
 
-        Ok(password_hash)
-    }).await?
-}
\ No newline at end of file
+        let total = sqlx::query_scalar!(r#"
+            SELECT COUNT(*) AS "total!" FROM users
+            WHERE tenant_id = $1 AND ($2::TEXT IS NULL OR email ILIKE $2) AND (NOT $3 OR approval_pending)
+            "#,
+            tenant_id.as_uuid(),
+            pattern,
+            pending_only
+          )
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|_| UserStoreError::UnexpectedError)?;
+
+        let rows = sqlx::query_as!(
+            UserAccountRow,
+            r#"
+            SELECT id, email, password_hash, requires_2fa, tenant_id, locked_at, password_reset_required, approval_pending, password_changed_at, deletion_scheduled_at
+            FROM users
+            WHERE tenant_id = $1 AND ($2::TEXT IS NULL OR email ILIKE $2) AND (NOT $3 OR approval_pending)
+            ORDER BY email
+            OFFSET $4 LIMIT $5
+            "#,
+            tenant_id.as_uuid(),
+            pattern,
+            pending_only,
+            offset as i64,
+            limit as i64
+          )
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|_| UserStoreError::UnexpectedError)?;
+
+        let accounts = rows.into_iter().map(UserAccount::try_from).collect::<Result<Vec<_>, _>>()?;
+
+        Ok((accounts, total as u64))
+    }
+
+    async fn get_user_account(&self, id: &UserId) -> Result<UserAccount, UserStoreError> {
+        sqlx::query_as!(
+            UserAccountRow,
+            r#"
+            SELECT id, email, password_hash, requires_2fa, tenant_id, locked_at, password_reset_required, approval_pending, password_changed_at, deletion_scheduled_at
+            FROM users
+            WHERE id = $1
+            "#,
+            id.as_uuid()
+          )
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|_| UserStoreError::UserNotFound)?
+            .try_into()
+    }
+
+    async fn set_locked(&mut self, email: &Email, locked: bool) -> Result<(), UserStoreError> {
+        let result = sqlx::query!(r#"
+            UPDATE users SET locked_at = CASE WHEN $2 THEN COALESCE(locked_at, NOW()) END
+            WHERE email = $1
+            "#,
+            email.as_ref(),
+            locked
+          )
+            .execute(&self.pool)
+            .await
+            .map_err(|_| UserStoreError::UnexpectedError)?;
+
+        if result.rows_affected() == 0 {
+            return Err(UserStoreError::UserNotFound);
+        }
+
+        Ok(())
+    }
+
+    async fn require_password_reset(&mut self, email: &Email) -> Result<(), UserStoreError> {
+        let result = sqlx::query!(r#"
+            UPDATE users SET password_reset_required = TRUE
+            WHERE email = $1
+            "#,
+            email.as_ref()
+          )
+            .execute(&self.pool)
+            .await
+            .map_err(|_| UserStoreError::UnexpectedError)?;
+
+        if result.rows_affected() == 0 {
+            return Err(UserStoreError::UserNotFound);
+        }
+
+        Ok(())
+    }
+
+    async fn update_tenant(&mut self, email: &Email, tenant_id: &TenantId) -> Result<(), UserStoreError> {
+        let result = sqlx::query!(r#"
+            UPDATE users SET tenant_id = $2
+            WHERE email = $1
+            "#,
+            email.as_ref(),
+            tenant_id.as_uuid()
+          )
+            .execute(&self.pool)
+            .await
+            .map_err(|_| UserStoreError::UnexpectedError)?;
+
+        if result.rows_affected() == 0 {
+            return Err(UserStoreError::UserNotFound);
+        }
+
+        Ok(())
+    }
+}
+
+struct UserAccountRow {
+    id: Uuid,
+    email: String,
+    password_hash: String,
+    requires_2fa: bool,
+    tenant_id: Uuid,
+    locked_at: Option<DateTime<Utc>>,
+    password_reset_required: bool,
+    approval_pending: bool,
+    password_changed_at: DateTime<Utc>,
+    deletion_scheduled_at: Option<DateTime<Utc>>,
+}
+
+impl TryFrom<UserAccountRow> for UserAccount {
+    type Error = UserStoreError;
+
+    fn try_from(row: UserAccountRow) -> Result<Self, Self::Error> {
+        let email = Email::parse(row.email).map_err(|_| UserStoreError::UnexpectedError)?;
+        let password = Password::parse(row.password_hash).map_err(|_| UserStoreError::UnexpectedError)?;
+
+        Ok(UserAccount {
+            user: User::with_id(row.id.into(), email, password, row.requires_2fa).with_tenant(row.tenant_id.into()),
+            locked: row.locked_at.is_some(),
+            password_reset_required: row.password_reset_required,
+            approval_pending: row.approval_pending,
+            password_changed_at: row.password_changed_at,
+            deletion_scheduled_at: row.deletion_scheduled_at,
+        })
+    }
+}
+
+// Searches match the email literally, LIKE wildcards typed by the admin included
+fn escape_like(search: &str) -> String {
+    search.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+}
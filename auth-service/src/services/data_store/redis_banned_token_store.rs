@@ -1,11 +1,12 @@
 use std::sync::Arc;
 
+use chrono::{DateTime, Utc};
 use redis::{Commands, Connection};
 use tokio::sync::RwLock;
 
 use crate::{
     domain::data_store::{BannedTokenStore, BannedTokenStoreError},
-    utils::auth::TOKEN_TTL_SECONDS,
+    utils::constants::BANNED_TOKEN_TTL_SECONDS,
 };
 
 pub struct RedisBannedTokenStore {
@@ -21,7 +22,7 @@ impl RedisBannedTokenStore {
 #[async_trait::async_trait]
 impl BannedTokenStore for RedisBannedTokenStore {
     async fn storing_tokens(&mut self, token: String) -> Result<(), BannedTokenStoreError> {
-        let ttl = match TOKEN_TTL_SECONDS.try_into() {
+        let ttl = match (*BANNED_TOKEN_TTL_SECONDS).try_into() {
             Ok(u_value) => u_value,
             Err(_) => return Err(BannedTokenStoreError::UnexpectedError)
         };
@@ -48,10 +49,48 @@ impl BannedTokenStore for RedisBannedTokenStore {
 
         Ok(result)
     }
+
+    async fn ban_user_tokens(&mut self, sub: &str) -> Result<(), BannedTokenStoreError> {
+        // Tokens issued before the marker expires have expired too, impersonation and exchanged ones included
+        let ttl = match (*BANNED_TOKEN_TTL_SECONDS).try_into() {
+            Ok(u_value) => u_value,
+            Err(_) => return Err(BannedTokenStoreError::UnexpectedError)
+        };
+
+        let key = get_user_key(sub);
+
+        self.conn
+            .write()
+            .await
+            .set_ex::<_, _, ()>(key, Utc::now().timestamp_micros(), ttl)
+            .map_err(|_| BannedTokenStoreError::UnexpectedError)?;
+
+        Ok(())
+    }
+
+    async fn user_token_is_banned(&self, sub: &str, issued_at: DateTime<Utc>) -> Result<bool, BannedTokenStoreError> {
+        let key = get_user_key(sub);
+
+        let banned_at: Option<i64> = self.conn
+                .write()
+                .await
+                .get(key)
+                .map_err(|_| BannedTokenStoreError::UnexpectedError)?;
+
+        match banned_at {
+            Some(banned_at) => Ok(issued_at.timestamp_micros() < banned_at),
+            None => Ok(false)
+        }
+    }
 }
 
 const BANNED_TOKEN_KEY_PREFIX: &str = "banned_token:";
+pub const BANNED_USER_KEY_PREFIX: &str = "banned_user_tokens:";
 
 fn get_key(token: &str) -> String {
     format!("{}{}", BANNED_TOKEN_KEY_PREFIX, token)
 }
+
+fn get_user_key(sub: &str) -> String {
+    format!("{}{}", BANNED_USER_KEY_PREFIX, sub)
+}
@@ -0,0 +1,86 @@
+use std::sync::Arc;
+
+use redis::{Commands, Connection};
+use tokio::sync::RwLock;
+
+use crate::domain::{
+    data_store::{EmailChangeStore, EmailChangeStoreError, EmailChangeToken},
+    Email,
+};
+
+pub struct RedisEmailChangeStore {
+    conn: Arc<RwLock<Connection>>,
+}
+
+impl RedisEmailChangeStore {
+    pub fn new(conn: Arc<RwLock<Connection>>) -> Self {
+        Self { conn }
+    }
+}
+
+#[async_trait::async_trait]
+impl EmailChangeStore for RedisEmailChangeStore {
+    async fn add_request(
+        &mut self,
+        email: Email,
+        new_email: Email,
+        confirmation_token: EmailChangeToken,
+        cancel_token: EmailChangeToken,
+    ) -> Result<(), EmailChangeStoreError> {
+        let key = get_key(&email);
+        let request = (new_email.as_ref().to_owned(), confirmation_token, cancel_token);
+
+        let json_request = serde_json::to_string(&request)
+            .map_err(|_| EmailChangeStoreError::UnexpectedError)?;
+
+        self.conn
+            .write()
+            .await
+            .set_ex::<_, _, ()>(key, json_request, ONE_DAY_IN_SECONDS)
+            .map_err(|_| EmailChangeStoreError::UnexpectedError)?;
+
+        Ok(())
+    }
+
+    async fn remove_request(&mut self, email: &Email) -> Result<(), EmailChangeStoreError> {
+        let key = get_key(email);
+
+        self.conn
+            .write()
+            .await
+            .del::<_, ()>(key)
+            .map_err(|_| EmailChangeStoreError::UnexpectedError)?;
+
+        Ok(())
+    }
+
+    async fn get_request(
+        &self,
+        email: &Email,
+    ) -> Result<(Email, EmailChangeToken, EmailChangeToken), EmailChangeStoreError> {
+        let key = get_key(email);
+
+        let request_value: Option<String> = self.conn
+            .write()
+            .await
+            .get(key)
+            .map_err(|_| EmailChangeStoreError::UnexpectedError)?;
+
+        let request_value = request_value.ok_or(EmailChangeStoreError::RequestNotFound)?;
+
+        let (new_email, confirmation_token, cancel_token): (String, EmailChangeToken, EmailChangeToken) =
+            serde_json::from_str(&request_value)
+                .map_err(|_| EmailChangeStoreError::UnexpectedError)?;
+
+        let new_email = Email::parse(new_email).map_err(|_| EmailChangeStoreError::UnexpectedError)?;
+
+        Ok((new_email, confirmation_token, cancel_token))
+    }
+}
+
+const ONE_DAY_IN_SECONDS: u64 = 86400;
+const EMAIL_CHANGE_PREFIX: &str = "email_change:";
+
+fn get_key(email: &Email) -> String {
+    format!("{}{}", EMAIL_CHANGE_PREFIX, email.as_ref())
+}
@@ -10,11 +10,22 @@ use crate::domain::{
 
 pub struct RedisTwoFACodeStore {
     conn: Arc<RwLock<Connection>>,
+    key_prefix: &'static str,
 }
 
 impl RedisTwoFACodeStore {
     pub fn new(conn: Arc<RwLock<Connection>>) -> Self {
-        Self { conn }
+        Self { conn, key_prefix: TWO_FA_CODE_PREFIX }
+    }
+
+    // Keeps the codes apart from those of other stores sharing the connection
+    pub fn with_key_prefix(mut self, key_prefix: &'static str) -> Self {
+        self.key_prefix = key_prefix;
+        self
+    }
+
+    fn get_key(&self, email: &Email) -> String {
+        format!("{}{}", self.key_prefix, email.as_ref())
     }
 }
 
@@ -26,7 +37,7 @@ impl TwoFACodeStore for RedisTwoFACodeStore {
         login_attempt_id: LoginAttemptId,
         code: TwoFACode,
     ) -> Result<(), TwoFACodeStoreError> {
-        let key = get_key(&email);
+        let key = self.get_key(&email);
         let two_fa_instance = (login_attempt_id, code);
 
         let json_two_fa_instance = serde_json::to_string(&two_fa_instance)
@@ -42,7 +53,7 @@ impl TwoFACodeStore for RedisTwoFACodeStore {
     }
 
     async fn remove_code(&mut self, email: &Email) -> Result<(), TwoFACodeStoreError> {
-        let key = get_key(&email);
+        let key = self.get_key(&email);
 
         self.conn
             .write()
@@ -57,7 +68,7 @@ impl TwoFACodeStore for RedisTwoFACodeStore {
         &self,
         email: &Email,
     ) -> Result<(LoginAttemptId, TwoFACode), TwoFACodeStoreError> {
-        let key = get_key(&email);
+        let key = self.get_key(&email);
 
         let two_fa_instance_value: String = self.conn
             .write()
@@ -74,7 +85,4 @@ impl TwoFACodeStore for RedisTwoFACodeStore {
 
 const TEN_MINUTES_IN_SECONDS: u64 = 600;
 const TWO_FA_CODE_PREFIX: &str = "two_fa_code:";
-
-fn get_key(email: &Email) -> String {
-    format!("{}{}", TWO_FA_CODE_PREFIX, email.as_ref())
-}
+pub const DISABLE_2FA_CODE_PREFIX: &str = "disable_2fa_code:";
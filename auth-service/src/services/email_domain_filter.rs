@@ -0,0 +1,225 @@
+use std::{
+    collections::HashSet,
+    fs,
+    path::{Path, PathBuf},
+    sync::RwLock,
+    time::SystemTime,
+};
+
+use crate::domain::{Email, EmailDomainFilter};
+
+pub const DISPOSABLE_EMAIL_DOMAINS: &str = include_str!("disposable_email_domains.txt");
+
+// One rule per line: `example.com` blocks the domain and `*.example.com` the domain and its subdomains.
+// A leading `!` turns either into an allow rule, which wins over every block rule. `#` starts a comment.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct DomainRules {
+    blocked: HashSet<String>,
+    blocked_wildcards: HashSet<String>,
+    allowed: HashSet<String>,
+    allowed_wildcards: HashSet<String>,
+}
+
+impl DomainRules {
+    // Domains are normalized the way emails are, so they can be compared with the domain of an `Email`
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut rules = Self::default();
+
+        for (number, line) in text.lines().enumerate() {
+            let rule = line.split('#').next().unwrap_or_default().trim();
+            if rule.is_empty() {
+                continue;
+            }
+
+            let (allow, rule) = match rule.strip_prefix('!') {
+                Some(rule) => (true, rule.trim()),
+                None => (false, rule),
+            };
+            let (wildcard, domain) = match rule.strip_prefix("*.") {
+                Some(domain) => (true, domain),
+                None => (false, rule),
+            };
+
+            let domain = match idna::domain_to_ascii(domain) {
+                Ok(domain) if !domain.is_empty() && !domain.contains('*') => domain,
+                _ => return Err(format!("Line {}: {} is not a valid domain rule.", number + 1, line.trim())),
+            };
+
+            match (allow, wildcard) {
+                (false, false) => rules.blocked.insert(domain),
+                (false, true) => rules.blocked_wildcards.insert(domain),
+                (true, false) => rules.allowed.insert(domain),
+                (true, true) => rules.allowed_wildcards.insert(domain),
+            };
+        }
+
+        Ok(rules)
+    }
+
+    pub fn merge(mut self, other: DomainRules) -> Self {
+        self.blocked.extend(other.blocked);
+        self.blocked_wildcards.extend(other.blocked_wildcards);
+        self.allowed.extend(other.allowed);
+        self.allowed_wildcards.extend(other.allowed_wildcards);
+        self
+    }
+
+    pub fn is_blocked(&self, domain: &str) -> bool {
+        let matches = |exact: &HashSet<String>, wildcards: &HashSet<String>| {
+            exact.contains(domain) || parent_domains(domain).any(|parent| wildcards.contains(parent))
+        };
+
+        matches(&self.blocked, &self.blocked_wildcards) && !matches(&self.allowed, &self.allowed_wildcards)
+    }
+}
+
+// The domain itself followed by each of its parents: a.example.com, example.com, com
+fn parent_domains(domain: &str) -> impl Iterator<Item = &str> {
+    std::iter::successors(Some(domain), |domain| domain.split_once('.').map(|(_, parent)| parent))
+}
+
+struct LoadedRules {
+    rules: DomainRules,
+    // Modification time and length of the file the rules were read from, to tell when it changed
+    version: Option<(SystemTime, u64)>,
+}
+
+// The bundled disposable domains, along with the rules of an optional file that `reload` picks up changes of
+pub struct FileEmailDomainFilter {
+    bundled: DomainRules,
+    path: Option<PathBuf>,
+    loaded: RwLock<LoadedRules>,
+}
+
+impl FileEmailDomainFilter {
+    pub fn new(block_disposable: bool) -> Self {
+        let bundled = match block_disposable {
+            true => DomainRules::parse(DISPOSABLE_EMAIL_DOMAINS).expect("Bundled disposable email domains are valid"),
+            false => DomainRules::default(),
+        };
+
+        Self {
+            loaded: RwLock::new(LoadedRules { rules: bundled.clone(), version: None }),
+            bundled,
+            path: None,
+        }
+    }
+
+    pub fn load(path: impl AsRef<Path>, block_disposable: bool) -> Result<Self, String> {
+        let filter = Self { path: Some(path.as_ref().to_path_buf()), ..Self::new(block_disposable) };
+        filter.reload()?;
+
+        Ok(filter)
+    }
+
+    // Reads the rules file again if it changed since it was last read, and tells whether it did.
+    // Rules that fail to load leave the current ones in place.
+    pub fn reload(&self) -> Result<bool, String> {
+        let Some(path) = &self.path else {
+            return Ok(false);
+        };
+
+        let metadata = fs::metadata(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        let version = Some((metadata.modified().map_err(|e| e.to_string())?, metadata.len()));
+        if self.loaded.read().map_err(|e| e.to_string())?.version == version {
+            return Ok(false);
+        }
+
+        let text = fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        let rules = self.bundled.clone().merge(DomainRules::parse(&text)?);
+
+        *self.loaded.write().map_err(|e| e.to_string())? = LoadedRules { rules, version };
+        Ok(true)
+    }
+}
+
+impl EmailDomainFilter for FileEmailDomainFilter {
+    fn is_allowed(&self, email: &Email) -> bool {
+        let domain = email.as_ref().rsplit_once('@').map(|(_, domain)| domain).unwrap_or_default();
+
+        // A poisoned lock still holds the last rules that loaded
+        let loaded = self.loaded.read().unwrap_or_else(|e| e.into_inner());
+        !loaded.rules.is_blocked(domain)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn email(s: &str) -> Email {
+        Email::parse(s.to_string()).unwrap()
+    }
+
+    #[test]
+    fn test_rules_match_domains_and_wildcard_subdomains() {
+        let rules = DomainRules::parse("
+            # Throwaway providers
+            spam.test
+            *.throwaway.test   # and its subdomains
+            *.example.test
+            !corp.example.test
+            !*.partner.example.test
+            Bücher.test
+        ").unwrap();
+
+        assert!(rules.is_blocked("spam.test"));
+        assert!(!rules.is_blocked("mail.spam.test"));
+        assert!(rules.is_blocked("throwaway.test"));
+        assert!(rules.is_blocked("a.b.throwaway.test"));
+        assert!(!rules.is_blocked("notthrowaway.test"));
+
+        assert!(rules.is_blocked("example.test"));
+        assert!(!rules.is_blocked("corp.example.test"));
+        assert!(rules.is_blocked("mail.corp.example.test"));
+        assert!(!rules.is_blocked("eu.partner.example.test"));
+
+        assert!(rules.is_blocked("xn--bcher-kva.test"));
+    }
+
+    #[test]
+    fn test_invalid_rules_are_rejected() {
+        assert!(DomainRules::parse("spam.test\n*.\n").is_err());
+        assert!(DomainRules::parse("spam.*.test").is_err());
+        assert!(DomainRules::parse("!").is_err());
+    }
+
+    #[test]
+    fn test_bundled_disposable_domains() {
+        let filter = FileEmailDomainFilter::new(true);
+        assert!(!filter.is_allowed(&email("someone@mailinator.com")));
+        assert!(!filter.is_allowed(&email("someone@private.mailinator.com")));
+        assert!(filter.is_allowed(&email("someone@example.com")));
+
+        let filter = FileEmailDomainFilter::new(false);
+        assert!(filter.is_allowed(&email("someone@mailinator.com")));
+    }
+
+    #[test]
+    fn test_rules_file_is_reloaded_when_it_changes() {
+        let path = std::env::temp_dir().join(format!("email-domain-rules-{}.txt", uuid::Uuid::new_v4()));
+        fs::write(&path, "spam.test\n!mailinator.com\n").unwrap();
+
+        let filter = FileEmailDomainFilter::load(&path, true).unwrap();
+        assert!(!filter.is_allowed(&email("someone@spam.test")));
+        assert!(filter.is_allowed(&email("someone@mailinator.com")));
+        assert!(!filter.is_allowed(&email("someone@yopmail.com")));
+        assert!(!filter.reload().unwrap());
+
+        // The length differs, so the change is seen even within the resolution of the modification time
+        fs::write(&path, "*.other-spam.test\n").unwrap();
+        assert!(filter.reload().unwrap());
+        assert!(filter.is_allowed(&email("someone@spam.test")));
+        assert!(!filter.is_allowed(&email("someone@mail.other-spam.test")));
+        assert!(!filter.is_allowed(&email("someone@mailinator.com")));
+
+        // Broken rules keep the previous ones
+        fs::write(&path, "not a * rule\n").unwrap();
+        assert!(filter.reload().is_err());
+        assert!(!filter.is_allowed(&email("someone@mail.other-spam.test")));
+
+        fs::remove_file(&path).unwrap();
+        assert!(filter.reload().is_err());
+        assert!(FileEmailDomainFilter::load(&path, true).is_err());
+    }
+}
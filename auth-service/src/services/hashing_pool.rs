@@ -0,0 +1,177 @@
+use std::{
+    sync::{
+        atomic::{AtomicU64, AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Instant,
+};
+
+use serde::{Deserialize, Serialize};
+use tokio::sync::Semaphore;
+
+#[derive(Debug, PartialEq)]
+pub enum HashingPoolError {
+    Saturated,
+    Failed,
+}
+
+// Runs password hashing on tokio's blocking threads, but never more than `max_concurrency` at a time.
+// Each Argon2 call holds its memory cost, so a burst of logins waits in a queue of at most `max_queued`
+// jobs and anything beyond that is rejected instead of exhausting memory.
+pub struct HashingPool {
+    permits: Arc<Semaphore>,
+    max_concurrency: usize,
+    max_queued: usize,
+    counters: Arc<Counters>,
+}
+
+#[derive(Default)]
+struct Counters {
+    queued: AtomicUsize,
+    running: AtomicUsize,
+    completed: AtomicU64,
+    rejected: AtomicU64,
+    total_latency_micros: AtomicU64,
+    max_latency_micros: AtomicU64,
+}
+
+// Leaves the queue even when the waiting request is cancelled
+struct QueueSlot<'a>(&'a AtomicUsize);
+
+impl Drop for QueueSlot<'_> {
+    fn drop(&mut self) {
+        self.0.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct HashingPoolMetrics {
+    pub max_concurrency: usize,
+    pub max_queued: usize,
+    pub queued: usize,
+    pub running: usize,
+    pub completed: u64,
+    pub rejected: u64,
+    // Time spent hashing, without the time spent waiting in the queue
+    pub average_latency_ms: f64,
+    pub max_latency_ms: f64,
+}
+
+impl HashingPool {
+    pub fn new(max_concurrency: usize, max_queued: usize) -> Self {
+        let max_concurrency = max_concurrency.max(1);
+
+        Self {
+            permits: Arc::new(Semaphore::new(max_concurrency)),
+            max_concurrency,
+            max_queued,
+            counters: Arc::new(Counters::default()),
+        }
+    }
+
+    pub async fn run<F, T>(&self, job: F) -> Result<T, HashingPoolError>
+    where
+        F: FnOnce() -> T + Send + 'static,
+        T: Send + 'static,
+    {
+        let permit = match self.permits.clone().try_acquire_owned() {
+            Ok(permit) => permit,
+            Err(_) => {
+                let queued = self.counters.queued.fetch_add(1, Ordering::SeqCst);
+                let queue_slot = QueueSlot(&self.counters.queued);
+                if queued >= self.max_queued {
+                    self.counters.rejected.fetch_add(1, Ordering::Relaxed);
+                    return Err(HashingPoolError::Saturated);
+                }
+
+                let permit = self.permits.clone().acquire_owned().await;
+                drop(queue_slot);
+                permit.map_err(|_| HashingPoolError::Failed)?
+            }
+        };
+
+        // The permit and the bookkeeping move into the job, so a cancelled request keeps its slot until hashing ends
+        let counters = self.counters.clone();
+        tokio::task::spawn_blocking(move || {
+            counters.running.fetch_add(1, Ordering::SeqCst);
+            let started_at = Instant::now();
+
+            let output = job();
+
+            let latency_micros = started_at.elapsed().as_micros() as u64;
+            counters.completed.fetch_add(1, Ordering::Relaxed);
+            counters.total_latency_micros.fetch_add(latency_micros, Ordering::Relaxed);
+            counters.max_latency_micros.fetch_max(latency_micros, Ordering::Relaxed);
+            counters.running.fetch_sub(1, Ordering::SeqCst);
+            drop(permit);
+
+            output
+        }).await.map_err(|_| HashingPoolError::Failed)
+    }
+
+    pub fn metrics(&self) -> HashingPoolMetrics {
+        let completed = self.counters.completed.load(Ordering::Relaxed);
+        let total_latency_micros = self.counters.total_latency_micros.load(Ordering::Relaxed);
+
+        HashingPoolMetrics {
+            max_concurrency: self.max_concurrency,
+            max_queued: self.max_queued,
+            queued: self.counters.queued.load(Ordering::SeqCst),
+            running: self.counters.running.load(Ordering::SeqCst),
+            completed,
+            rejected: self.counters.rejected.load(Ordering::Relaxed),
+            average_latency_ms: match completed {
+                0 => 0.0,
+                _ => total_latency_micros as f64 / completed as f64 / 1000.0,
+            },
+            max_latency_ms: self.counters.max_latency_micros.load(Ordering::Relaxed) as f64 / 1000.0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_jobs_run_and_are_measured() {
+        let pool = HashingPool::new(2, 2);
+
+        assert_eq!(pool.run(|| 21 * 2).await, Ok(42));
+
+        let metrics = pool.metrics();
+        assert_eq!(metrics.completed, 1);
+        assert_eq!((metrics.queued, metrics.running, metrics.rejected), (0, 0, 0));
+    }
+
+    #[tokio::test]
+    async fn test_saturated_pool_rejects_jobs() {
+        let pool = Arc::new(HashingPool::new(1, 1));
+        let slow_job = || std::thread::sleep(Duration::from_millis(300));
+
+        // One job runs and one waits in the queue, which leaves no room for a third
+        let running = tokio::spawn({
+            let pool = pool.clone();
+            async move { pool.run(slow_job).await }
+        });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        let queued = tokio::spawn({
+            let pool = pool.clone();
+            async move { pool.run(slow_job).await }
+        });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+
+        assert_eq!(pool.metrics().running, 1);
+        assert_eq!(pool.metrics().queued, 1);
+        assert_eq!(pool.run(slow_job).await, Err(HashingPoolError::Saturated));
+
+        assert!(running.await.unwrap().is_ok());
+        assert!(queued.await.unwrap().is_ok());
+
+        let metrics = pool.metrics();
+        assert_eq!((metrics.completed, metrics.rejected), (2, 1));
+        assert!(metrics.max_latency_ms >= 300.0);
+    }
+}
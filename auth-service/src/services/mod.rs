@@ -1 +1,6 @@
-pub mod data_store;
\ No newline at end of file
+pub mod data_store;
+pub mod password_policy;
+pub mod breached_password_checker;
+pub mod email_domain_filter;
+pub mod hashing_pool;
+pub mod password_hasher;
@@ -0,0 +1,284 @@
+use std::error::Error;
+
+use argon2::{password_hash::SaltString, Algorithm, Argon2, KeyId, Params, ParamsBuilder, PasswordHash, PasswordHasher as _, PasswordVerifier as _, Version};
+use argon2::password_hash::rand_core::OsRng;
+use pbkdf2::Pbkdf2;
+use scrypt::Scrypt;
+use tokio::sync::OnceCell;
+use uuid::Uuid;
+
+use crate::app_state::HashingPoolType;
+use crate::domain::{Password, PasswordHasher, PasswordHasherError, PasswordPepper, MAX_PEPPER_ID_LENGTH};
+use crate::services::hashing_pool::HashingPoolError;
+
+// Pepper ids are stored in the hash as the Argon2 key id
+const _: () = assert!(MAX_PEPPER_ID_LENGTH <= Params::MAX_KEYID_LEN);
+
+// Hashes new passwords with Argon2id on the hashing pool, and verifies those plus the legacy formats accepted from imports
+pub struct Argon2PasswordHasher {
+    params: Params,
+    peppers: Vec<PasswordPepper>,
+    hashing_pool: HashingPoolType,
+    dummy_password_hash: OnceCell<String>,
+}
+
+impl Argon2PasswordHasher {
+    // New hashes use the first pepper, the others are only kept to verify hashes made before a rotation
+    pub fn new(params: Params, peppers: Vec<PasswordPepper>, hashing_pool: HashingPoolType) -> Self {
+        let params = match peppers.first() {
+            Some(pepper) => with_key_id(&params, pepper.id().as_bytes()),
+            None => params,
+        };
+
+        Self { params, peppers, hashing_pool, dummy_password_hash: OnceCell::new() }
+    }
+}
+
+#[async_trait::async_trait]
+impl PasswordHasher for Argon2PasswordHasher {
+    async fn hash_password(&self, password: &Password) -> Result<String, PasswordHasherError> {
+        let password = password.as_ref().to_string();
+        let params = self.params.clone();
+        let secret = self.peppers.first().map(|pepper| pepper.secret().to_owned());
+
+        self.hashing_pool
+            .run(move || compute_password_hash(&password, params, secret.as_deref()))
+            .await
+            .map_err(hashing_pool_error)?
+            .map_err(|_| PasswordHasherError::UnexpectedError)
+    }
+
+    async fn verify_password(&self, password_hash: &str, password: &Password) -> Result<(), PasswordHasherError> {
+        let password_hash = password_hash.to_string();
+        let password = password.as_ref().to_string();
+        let peppers = self.peppers.clone();
+
+        self.hashing_pool
+            .run(move || verify_password_hash(&password_hash, &password, &peppers))
+            .await
+            .map_err(hashing_pool_error)?
+            .map_err(|_| PasswordHasherError::IncorrectPassword)
+    }
+
+    // Verifying against a hash made with the current settings takes as long as for a real user
+    async fn verify_dummy_password(&self, password: &Password) -> Result<(), PasswordHasherError> {
+        let dummy_password_hash = self.dummy_password_hash
+            .get_or_try_init(|| async {
+                let dummy_password = Password::parse(Uuid::new_v4().to_string()).map_err(|_| PasswordHasherError::UnexpectedError)?;
+                self.hash_password(&dummy_password).await
+            })
+            .await?;
+
+        match self.verify_password(dummy_password_hash, password).await {
+            Err(PasswordHasherError::Overloaded) => Err(PasswordHasherError::Overloaded),
+            _ => Ok(()),
+        }
+    }
+
+    fn needs_rehash(&self, password_hash: &str) -> bool {
+        needs_rehash(password_hash, &self.params)
+    }
+}
+
+fn verify_password_hash(
+    expected_password_hash: &str,
+    password_candidate: &str,
+    peppers: &[PasswordPepper],
+) -> Result<(), Box<dyn Error + Send + Sync>> {
+    if is_bcrypt_hash(expected_password_hash) {
+        return match bcrypt::verify(password_candidate.as_bytes(), expected_password_hash)? {
+            true => Ok(()),
+            false => Err("Password does not match the bcrypt hash".into()),
+        };
+    }
+
+    let expected_password_hash: PasswordHash<'_> = PasswordHash::new(expected_password_hash)?;
+
+    // Imported scrypt and PBKDF2 hashes were never peppered
+    if Algorithm::try_from(expected_password_hash.algorithm).is_err() {
+        expected_password_hash.verify_password(&[&Scrypt, &Pbkdf2], password_candidate.as_bytes())?;
+        return Ok(());
+    }
+
+    // Hashes made before peppering was enabled have no key id
+    let key_id = Params::try_from(&expected_password_hash)?.keyid().to_vec();
+    let argon2 = if key_id.is_empty() {
+        Argon2::default()
+    } else {
+        let pepper = peppers
+            .iter()
+            .find(|pepper| pepper.id().as_bytes() == key_id)
+            .ok_or("The pepper of this password hash is not configured")?;
+        Argon2::new_with_secret(pepper.secret().as_bytes(), Algorithm::default(), Version::default(), Params::default())?
+    };
+
+    argon2
+        .verify_password(password_candidate.as_bytes(), &expected_password_hash)?;
+
+    Ok(())
+}
+
+fn hashing_pool_error(error: HashingPoolError) -> PasswordHasherError {
+    match error {
+        HashingPoolError::Saturated => PasswordHasherError::Overloaded,
+        HashingPoolError::Failed => PasswordHasherError::UnexpectedError,
+    }
+}
+
+// bcrypt predates PHC strings, its hashes look like "$2b$<cost>$<salt and hash>"
+fn is_bcrypt_hash(password_hash: &str) -> bool {
+    ["$2a$", "$2b$", "$2x$", "$2y$"].iter().any(|prefix| password_hash.starts_with(prefix))
+}
+
+// Argon2 hashes plus the legacy formats accepted from imports: bcrypt, scrypt and PBKDF2
+pub fn is_supported_password_hash(password_hash: &str) -> bool {
+    if is_bcrypt_hash(password_hash) {
+        return password_hash.parse::<bcrypt::HashParts>().is_ok();
+    }
+
+    match PasswordHash::new(password_hash) {
+        Ok(password_hash) => {
+            let algorithm = password_hash.algorithm;
+            Algorithm::try_from(algorithm).is_ok()
+                || algorithm == scrypt::ALG_ID
+                || pbkdf2::Algorithm::try_from(algorithm).is_ok()
+        }
+        Err(_) => false,
+    }
+}
+
+fn compute_password_hash(
+    password: &str,
+    params: Params,
+    pepper_secret: Option<&str>,
+) -> Result<String, Box<dyn Error + Send + Sync>> {
+    let salt: SaltString = SaltString::generate(&mut OsRng);
+    let argon2 = match pepper_secret {
+        Some(secret) => Argon2::new_with_secret(secret.as_bytes(), Algorithm::Argon2id, Version::V0x13, params)?,
+        None => Argon2::new(Algorithm::Argon2id, Version::V0x13, params),
+    };
+    let password_hash = argon2
+        .hash_password(password.as_bytes(), &salt)?
+        .to_string();
+
+    Ok(password_hash)
+}
+
+fn with_key_id(params: &Params, key_id: &[u8]) -> Params {
+    ParamsBuilder::new()
+        .m_cost(params.m_cost())
+        .t_cost(params.t_cost())
+        .p_cost(params.p_cost())
+        .keyid(KeyId::new(key_id).expect("Pepper ids are validated on creation"))
+        .build()
+        .expect("Parameters were valid before adding the key id")
+}
+
+// Whether the hash was computed with another algorithm, other parameters or another pepper than the configured ones
+fn needs_rehash(password_hash: &str, params: &Params) -> bool {
+    // Only called after a successful verification, so a hash that is not a PHC string is a bcrypt one
+    let Ok(password_hash) = PasswordHash::new(password_hash) else {
+        return true;
+    };
+
+    let same_algorithm = password_hash.algorithm == Algorithm::Argon2id.ident()
+        && password_hash.version == Some(Version::V0x13.into());
+
+    match Params::try_from(&password_hash) {
+        Ok(hash_params) => !same_algorithm
+            || hash_params.m_cost() != params.m_cost()
+            || hash_params.t_cost() != params.t_cost()
+            || hash_params.p_cost() != params.p_cost()
+            || hash_params.keyid() != params.keyid(),
+        Err(_) => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::services::hashing_pool::HashingPool;
+
+    use super::*;
+
+    fn pepper(id: &str, secret: &str) -> PasswordPepper {
+        PasswordPepper::new(id.to_string(), secret.to_string()).unwrap()
+    }
+
+    #[test]
+    fn test_needs_rehash() {
+        let old_params = Params::new(1024, 1, 1, None).unwrap();
+        let new_params = Params::new(2048, 2, 1, None).unwrap();
+
+        let password_hash = compute_password_hash("password", old_params.clone(), None).unwrap();
+        assert!(verify_password_hash(&password_hash, "password", &[]).is_ok());
+
+        assert!(!needs_rehash(&password_hash, &old_params));
+        assert!(needs_rehash(&password_hash, &new_params));
+        assert!(needs_rehash(&password_hash, &with_key_id(&old_params, b"1")));
+    }
+
+    #[test]
+    fn test_peppered_hashes() {
+        let params = with_key_id(&Params::new(1024, 1, 1, None).unwrap(), b"1");
+        let password_hash = compute_password_hash("password", params.clone(), Some("pepper")).unwrap();
+
+        // The hash alone is not enough, the pepper it names has to be configured
+        assert!(verify_password_hash(&password_hash, "password", &[pepper("1", "pepper")]).is_ok());
+        assert!(verify_password_hash(&password_hash, "password", &[]).is_err());
+        assert!(verify_password_hash(&password_hash, "password", &[pepper("1", "other")]).is_err());
+
+        // After a rotation the old pepper still verifies, but the hash is due for an upgrade
+        let rotated = vec![pepper("2", "new pepper"), pepper("1", "pepper")];
+        assert!(verify_password_hash(&password_hash, "password", &rotated).is_ok());
+        assert!(!needs_rehash(&password_hash, &params));
+        assert!(needs_rehash(&password_hash, &with_key_id(&params, b"2")));
+    }
+
+    #[test]
+    fn test_legacy_hashes() {
+        let bcrypt_hash = bcrypt::hash("password", 4).unwrap();
+        let scrypt_hash = Scrypt
+            .hash_password_customized(b"password", None, None, scrypt::Params::new(4, 8, 1, 32).unwrap(), &SaltString::generate(&mut OsRng))
+            .unwrap()
+            .to_string();
+        let pbkdf2_hash = Pbkdf2
+            .hash_password_customized(b"password", None, None, pbkdf2::Params { rounds: 1000, output_length: 32 }, &SaltString::generate(&mut OsRng))
+            .unwrap()
+            .to_string();
+
+        let params = Params::new(1024, 1, 1, None).unwrap();
+        for legacy_hash in [bcrypt_hash, scrypt_hash, pbkdf2_hash] {
+            assert!(is_supported_password_hash(&legacy_hash), "Unsupported {}", legacy_hash);
+            assert!(verify_password_hash(&legacy_hash, "password", &[]).is_ok());
+            assert!(verify_password_hash(&legacy_hash, "wrong password", &[]).is_err());
+            assert!(needs_rehash(&legacy_hash, &params));
+        }
+
+        assert!(!is_supported_password_hash("$md5$not-supported"));
+        assert!(!is_supported_password_hash("plaintext password"));
+    }
+
+    #[tokio::test]
+    async fn test_argon2_password_hasher() {
+        let params = Params::new(1024, 1, 1, None).unwrap();
+        let hashing_pool = std::sync::Arc::new(HashingPool::new(2, 16));
+        let hasher = Argon2PasswordHasher::new(params, vec![pepper("1", "pepper")], hashing_pool);
+
+        let password = Password::parse("password".to_string()).unwrap();
+        let wrong_password = Password::parse("wrong password".to_string()).unwrap();
+        let password_hash = hasher.hash_password(&password).await.unwrap();
+
+        assert!(password_hash.starts_with("$argon2id$"));
+        assert!(hasher.verify_password(&password_hash, &password).await.is_ok());
+        assert_eq!(Err(PasswordHasherError::IncorrectPassword), hasher.verify_password(&password_hash, &wrong_password).await);
+        assert!(!hasher.needs_rehash(&password_hash));
+        assert!(hasher.verify_dummy_password(&password).await.is_ok());
+    }
+
+    #[test]
+    fn test_pepper_validation() {
+        assert!(PasswordPepper::new("".to_string(), "secret".to_string()).is_err());
+        assert!(PasswordPepper::new("123456789".to_string(), "secret".to_string()).is_err());
+        assert!(PasswordPepper::new("1".to_string(), "".to_string()).is_err());
+    }
+}
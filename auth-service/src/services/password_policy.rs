@@ -0,0 +1,306 @@
+use std::sync::Arc;
+
+use chrono::{DateTime, Utc};
+
+use crate::{app_state::BreachedPasswordCheckerType, domain::{Email, Password, PasswordPolicy, PasswordPolicyOverrides, PasswordPolicyViolation}};
+
+// Passwords (and their base words) that are among the first guesses of any attacker
+const COMMON_PASSWORDS: &[&str] = &[
+    "password", "123456", "12345678", "qwerty", "qwertyuiop", "letmein", "welcome", "admin", "administrator",
+    "iloveyou", "monkey", "dragon", "football", "baseball", "master", "sunshine", "shadow", "princess",
+    "trustno", "superman", "batman", "starwars", "login", "hello", "freedom", "whatever", "qazwsx",
+    "secret", "michael", "jennifer", "charlie", "computer", "hunter", "soccer", "jordan", "harley",
+    "ranger", "buster", "tigger", "pepper", "summer", "winter", "changeme", "default", "guest", "root",
+];
+
+// Guessing one dictionary word takes roughly a hundred attempts
+const DICTIONARY_WORD_LOG10_GUESSES: f64 = 2.0;
+
+// Enforces length limits in characters, a minimum strength score, that the password does not
+// contain the email local part and, when a checker is configured, that it is not known from a breach.
+// Passwords expire after `max_age_days`, 0 disables expiry.
+#[derive(Clone)]
+pub struct StrengthPasswordPolicy {
+    min_length: usize,
+    max_length: usize,
+    min_score: u8,
+    history_length: usize,
+    max_age_days: i64,
+    breached_password_checker: Option<BreachedPasswordCheckerType>,
+}
+
+impl StrengthPasswordPolicy {
+    pub fn new(
+        min_length: usize,
+        max_length: usize,
+        min_score: u8,
+        history_length: usize,
+        max_age_days: i64,
+        breached_password_checker: Option<BreachedPasswordCheckerType>,
+    ) -> Self {
+        Self { min_length, max_length, min_score, history_length, max_age_days, breached_password_checker }
+    }
+}
+
+impl PasswordPolicy for StrengthPasswordPolicy {
+    fn check(&self, password: &Password, email: &Email) -> Result<(), Vec<PasswordPolicyViolation>> {
+        let mut violations = Vec::new();
+
+        let length = password.as_ref().chars().count();
+        if length < self.min_length {
+            violations.push(PasswordPolicyViolation::TooShort { min_length: self.min_length });
+        }
+        if length > self.max_length {
+            violations.push(PasswordPolicyViolation::TooLong { max_length: self.max_length });
+        }
+
+        let local_part = email.as_ref().rsplit_once('@').map(|(local, _)| local).unwrap_or_default();
+        if local_part.chars().count() >= 3 && password.as_ref().to_lowercase().contains(local_part) {
+            violations.push(PasswordPolicyViolation::ContainsEmail);
+        }
+
+        let score = strength_score(password.as_ref(), &[local_part]);
+        if score < self.min_score {
+            violations.push(PasswordPolicyViolation::TooWeak { score, min_score: self.min_score });
+        }
+
+        if self.breached_password_checker.as_ref().is_some_and(|checker| checker.is_breached(password)) {
+            violations.push(PasswordPolicyViolation::Breached);
+        }
+
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(violations)
+        }
+    }
+
+    fn history_length(&self) -> usize {
+        self.history_length
+    }
+
+    fn is_expired(&self, password_changed_at: DateTime<Utc>) -> bool {
+        match chrono::Duration::try_days(self.max_age_days) {
+            Some(max_age) if self.max_age_days > 0 => password_changed_at + max_age <= Utc::now(),
+            _ => false,
+        }
+    }
+
+    // Overrides can only tighten the policy, weaker values are ignored. The maximum length is not overridable,
+    // it protects the hashing.
+    fn with_overrides(&self, overrides: &PasswordPolicyOverrides) -> Arc<dyn PasswordPolicy> {
+        let max_age_days = match overrides.max_age_days {
+            Some(days) if days > 0 && (self.max_age_days <= 0 || days < self.max_age_days) => days,
+            _ => self.max_age_days,
+        };
+
+        Arc::new(Self {
+            min_length: overrides.min_length.unwrap_or(self.min_length).max(self.min_length).min(self.max_length),
+            min_score: overrides.min_score.unwrap_or(self.min_score).max(self.min_score).min(4),
+            history_length: overrides.history_length.unwrap_or(self.history_length).max(self.history_length),
+            max_age_days,
+            ..self.clone()
+        })
+    }
+}
+
+// Score from 0 (too guessable) to 4 (very unguessable) using the same guess thresholds as zxcvbn
+pub fn strength_score(password: &str, user_inputs: &[&str]) -> u8 {
+    match estimate_log10_guesses(password, user_inputs) {
+        g if g < 3.0 => 0,
+        g if g < 6.0 => 1,
+        g if g < 8.0 => 2,
+        g if g < 10.0 => 3,
+        _ => 4,
+    }
+}
+
+fn estimate_log10_guesses(password: &str, user_inputs: &[&str]) -> f64 {
+    // Try the password as typed, lowercased and with l33t substitutions undone, the cheapest attack wins
+    let lowercase = password.to_lowercase();
+    let unleeted: String = lowercase.chars().map(unleet).collect();
+
+    [password, &lowercase, &unleeted]
+        .into_iter()
+        .map(|candidate| {
+            let (remainder, words) = strip_words(candidate, user_inputs);
+            words as f64 * DICTIONARY_WORD_LOG10_GUESSES + brute_force_log10_guesses(&remainder)
+        })
+        .fold(f64::INFINITY, f64::min)
+}
+
+// Remove every common password and user input, returning what is left and how many were found
+fn strip_words(candidate: &str, user_inputs: &[&str]) -> (String, usize) {
+    let mut remainder = candidate.to_string();
+    let mut words = 0;
+
+    for word in COMMON_PASSWORDS.iter().chain(user_inputs.iter()).filter(|word| word.chars().count() >= 3) {
+        let count = remainder.matches(word).count();
+        if count > 0 {
+            words += count;
+            remainder = remainder.replace(word, "");
+        }
+    }
+
+    (remainder, words)
+}
+
+// Repeated characters and sequences like "abc" or "123" barely count
+fn brute_force_log10_guesses(s: &str) -> f64 {
+    let mut effective_length = 0;
+    let mut previous: Option<char> = None;
+    for c in s.chars() {
+        let continues_run = previous.is_some_and(|p| (c as i64 - p as i64).abs() <= 1);
+        if !continues_run {
+            effective_length += 1;
+        }
+        previous = Some(c);
+    }
+
+    effective_length as f64 * (charset_size(s) as f64).log10()
+}
+
+fn charset_size(password: &str) -> u32 {
+    let mut size = 0;
+    if password.chars().any(|c| c.is_ascii_lowercase()) {
+        size += 26;
+    }
+    if password.chars().any(|c| c.is_ascii_uppercase()) {
+        size += 26;
+    }
+    if password.chars().any(|c| c.is_ascii_digit()) {
+        size += 10;
+    }
+    if password.chars().any(|c| c.is_ascii_punctuation() || c == ' ') {
+        size += 33;
+    }
+    if !password.is_ascii() {
+        size += 100;
+    }
+    size.max(1)
+}
+
+fn unleet(c: char) -> char {
+    match c {
+        '4' | '@' => 'a',
+        '3' => 'e',
+        '1' => 'i',
+        '0' => 'o',
+        '5' | '$' => 's',
+        '7' => 't',
+        _ => c,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::services::breached_password_checker::{password_digest, BloomFilter, BloomFilterBreachedPasswordChecker};
+
+    fn policy() -> StrengthPasswordPolicy {
+        StrengthPasswordPolicy::new(8, 64, 2, 0, 0, None)
+    }
+
+    fn email() -> Email {
+        Email::parse("alice@example.com".to_string()).unwrap()
+    }
+
+    #[test]
+    fn test_strong_password_is_accepted() {
+        let password = Password::parse("S3cure-Passw0rd!".to_string()).unwrap();
+        assert!(policy().check(&password, &email()).is_ok());
+    }
+
+    #[test]
+    fn test_common_passwords_are_weak() {
+        for password in ["password123", "P@ssw0rd!", "12345678", "aaaaaaaaaa", "abcdefghij"] {
+            assert!(strength_score(password, &[]) < 2, "Failed for {}", password);
+        }
+    }
+
+    #[test]
+    fn test_length_is_counted_in_characters() {
+        let policy = StrengthPasswordPolicy::new(10, 12, 0, 0, 0, None);
+
+        // 8 characters but 16 bytes
+        let password = Password::parse("ääääääää".to_string()).unwrap();
+        let violations = policy.check(&password, &email()).unwrap_err();
+        assert_eq!(violations, vec![PasswordPolicyViolation::TooShort { min_length: 10 }]);
+
+        let password = Password::parse("S3cure-Passw0rd!".to_string()).unwrap();
+        let violations = policy.check(&password, &email()).unwrap_err();
+        assert_eq!(violations, vec![PasswordPolicyViolation::TooLong { max_length: 12 }]);
+    }
+
+    #[test]
+    fn test_every_failed_rule_is_reported() {
+        let password = Password::parse("alice123".to_string()).unwrap();
+        let violations = policy().check(&password, &email()).unwrap_err();
+
+        assert!(violations.contains(&PasswordPolicyViolation::ContainsEmail));
+        assert!(violations.iter().any(|v| matches!(v, PasswordPolicyViolation::TooWeak { .. })));
+    }
+
+    #[test]
+    fn test_breached_password_is_rejected() {
+        let mut filter = BloomFilter::new(1, 0.001);
+        filter.insert(&password_digest("Tr0ub4dor&3x"));
+        let checker = Arc::new(BloomFilterBreachedPasswordChecker::new(filter));
+        let policy = StrengthPasswordPolicy::new(8, 64, 2, 0, 0, Some(checker));
+
+        let password = Password::parse("Tr0ub4dor&3x".to_string()).unwrap();
+        assert_eq!(policy.check(&password, &email()).unwrap_err(), vec![PasswordPolicyViolation::Breached]);
+
+        let password = Password::parse("S3cure-Passw0rd!".to_string()).unwrap();
+        assert!(policy.check(&password, &email()).is_ok());
+    }
+
+    #[test]
+    fn test_password_expiry() {
+        let expiring_policy = StrengthPasswordPolicy::new(8, 64, 2, 0, 90, None);
+        assert!(!expiring_policy.is_expired(Utc::now() - chrono::Duration::days(89)));
+        assert!(expiring_policy.is_expired(Utc::now() - chrono::Duration::days(90)));
+
+        // Expiry is disabled with a max age of 0 days
+        assert!(!policy().is_expired(Utc::now() - chrono::Duration::days(3650)));
+    }
+
+    #[test]
+    fn test_overrides_replace_only_the_given_settings() {
+        let overrides = PasswordPolicyOverrides { min_length: Some(20), max_age_days: Some(30), ..Default::default() };
+        let tenant_policy = policy().with_overrides(&overrides);
+
+        let password = Password::parse("S3cure-Passw0rd!".to_string()).unwrap();
+        assert_eq!(tenant_policy.check(&password, &email()).unwrap_err(), vec![PasswordPolicyViolation::TooShort { min_length: 20 }]);
+        assert!(tenant_policy.is_expired(Utc::now() - chrono::Duration::days(30)));
+        assert_eq!(tenant_policy.history_length(), 0);
+
+        // The minimum length never exceeds the maximum
+        let overrides = PasswordPolicyOverrides { min_length: Some(1000), ..Default::default() };
+        let password = Password::parse("Correct-Horse-Battery-Staple-42-and-then-some".to_string()).unwrap();
+        assert_eq!(
+            policy().with_overrides(&overrides).check(&password, &email()).unwrap_err(),
+            vec![PasswordPolicyViolation::TooShort { min_length: 64 }],
+        );
+    }
+
+    #[test]
+    fn test_overrides_cannot_weaken_the_policy() {
+        let strict_policy = StrengthPasswordPolicy::new(12, 64, 3, 5, 90, None);
+        let overrides = PasswordPolicyOverrides { min_length: Some(4), min_score: Some(0), history_length: Some(0), max_age_days: Some(0) };
+        let tenant_policy = strict_policy.with_overrides(&overrides);
+
+        let password = Password::parse("Zq8#vL2".to_string()).unwrap();
+        assert!(tenant_policy.check(&password, &email()).unwrap_err().contains(&PasswordPolicyViolation::TooShort { min_length: 12 }));
+        assert_eq!(tenant_policy.history_length(), 5);
+        assert!(tenant_policy.is_expired(Utc::now() - chrono::Duration::days(90)));
+
+        // A longer max age is weaker, a shorter one applies
+        let overrides = PasswordPolicyOverrides { max_age_days: Some(365), ..Default::default() };
+        assert!(strict_policy.with_overrides(&overrides).is_expired(Utc::now() - chrono::Duration::days(90)));
+        let overrides = PasswordPolicyOverrides { max_age_days: Some(30), ..Default::default() };
+        assert!(strict_policy.with_overrides(&overrides).is_expired(Utc::now() - chrono::Duration::days(30)));
+    }
+}
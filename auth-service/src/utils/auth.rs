@@ -1,21 +1,72 @@
-use axum_extra::extract::cookie::{Cookie, SameSite};
-use chrono::Utc;
+use axum::{extract::{Request, State}, http::{header, HeaderMap, HeaderValue}, middleware::Next, response::{IntoResponse, Response}};
+use axum_extra::extract::{cookie::{Cookie, SameSite}, CookieJar};
+use chrono::{DateTime, Utc};
 use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Validation};
 use serde::{Deserialize, Serialize};
 
-use crate::{app_state::BannedTokenStoreType, domain::{email::Email, data_store::BannedTokenStore}};
+use crate::{
+    app_state::{AppState, BannedTokenStoreType, UserStoreType},
+    domain::{
+        data_store::BannedTokenStore, AuthAPIError, PersonalAccessToken, PersonalAccessTokenStatus, TenantId, TokenExchangeClient, User,
+        UserId, UserRoles, PERSONAL_ACCESS_TOKEN_PREFIX,
+    },
+};
 
 use super::constants::{JWT_COOKIE_NAME, JWT_SECRET};
 
 // This is definitely NOT a good secret. We will update it soon!
 // const JWT_SECRET: &str = "secret";
 
-// Create cookie with a new JWT auth token
-pub fn generate_auth_cookie(email: &Email) -> Result<Cookie<'static>, GenerateTokenError> {
-    let token = generate_auth_token(email)?;
+// Create cookie with a new JWT auth token carrying the user's organization, roles and permissions
+pub fn generate_auth_cookie(user: &User, user_roles: &UserRoles) -> Result<Cookie<'static>, GenerateTokenError> {
+    let token = generate_auth_token(&user.get_id(), &user.get_tenant_id(), None, user_roles)?;
     Ok(create_auth_cookie(token))
 }
 
+// Create cookie with a JWT auth token that is only accepted by the routes of `scope`, it never grants any permission
+pub fn generate_restricted_auth_cookie(user: &User, scope: TokenScope) -> Result<Cookie<'static>, GenerateTokenError> {
+    let token = generate_auth_token(&user.get_id(), &user.get_tenant_id(), Some(scope), &UserRoles::default())?;
+    Ok(create_auth_cookie(token))
+}
+
+// Create cookie with a token that lets an admin see what `user` sees. The admin is named in the `act` claim, and the token
+// carries the user's roles but none of their permissions, so it cannot be used to reach the admin routes.
+pub fn generate_impersonation_cookie(user: &User, user_roles: &UserRoles, actor_id: &UserId, ttl_seconds: i64) -> Result<Cookie<'static>, GenerateTokenError> {
+    let claims = Claims {
+        roles: user_roles.roles.clone(),
+        act: Some(Actor { sub: actor_id.to_string() }),
+        ..new_claims(&user.get_id(), &user.get_tenant_id(), ttl_seconds)?
+    };
+
+    let token = create_token(&claims).map_err(GenerateTokenError::TokenError)?;
+    Ok(create_auth_cookie(token))
+}
+
+// Create a token for `client` to call the service of `audience` on behalf of the user of `subject`, as of RFC 8693.
+// It carries the requested `permissions` only, names the client in the `act` claim and expires with the subject token
+// at the latest. Its `aud` claim keeps it from being accepted anywhere but by `audience`.
+pub fn generate_exchanged_token(
+    subject: &Claims,
+    client: &TokenExchangeClient,
+    audience: &str,
+    permissions: Vec<String>,
+    ttl_seconds: i64,
+) -> Result<(String, Claims), GenerateTokenError> {
+    let user_id = UserId::parse(subject.sub.clone()).map_err(|_| GenerateTokenError::UnexpectedError)?;
+    let claims = new_claims(&user_id, &subject.tenant, ttl_seconds)?;
+
+    let claims = Claims {
+        exp: claims.exp.min(subject.exp),
+        permissions,
+        act: Some(Actor { sub: client.id().to_string() }),
+        aud: Some(audience.to_string()),
+        ..claims
+    };
+
+    let token = create_token(&claims).map_err(GenerateTokenError::TokenError)?;
+    Ok((token, claims))
+}
+
 // Create cookie and set the value to the passed-in token string 
 fn create_auth_cookie(token: String) -> Cookie<'static> {
     let cookie = Cookie::build((JWT_COOKIE_NAME, token))
@@ -37,12 +88,26 @@ pub enum GenerateTokenError {
 pub const TOKEN_TTL_SECONDS: i64 = 600; // 10 minutes
 
 // Create JWT auth token
-fn generate_auth_token(email: &Email) -> Result<String, GenerateTokenError> {
-    let delta = chrono::Duration::try_seconds(TOKEN_TTL_SECONDS)
+fn generate_auth_token(user_id: &UserId, tenant_id: &TenantId, scope: Option<TokenScope>, user_roles: &UserRoles) -> Result<String, GenerateTokenError> {
+    let claims = Claims {
+        scope,
+        roles: user_roles.roles.clone(),
+        permissions: user_roles.permissions.clone(),
+        ..new_claims(user_id, tenant_id, TOKEN_TTL_SECONDS)?
+    };
+
+    create_token(&claims).map_err(GenerateTokenError::TokenError)
+}
+
+// Claims of a token valid for `ttl_seconds` from now, granting nothing
+fn new_claims(user_id: &UserId, tenant_id: &TenantId, ttl_seconds: i64) -> Result<Claims, GenerateTokenError> {
+    let delta = chrono::Duration::try_seconds(ttl_seconds)
         .ok_or(GenerateTokenError::UnexpectedError)?;
 
+    let now = Utc::now();
+
     // Create JWT expiration time
-    let exp = Utc::now()
+    let exp = now
         .checked_add_signed(delta)
         .ok_or(GenerateTokenError::UnexpectedError)?
         .timestamp();
@@ -52,27 +117,247 @@ fn generate_auth_token(email: &Email) -> Result<String, GenerateTokenError> {
         .try_into()
         .map_err(|_| GenerateTokenError::UnexpectedError)?;
 
-    let sub = email.as_ref().to_owned();
-
-    let claims = Claims { sub, exp };
+    // Issued-at time lets us revoke every token of a user issued before a given moment
+    let iat: usize = now
+        .timestamp()
+        .try_into()
+        .map_err(|_| GenerateTokenError::UnexpectedError)?;
 
-    create_token(&claims).map_err(GenerateTokenError::TokenError)
+    Ok(Claims {
+        sub: user_id.to_string(),
+        exp,
+        iat,
+        iat_us: Some(now.timestamp_micros()),
+        tenant: *tenant_id,
+        scope: None,
+        roles: Vec::new(),
+        permissions: Vec::new(),
+        act: None,
+        aud: None,
+        pat: None,
+    })
 }
 
-// Check if JWT auth token is valid by decoding it using the JWT secret
+// Check if JWT auth token is valid by decoding it using the JWT secret.
+// Tokens restricted to an audience are not valid here.
 pub async fn validate_token(token: &str, banned_token_store: BannedTokenStoreType) -> Result<Claims, String> {
+    validate_token_with_scope(token, banned_token_store, None).await
+}
+
+// Same as `validate_token`, but tokens exchanged for `audience` are accepted as well
+pub async fn validate_token_for_audience(token: &str, banned_token_store: BannedTokenStoreType, audience: &str) -> Result<Claims, String> {
+    let mut validation = Validation::default();
+    validation.set_audience(&[audience]);
+
+    validate_token_with_validation(token, banned_token_store, None, &validation).await
+}
+
+// Same as `validate_token`, but restricted tokens of `allowed_scope` are accepted as well
+pub async fn validate_token_with_scope(
+    token: &str,
+    banned_token_store: BannedTokenStoreType,
+    allowed_scope: Option<TokenScope>,
+) -> Result<Claims, String> {
+    validate_token_with_validation(token, banned_token_store, allowed_scope, &Validation::default()).await
+}
+
+async fn validate_token_with_validation(
+    token: &str,
+    banned_token_store: BannedTokenStoreType,
+    allowed_scope: Option<TokenScope>,
+    validation: &Validation,
+) -> Result<Claims, String> {
     let banned_token_store = banned_token_store.read().await;
     if banned_token_store.token_is_banned(token).await.unwrap_or(false) {
         return Err("token is banned".to_string());
     }
 
-    decode::<Claims>(
+    let claims = decode::<Claims>(
         token,
         &DecodingKey::from_secret(JWT_SECRET.as_bytes()),
-        &Validation::default(),
+        validation,
     )
     .map(|data| data.claims)
-    .map_err(|err| format!("{}", err))
+    .map_err(|err| format!("{}", err))?;
+
+    if banned_token_store.user_token_is_banned(&claims.sub, claims.issued_at()).await.unwrap_or(false) {
+        return Err("token is banned".to_string());
+    }
+
+    // Revoking the sessions of an admin also ends the impersonations they started
+    if let Some(actor) = &claims.act {
+        if banned_token_store.user_token_is_banned(&actor.sub, claims.issued_at()).await.unwrap_or(false) {
+            return Err("token is banned".to_string());
+        }
+    }
+
+    if claims.scope.is_some() && claims.scope != allowed_scope {
+        return Err("token is restricted".to_string());
+    }
+
+    Ok(claims)
+}
+
+// Extract the JWT auth token from the cookie jar and validate it
+pub async fn validate_auth_cookie(jar: &CookieJar, banned_token_store: BannedTokenStoreType) -> Result<(String, Claims), AuthAPIError> {
+    validate_auth_cookie_with_scope(jar, banned_token_store, None).await
+}
+
+// Every route authenticated with the cookie acts on the account, so impersonation tokens are refused here.
+// They are only accepted by `/verify-token` and `/logout`. Personal access tokens are refused as well, whatever
+// their scopes they would otherwise give full access to the account, and could be traded for new tokens.
+pub async fn validate_auth_cookie_with_scope(
+    jar: &CookieJar,
+    banned_token_store: BannedTokenStoreType,
+    allowed_scope: Option<TokenScope>,
+) -> Result<(String, Claims), AuthAPIError> {
+    let (token, claims) = validate_cookie_token(jar, banned_token_store, allowed_scope).await?;
+
+    if claims.pat.is_some() {
+        return Err(AuthAPIError::PersonalAccessTokenNotAllowed);
+    }
+
+    Ok((token, claims))
+}
+
+// Same as `validate_auth_cookie`, but the token must also grant `permission`. Personal access tokens are accepted,
+// within their scopes.
+pub async fn validate_auth_cookie_with_permission(
+    jar: &CookieJar,
+    banned_token_store: BannedTokenStoreType,
+    permission: &str,
+) -> Result<(String, Claims), AuthAPIError> {
+    let (token, claims) = validate_cookie_token(jar, banned_token_store, None).await?;
+
+    if !claims.permissions.iter().any(|granted| granted == permission) {
+        return Err(AuthAPIError::Forbidden);
+    }
+
+    Ok((token, claims))
+}
+
+async fn validate_cookie_token(
+    jar: &CookieJar,
+    banned_token_store: BannedTokenStoreType,
+    allowed_scope: Option<TokenScope>,
+) -> Result<(String, Claims), AuthAPIError> {
+    let token = match jar.get(JWT_COOKIE_NAME) {
+        Some(cookie) => cookie.value().to_owned(),
+        None => return Err(AuthAPIError::MissingToken)
+    };
+
+    let claims = validate_token_with_scope(&token, banned_token_store, allowed_scope)
+        .await
+        .map_err(|_| AuthAPIError::InvalidToken)?;
+
+    if claims.act.is_some() {
+        return Err(AuthAPIError::ImpersonationNotAllowed);
+    }
+
+    Ok((token, claims))
+}
+
+// Ends every session of the user: their JWTs are banned and their personal access tokens revoked
+pub async fn revoke_user_tokens(state: &AppState, user_id: &UserId) -> Result<(), AuthAPIError> {
+    state.banned_token_store.write().await
+        .ban_user_tokens(&user_id.to_string())
+        .await
+        .map_err(|_| AuthAPIError::UnexpectedError)?;
+
+    state.personal_access_token_store.write().await
+        .revoke_user_tokens(user_id)
+        .await
+        .map_err(|_| AuthAPIError::UnexpectedError)
+}
+
+// Load the user the validated token was issued for
+pub async fn get_claims_user(claims: &Claims, user_store: UserStoreType) -> Result<User, AuthAPIError> {
+    let user_id = UserId::parse(claims.sub.clone()).map_err(|_| AuthAPIError::InvalidToken)?;
+
+    let user_store = user_store.read().await;
+    user_store.get_user_by_id(&user_id).await.map_err(|_| AuthAPIError::InvalidToken)
+}
+
+// Requests authenticated with a personal access token get a JWT that only lives as long as the request
+const PERSONAL_ACCESS_TOKEN_SESSION_TTL_SECONDS: i64 = 60;
+// The last use of a token is recorded at most this often, so busy scripts do not write on every request
+const PERSONAL_ACCESS_TOKEN_LAST_USED_RESOLUTION_SECONDS: i64 = 60;
+
+// Claims of a request authenticated with a personal access token: the scopes of the token the user still has as permissions, and no
+// roles. The tokens of users who cannot log in freely, like locked accounts, are refused.
+pub async fn validate_personal_access_token(token: &str, state: &AppState) -> Result<Claims, AuthAPIError> {
+    let prefix = PersonalAccessToken::parse_prefix(token).ok_or(AuthAPIError::InvalidToken)?;
+    let personal_access_token = state.personal_access_token_store.read().await
+        .get_token_by_prefix(prefix)
+        .await
+        .map_err(|_| AuthAPIError::InvalidToken)?;
+
+    if !personal_access_token.verify(token) || personal_access_token.status(Utc::now()) != PersonalAccessTokenStatus::Active {
+        return Err(AuthAPIError::InvalidToken);
+    }
+
+    let account = state.user_store.read().await
+        .get_user_account(&personal_access_token.user_id)
+        .await
+        .map_err(|_| AuthAPIError::InvalidToken)?;
+    if account.locked || account.approval_pending || account.password_reset_required || account.deletion_scheduled_at.is_some() {
+        return Err(AuthAPIError::InvalidToken);
+    }
+
+    let user_roles = state.role_store.read().await.get_user_roles(&account.user.get_id()).await?;
+    let permissions = personal_access_token.scopes
+        .into_iter()
+        .filter(|scope| user_roles.permissions.contains(scope))
+        .collect();
+
+    let recently_used = personal_access_token.last_used_at.is_some_and(|last_used_at| {
+        Utc::now() - last_used_at < chrono::Duration::seconds(PERSONAL_ACCESS_TOKEN_LAST_USED_RESOLUTION_SECONDS)
+    });
+    if !recently_used {
+        state.personal_access_token_store.write().await.record_use(&personal_access_token.id).await?;
+    }
+
+    let claims = new_claims(&account.user.get_id(), &account.user.get_tenant_id(), PERSONAL_ACCESS_TOKEN_SESSION_TTL_SECONDS)
+        .map_err(|_| AuthAPIError::UnexpectedError)?;
+
+    Ok(Claims {
+        permissions,
+        pat: Some(personal_access_token.id.to_string()),
+        ..claims
+    })
+}
+
+// Lets scripts send a personal access token, or a JWT, as `Authorization: Bearer` wherever the `jwt` cookie is accepted.
+// The bearer token takes the place of the cookies of the request, a personal access token being swapped for a JWT.
+pub async fn authenticate_bearer_token(State(state): State<AppState>, mut request: Request, next: Next) -> Response {
+    let Some(token) = bearer_token(request.headers()) else {
+        return next.run(request).await;
+    };
+
+    let token = match token.starts_with(PERSONAL_ACCESS_TOKEN_PREFIX) {
+        true => match validate_personal_access_token(&token, &state).await {
+            Ok(claims) => match create_token(&claims) {
+                Ok(token) => token,
+                Err(_) => return AuthAPIError::UnexpectedError.into_response(),
+            },
+            Err(e) => return e.into_response(),
+        },
+        false => token,
+    };
+
+    let Ok(cookie) = HeaderValue::from_str(&format!("{}={}", JWT_COOKIE_NAME, token)) else {
+        return AuthAPIError::InvalidToken.into_response();
+    };
+    request.headers_mut().insert(header::COOKIE, cookie);
+
+    next.run(request).await
+}
+
+pub fn bearer_token(headers: &HeaderMap) -> Option<String> {
+    let authorization = headers.get(header::AUTHORIZATION)?.to_str().ok()?;
+    let token = authorization.strip_prefix("Bearer ")?.trim();
+
+    (!token.is_empty()).then(|| token.to_owned())
 }
 
 // Create JWT auth token by encoding claims using the JWT secret
@@ -88,6 +373,52 @@ fn create_token(claims: &Claims) -> Result<String, jsonwebtoken::errors::Error>
 pub struct Claims {
     pub sub: String,
     pub exp: usize,
+    pub iat: usize,
+    // `iat` in microseconds, so that a token issued in the same second as a revocation is told apart from older ones
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub iat_us: Option<i64>,
+    // Tokens issued before organizations existed belong to the default one
+    #[serde(default)]
+    pub tenant: TenantId,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<TokenScope>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub roles: Vec<String>,
+    // The permissions granted by the roles, unrelated to the restricting `scope`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub permissions: Vec<String>,
+    // RFC 8693 actor, the admin impersonating the user or the client the token was exchanged for
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub act: Option<Actor>,
+    // The only service an exchanged token is valid for
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub aud: Option<String>,
+    // Id of the personal access token the request was authenticated with
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pat: Option<String>,
+}
+
+impl Claims {
+    // Tokens issued before `iat_us` existed count from the start of their second
+    pub fn issued_at(&self) -> DateTime<Utc> {
+        self.iat_us
+            .and_then(DateTime::from_timestamp_micros)
+            .or_else(|| DateTime::from_timestamp(self.iat as i64, 0))
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Actor {
+    pub sub: String,
+}
+
+// Restricted tokens carry a scope and are rejected everywhere except by the routes of that scope
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TokenScope {
+    // Issued on login when the password has expired
+    ChangePassword,
 }
 
 #[cfg(test)]
@@ -96,14 +427,14 @@ mod tests {
 
     use tokio::sync::RwLock;
 
-    use crate::services::data_store::hashset_token_store::HashsetBannedTokenStore;
+    use crate::{domain::{Email, Password}, services::data_store::hashset_token_store::HashsetBannedTokenStore};
 
     use super::*;
 
     #[tokio::test]
     async fn test_generate_auth_cookie() {
-        let email = Email::parse("test@example.com".to_owned()).unwrap();
-        let cookie = generate_auth_cookie(&email).unwrap();
+        let user = User::new(Email::parse("alice@example.com".to_string()).unwrap(), Password::parse("password".to_string()).unwrap(), false);
+        let cookie = generate_auth_cookie(&user, &UserRoles::default()).unwrap();
         assert_eq!(cookie.name(), JWT_COOKIE_NAME);
         assert_eq!(cookie.value().split('.').count(), 3);
         assert_eq!(cookie.path(), Some("/"));
@@ -124,18 +455,18 @@ mod tests {
 
     #[tokio::test]
     async fn test_generate_auth_token() {
-        let email = Email::parse("test@example.com".to_owned()).unwrap();
-        let result = generate_auth_token(&email).unwrap();
+        let user_id = UserId::default();
+        let result = generate_auth_token(&user_id, &TenantId::default(), None, &UserRoles::default()).unwrap();
         assert_eq!(result.split('.').count(), 3);
     }
 
     #[tokio::test]
     async fn test_validate_token_with_valid_token() {
-        let email = Email::parse("test@example.com".to_owned()).unwrap();
-        let token = generate_auth_token(&email).unwrap();
+        let user_id = UserId::default();
+        let token = generate_auth_token(&user_id, &TenantId::default(), None, &UserRoles::default()).unwrap();
         let banned_token_store  = Arc::new(RwLock::new(HashsetBannedTokenStore::default()));
         let result = validate_token(&token, banned_token_store).await.unwrap();
-        assert_eq!(result.sub, "test@example.com");
+        assert_eq!(result.sub, user_id.to_string());
 
         let exp = Utc::now()
             .checked_add_signed(chrono::Duration::try_minutes(9).expect("valid duration"))
@@ -152,4 +483,115 @@ mod tests {
         let result = validate_token(&token, banned_token_store).await;
         assert!(result.is_err());
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_validate_token_with_banned_user_tokens() {
+        let user_id = UserId::default();
+        let token = generate_auth_token(&user_id, &TenantId::default(), None, &UserRoles::default()).unwrap();
+        let banned_token_store  = Arc::new(RwLock::new(HashsetBannedTokenStore::default()));
+
+        banned_token_store.write().await.ban_user_tokens(&user_id.to_string()).await.unwrap();
+
+        let result = validate_token(&token, banned_token_store.clone()).await;
+        assert!(result.is_err());
+
+        let new_token = generate_auth_token(&user_id, &TenantId::default(), None, &UserRoles::default()).unwrap();
+        let result = validate_token(&new_token, banned_token_store).await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_restricted_token_is_only_valid_for_its_scope() {
+        let user_id = UserId::default();
+        let token = generate_auth_token(&user_id, &TenantId::default(), Some(TokenScope::ChangePassword), &UserRoles::default()).unwrap();
+        let banned_token_store  = Arc::new(RwLock::new(HashsetBannedTokenStore::default()));
+
+        let result = validate_token(&token, banned_token_store.clone()).await;
+        assert!(result.is_err());
+
+        let result = validate_token_with_scope(&token, banned_token_store, Some(TokenScope::ChangePassword)).await.unwrap();
+        assert_eq!(result.scope, Some(TokenScope::ChangePassword));
+    }
+
+    #[tokio::test]
+    async fn test_token_carries_roles_and_permissions() {
+        let user_id = UserId::default();
+        let user_roles = UserRoles {
+            roles: vec!["admin".to_string()],
+            permissions: vec!["roles:manage".to_string()],
+        };
+        let token = generate_auth_token(&user_id, &TenantId::default(), None, &user_roles).unwrap();
+        let banned_token_store  = Arc::new(RwLock::new(HashsetBannedTokenStore::default()));
+
+        let result = validate_token(&token, banned_token_store).await.unwrap();
+        assert_eq!(result.roles, user_roles.roles);
+        assert_eq!(result.permissions, user_roles.permissions);
+    }
+
+    #[tokio::test]
+    async fn test_impersonation_token() {
+        let user = User::new(Email::parse("alice@example.com".to_string()).unwrap(), Password::parse("password".to_string()).unwrap(), false);
+        let user_roles = UserRoles {
+            roles: vec!["admin".to_string()],
+            permissions: vec!["roles:manage".to_string()],
+        };
+        let admin_id = UserId::default();
+        let cookie = generate_impersonation_cookie(&user, &user_roles, &admin_id, 60).unwrap();
+        let banned_token_store  = Arc::new(RwLock::new(HashsetBannedTokenStore::default()));
+
+        let claims = validate_token(cookie.value(), banned_token_store.clone()).await.unwrap();
+        assert_eq!(claims.sub, user.get_id().to_string());
+        assert_eq!(claims.act, Some(Actor { sub: admin_id.to_string() }));
+        assert_eq!(claims.roles, user_roles.roles);
+        assert!(claims.permissions.is_empty());
+        assert!(claims.exp <= Utc::now().timestamp() as usize + 60);
+
+        // Not accepted for the account routes
+        let jar = CookieJar::new().add(cookie.clone());
+        let result = validate_auth_cookie(&jar, banned_token_store.clone()).await;
+        assert!(matches!(result, Err(AuthAPIError::ImpersonationNotAllowed)));
+
+        banned_token_store.write().await.ban_user_tokens(&admin_id.to_string()).await.unwrap();
+        assert!(validate_token(cookie.value(), banned_token_store).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_exchanged_token() {
+        let user_roles = UserRoles {
+            roles: vec!["admin".to_string()],
+            permissions: vec!["roles:manage".to_string(), "users:manage".to_string()],
+        };
+        let token = generate_auth_token(&UserId::default(), &TenantId::new(), None, &user_roles).unwrap();
+        let banned_token_store  = Arc::new(RwLock::new(HashsetBannedTokenStore::default()));
+        let subject = validate_token(&token, banned_token_store.clone()).await.unwrap();
+
+        let client = TokenExchangeClient::new("orders".to_string(), "s3cret".to_string(), vec!["billing".to_string()]).unwrap();
+        let (token, _) = generate_exchanged_token(&subject, &client, "billing", vec!["users:manage".to_string()], 60).unwrap();
+
+        let claims = validate_token_for_audience(&token, banned_token_store.clone(), "billing").await.unwrap();
+        assert_eq!(claims.sub, subject.sub);
+        assert_eq!(claims.tenant, subject.tenant);
+        assert!(claims.roles.is_empty());
+        assert_eq!(claims.permissions, vec!["users:manage"]);
+        assert_eq!(claims.act, Some(Actor { sub: "orders".to_string() }));
+        assert!(claims.exp <= Utc::now().timestamp() as usize + 60);
+
+        // Only the audience accepts it
+        assert!(validate_token(&token, banned_token_store.clone()).await.is_err());
+        assert!(validate_token_for_audience(&token, banned_token_store.clone(), "shipping").await.is_err());
+
+        // Nor does it outlive the subject token
+        let (_, claims) = generate_exchanged_token(&subject, &client, "billing", vec![], 3600).unwrap();
+        assert_eq!(claims.exp, subject.exp);
+    }
+
+    #[tokio::test]
+    async fn test_token_carries_tenant() {
+        let tenant_id = TenantId::new();
+        let token = generate_auth_token(&UserId::default(), &tenant_id, None, &UserRoles::default()).unwrap();
+        let banned_token_store  = Arc::new(RwLock::new(HashsetBannedTokenStore::default()));
+
+        let result = validate_token(&token, banned_token_store).await.unwrap();
+        assert_eq!(result.tenant, tenant_id);
+    }
+}
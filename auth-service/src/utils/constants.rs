@@ -2,11 +2,36 @@ use dotenvy::dotenv;
 use lazy_static::lazy_static;
 use std::env as std_env;
 
+use crate::{domain::{PasswordPepper, TokenExchangeClient}, utils::auth::TOKEN_TTL_SECONDS};
+
 // Define a lazily evaluated static. lazy_static is needed because std_env::var is not a const function.
 lazy_static! {
     pub static ref JWT_SECRET: String = set_token();
     pub static ref DATABASE_URL: String = set_database_url();
     pub static ref REDIS_HOST_NAME: String = set_redis_host();
+    pub static ref AUTH_SERVICE_URL: String = set_auth_service_url();
+    pub static ref ENUMERATION_SAFE_SIGNUP: bool = set_enumeration_safe_signup();
+    pub static ref ACCOUNT_DELETION_GRACE_PERIOD_DAYS: i64 = set_account_deletion_grace_period();
+    pub static ref INVITATION_TTL_HOURS: i64 = set_invitation_ttl();
+    pub static ref IMPERSONATION_TTL_SECONDS: i64 = set_impersonation_ttl();
+    pub static ref TOKEN_EXCHANGE_TTL_SECONDS: i64 = set_token_exchange_ttl();
+    // Bans are kept until every token they apply to has expired
+    pub static ref BANNED_TOKEN_TTL_SECONDS: i64 = longest_token_ttl(*IMPERSONATION_TTL_SECONDS, *TOKEN_EXCHANGE_TTL_SECONDS);
+    pub static ref PERSONAL_ACCESS_TOKEN_MAX_TTL_DAYS: i64 = set_personal_access_token_max_ttl();
+    pub static ref TOKEN_EXCHANGE_CLIENTS: Vec<TokenExchangeClient> = set_token_exchange_clients();
+    pub static ref PASSWORD_MIN_LENGTH: usize = set_password_min_length();
+    pub static ref PASSWORD_MAX_LENGTH: usize = set_password_max_length();
+    pub static ref PASSWORD_MIN_STRENGTH: u8 = set_password_min_strength();
+    pub static ref PASSWORD_HISTORY_LENGTH: usize = set_password_history_length();
+    pub static ref PASSWORD_MAX_AGE_DAYS: i64 = set_password_max_age();
+    pub static ref PASSWORD_PEPPERS: Vec<PasswordPepper> = set_password_peppers();
+    pub static ref HASHING_MAX_CONCURRENCY: usize = set_hashing_max_concurrency();
+    pub static ref HASHING_MAX_QUEUED: usize = set_hashing_max_queued();
+    pub static ref ARGON2_PARAMS: argon2::Params = set_argon2_params();
+    pub static ref BREACHED_PASSWORDS_FILTER_PATH: Option<String> = set_breached_passwords_filter_path();
+    pub static ref BLOCK_DISPOSABLE_EMAIL_DOMAINS: bool = set_block_disposable_email_domains();
+    pub static ref EMAIL_DOMAIN_RULES_PATH: Option<String> = set_email_domain_rules_path();
+    pub static ref EMAIL_DOMAIN_RULES_RELOAD_SECONDS: u64 = set_email_domain_rules_reload();
 }
 
 fn set_token() -> String {
@@ -32,14 +57,263 @@ fn set_redis_host() -> String {
     std_env::var(env::REDIS_HOST_NAME_ENV_VAR).unwrap_or(DEFAULT_REDIS_HOSTNAME.to_owned())
 }
 
+fn set_auth_service_url() -> String {
+    dotenv().ok();
+    std_env::var(env::AUTH_SERVICE_URL_ENV_VAR).unwrap_or(DEFAULT_AUTH_SERVICE_URL.to_owned())
+}
+
+fn set_enumeration_safe_signup() -> bool {
+    dotenv().ok();
+    match std_env::var(env::ENUMERATION_SAFE_SIGNUP_ENV_VAR) {
+        Ok(enabled) => enabled.parse().expect("ENUMERATION_SAFE_SIGNUP must be true or false."),
+        Err(_) => false,
+    }
+}
+
+fn set_account_deletion_grace_period() -> i64 {
+    dotenv().ok();
+    match std_env::var(env::ACCOUNT_DELETION_GRACE_PERIOD_DAYS_ENV_VAR) {
+        Ok(days) => days.parse().expect("ACCOUNT_DELETION_GRACE_PERIOD_DAYS must be a number of days."),
+        Err(_) => DEFAULT_ACCOUNT_DELETION_GRACE_PERIOD_DAYS,
+    }
+}
+
+fn set_invitation_ttl() -> i64 {
+    dotenv().ok();
+    match std_env::var(env::INVITATION_TTL_HOURS_ENV_VAR) {
+        Ok(hours) => hours.parse().expect("INVITATION_TTL_HOURS must be a number of hours."),
+        Err(_) => DEFAULT_INVITATION_TTL_HOURS,
+    }
+}
+
+fn set_impersonation_ttl() -> i64 {
+    dotenv().ok();
+    match std_env::var(env::IMPERSONATION_TTL_SECONDS_ENV_VAR) {
+        Ok(seconds) => seconds.parse().ok().filter(|seconds| *seconds > 0).expect("IMPERSONATION_TTL_SECONDS must be a positive number of seconds."),
+        Err(_) => DEFAULT_IMPERSONATION_TTL_SECONDS,
+    }
+}
+
+fn set_token_exchange_ttl() -> i64 {
+    dotenv().ok();
+    match std_env::var(env::TOKEN_EXCHANGE_TTL_SECONDS_ENV_VAR) {
+        Ok(seconds) => seconds.parse().ok().filter(|seconds| *seconds > 0).expect("TOKEN_EXCHANGE_TTL_SECONDS must be a positive number of seconds."),
+        Err(_) => DEFAULT_TOKEN_EXCHANGE_TTL_SECONDS,
+    }
+}
+
+// Personal access tokens are left out, they are revoked in the database instead
+fn longest_token_ttl(impersonation_ttl: i64, token_exchange_ttl: i64) -> i64 {
+    TOKEN_TTL_SECONDS.max(impersonation_ttl).max(token_exchange_ttl)
+}
+
+fn set_personal_access_token_max_ttl() -> i64 {
+    dotenv().ok();
+    match std_env::var(env::PERSONAL_ACCESS_TOKEN_MAX_TTL_DAYS_ENV_VAR) {
+        Ok(days) => days.parse().ok().filter(|days| *days > 0).expect("PERSONAL_ACCESS_TOKEN_MAX_TTL_DAYS must be a positive number of days."),
+        Err(_) => DEFAULT_PERSONAL_ACCESS_TOKEN_MAX_TTL_DAYS,
+    }
+}
+
+// Comma separated entries of <id>:<secret>:<audiences>, the audiences being separated by spaces.
+// Unset means no service can exchange tokens.
+fn set_token_exchange_clients() -> Vec<TokenExchangeClient> {
+    dotenv().ok();
+    let clients: Vec<TokenExchangeClient> = std_env::var(env::TOKEN_EXCHANGE_CLIENTS_ENV_VAR)
+        .unwrap_or_default()
+        .split(',')
+        .filter(|client| !client.trim().is_empty())
+        .map(|client| {
+            let (id, rest) = client.trim().split_once(':').expect("TOKEN_EXCHANGE_CLIENTS entries must look like <id>:<secret>:<audiences>.");
+            let (secret, audiences) = rest.rsplit_once(':').expect("TOKEN_EXCHANGE_CLIENTS entries must look like <id>:<secret>:<audiences>.");
+            let audiences = audiences.split_whitespace().map(str::to_string).collect();
+            TokenExchangeClient::new(id.to_string(), secret.to_string(), audiences).unwrap_or_else(|e| panic!("Invalid TOKEN_EXCHANGE_CLIENTS: {}", e))
+        })
+        .collect();
+
+    let mut ids: Vec<&str> = clients.iter().map(|client| client.id()).collect();
+    ids.sort_unstable();
+    ids.dedup();
+    if ids.len() != clients.len() {
+        panic!("TOKEN_EXCHANGE_CLIENTS ids must be unique.");
+    }
+    clients
+}
+
+fn set_password_min_length() -> usize {
+    dotenv().ok();
+    match std_env::var(env::PASSWORD_MIN_LENGTH_ENV_VAR) {
+        Ok(length) => length.parse().expect("PASSWORD_MIN_LENGTH must be a number of characters."),
+        Err(_) => DEFAULT_PASSWORD_MIN_LENGTH,
+    }
+}
+
+fn set_password_max_length() -> usize {
+    dotenv().ok();
+    match std_env::var(env::PASSWORD_MAX_LENGTH_ENV_VAR) {
+        Ok(length) => length.parse().expect("PASSWORD_MAX_LENGTH must be a number of characters."),
+        Err(_) => DEFAULT_PASSWORD_MAX_LENGTH,
+    }
+}
+
+fn set_password_min_strength() -> u8 {
+    dotenv().ok();
+    match std_env::var(env::PASSWORD_MIN_STRENGTH_ENV_VAR) {
+        Ok(score) => score.parse().ok().filter(|score| *score <= 4).expect("PASSWORD_MIN_STRENGTH must be a score from 0 to 4."),
+        Err(_) => DEFAULT_PASSWORD_MIN_STRENGTH,
+    }
+}
+
+fn set_password_history_length() -> usize {
+    dotenv().ok();
+    match std_env::var(env::PASSWORD_HISTORY_LENGTH_ENV_VAR) {
+        Ok(length) => length.parse().expect("PASSWORD_HISTORY_LENGTH must be a number of passwords."),
+        Err(_) => DEFAULT_PASSWORD_HISTORY_LENGTH,
+    }
+}
+
+fn set_password_max_age() -> i64 {
+    dotenv().ok();
+    match std_env::var(env::PASSWORD_MAX_AGE_DAYS_ENV_VAR) {
+        Ok(days) => days.parse().expect("PASSWORD_MAX_AGE_DAYS must be a number of days."),
+        Err(_) => DEFAULT_PASSWORD_MAX_AGE_DAYS,
+    }
+}
+
+// Comma separated "<id>:<secret>" pairs, new hashes use the first one and the others remain for older hashes.
+// Unset means no pepper.
+fn set_password_peppers() -> Vec<PasswordPepper> {
+    dotenv().ok();
+    let peppers: Vec<PasswordPepper> = std_env::var(env::PASSWORD_PEPPERS_ENV_VAR)
+        .unwrap_or_default()
+        .split(',')
+        .filter(|pepper| !pepper.trim().is_empty())
+        .map(|pepper| {
+            let (id, secret) = pepper.trim().split_once(':').expect("PASSWORD_PEPPERS entries must look like <id>:<secret>.");
+            PasswordPepper::new(id.to_string(), secret.to_string()).unwrap_or_else(|e| panic!("Invalid PASSWORD_PEPPERS: {}", e))
+        })
+        .collect();
+
+    let mut ids: Vec<&str> = peppers.iter().map(|pepper| pepper.id()).collect();
+    ids.sort_unstable();
+    ids.dedup();
+    if ids.len() != peppers.len() {
+        panic!("PASSWORD_PEPPERS ids must be unique.");
+    }
+    peppers
+}
+
+// Raising these makes every hash stronger, existing hashes are upgraded when their users log in
+fn set_argon2_params() -> argon2::Params {
+    dotenv().ok();
+    let memory_kib = std_env::var(env::ARGON2_MEMORY_KIB_ENV_VAR)
+        .map(|memory| memory.parse().expect("ARGON2_MEMORY_KIB must be a number of kibibytes."))
+        .unwrap_or(DEFAULT_ARGON2_MEMORY_KIB);
+    let iterations = std_env::var(env::ARGON2_ITERATIONS_ENV_VAR)
+        .map(|iterations| iterations.parse().expect("ARGON2_ITERATIONS must be a number."))
+        .unwrap_or(DEFAULT_ARGON2_ITERATIONS);
+    let parallelism = std_env::var(env::ARGON2_PARALLELISM_ENV_VAR)
+        .map(|parallelism| parallelism.parse().expect("ARGON2_PARALLELISM must be a number of lanes."))
+        .unwrap_or(DEFAULT_ARGON2_PARALLELISM);
+
+    argon2::Params::new(memory_kib, iterations, parallelism, None).expect("Invalid Argon2 parameters.")
+}
+
+// Every running hash holds ARGON2_MEMORY_KIB, so this bounds the memory used for hashing
+fn set_hashing_max_concurrency() -> usize {
+    dotenv().ok();
+    match std_env::var(env::HASHING_MAX_CONCURRENCY_ENV_VAR) {
+        Ok(count) => count.parse().ok().filter(|count| *count > 0).expect("HASHING_MAX_CONCURRENCY must be a positive number."),
+        Err(_) => std::thread::available_parallelism().map(usize::from).unwrap_or(1),
+    }
+}
+
+fn set_hashing_max_queued() -> usize {
+    dotenv().ok();
+    match std_env::var(env::HASHING_MAX_QUEUED_ENV_VAR) {
+        Ok(count) => count.parse().expect("HASHING_MAX_QUEUED must be a number of requests."),
+        Err(_) => DEFAULT_HASHING_MAX_QUEUED,
+    }
+}
+
+fn set_breached_passwords_filter_path() -> Option<String> {
+    dotenv().ok();
+    std_env::var(env::BREACHED_PASSWORDS_FILTER_PATH_ENV_VAR).ok().filter(|path| !path.is_empty())
+}
+
+fn set_block_disposable_email_domains() -> bool {
+    dotenv().ok();
+    match std_env::var(env::BLOCK_DISPOSABLE_EMAIL_DOMAINS_ENV_VAR) {
+        Ok(enabled) => enabled.parse().expect("BLOCK_DISPOSABLE_EMAIL_DOMAINS must be true or false."),
+        Err(_) => true,
+    }
+}
+
+fn set_email_domain_rules_path() -> Option<String> {
+    dotenv().ok();
+    std_env::var(env::EMAIL_DOMAIN_RULES_PATH_ENV_VAR).ok().filter(|path| !path.is_empty())
+}
+
+fn set_email_domain_rules_reload() -> u64 {
+    dotenv().ok();
+    match std_env::var(env::EMAIL_DOMAIN_RULES_RELOAD_SECONDS_ENV_VAR) {
+        Ok(seconds) => seconds.parse().ok().filter(|seconds| *seconds > 0).expect("EMAIL_DOMAIN_RULES_RELOAD_SECONDS must be a positive number of seconds."),
+        Err(_) => DEFAULT_EMAIL_DOMAIN_RULES_RELOAD_SECONDS,
+    }
+}
+
 pub mod env {
     pub const JWT_SECRET_ENV_VAR: &str = "JWT_SECRET";
     pub const DATABASE_URL_ENV_VAR: &str = "DATABASE_URL";
     pub const REDIS_HOST_NAME_ENV_VAR: &str = "REDIS_HOST_NAME";
+    pub const AUTH_SERVICE_URL_ENV_VAR: &str = "AUTH_SERVICE_URL";
+    pub const ENUMERATION_SAFE_SIGNUP_ENV_VAR: &str = "ENUMERATION_SAFE_SIGNUP";
+    pub const ACCOUNT_DELETION_GRACE_PERIOD_DAYS_ENV_VAR: &str = "ACCOUNT_DELETION_GRACE_PERIOD_DAYS";
+    pub const INVITATION_TTL_HOURS_ENV_VAR: &str = "INVITATION_TTL_HOURS";
+    pub const IMPERSONATION_TTL_SECONDS_ENV_VAR: &str = "IMPERSONATION_TTL_SECONDS";
+    pub const TOKEN_EXCHANGE_TTL_SECONDS_ENV_VAR: &str = "TOKEN_EXCHANGE_TTL_SECONDS";
+    pub const TOKEN_EXCHANGE_CLIENTS_ENV_VAR: &str = "TOKEN_EXCHANGE_CLIENTS";
+    pub const PERSONAL_ACCESS_TOKEN_MAX_TTL_DAYS_ENV_VAR: &str = "PERSONAL_ACCESS_TOKEN_MAX_TTL_DAYS";
+    pub const PASSWORD_MIN_LENGTH_ENV_VAR: &str = "PASSWORD_MIN_LENGTH";
+    pub const PASSWORD_MAX_LENGTH_ENV_VAR: &str = "PASSWORD_MAX_LENGTH";
+    pub const PASSWORD_MIN_STRENGTH_ENV_VAR: &str = "PASSWORD_MIN_STRENGTH";
+    pub const PASSWORD_HISTORY_LENGTH_ENV_VAR: &str = "PASSWORD_HISTORY_LENGTH";
+    pub const PASSWORD_MAX_AGE_DAYS_ENV_VAR: &str = "PASSWORD_MAX_AGE_DAYS";
+    pub const PASSWORD_PEPPERS_ENV_VAR: &str = "PASSWORD_PEPPERS";
+    pub const HASHING_MAX_CONCURRENCY_ENV_VAR: &str = "HASHING_MAX_CONCURRENCY";
+    pub const HASHING_MAX_QUEUED_ENV_VAR: &str = "HASHING_MAX_QUEUED";
+    pub const ARGON2_MEMORY_KIB_ENV_VAR: &str = "ARGON2_MEMORY_KIB";
+    pub const ARGON2_ITERATIONS_ENV_VAR: &str = "ARGON2_ITERATIONS";
+    pub const ARGON2_PARALLELISM_ENV_VAR: &str = "ARGON2_PARALLELISM";
+    pub const BREACHED_PASSWORDS_FILTER_PATH_ENV_VAR: &str = "BREACHED_PASSWORDS_FILTER_PATH";
+    pub const BLOCK_DISPOSABLE_EMAIL_DOMAINS_ENV_VAR: &str = "BLOCK_DISPOSABLE_EMAIL_DOMAINS";
+    pub const EMAIL_DOMAIN_RULES_PATH_ENV_VAR: &str = "EMAIL_DOMAIN_RULES_PATH";
+    pub const EMAIL_DOMAIN_RULES_RELOAD_SECONDS_ENV_VAR: &str = "EMAIL_DOMAIN_RULES_RELOAD_SECONDS";
 }
 
 pub const JWT_COOKIE_NAME: &str = "jwt";
 pub const DEFAULT_REDIS_HOSTNAME: &str = "127.0.0.1";
+pub const DEFAULT_AUTH_SERVICE_URL: &str = "http://localhost:3000";
+pub const DEFAULT_ACCOUNT_DELETION_GRACE_PERIOD_DAYS: i64 = 30;
+pub const DEFAULT_INVITATION_TTL_HOURS: i64 = 72;
+// Shorter than a regular session
+pub const DEFAULT_IMPERSONATION_TTL_SECONDS: i64 = 300;
+// Exchanged tokens never outlive the token they were exchanged for
+pub const DEFAULT_TOKEN_EXCHANGE_TTL_SECONDS: i64 = 300;
+pub const DEFAULT_PERSONAL_ACCESS_TOKEN_MAX_TTL_DAYS: i64 = 365;
+// Used when the user does not pick an expiry, capped by the maximum
+pub const DEFAULT_PERSONAL_ACCESS_TOKEN_TTL_DAYS: i64 = 30;
+pub const DEFAULT_PASSWORD_MIN_LENGTH: usize = 8;
+pub const DEFAULT_PASSWORD_MAX_LENGTH: usize = 128;
+pub const DEFAULT_PASSWORD_MIN_STRENGTH: u8 = 2;
+pub const DEFAULT_PASSWORD_HISTORY_LENGTH: usize = 5;
+// Passwords do not expire unless configured
+pub const DEFAULT_PASSWORD_MAX_AGE_DAYS: i64 = 0;
+pub const DEFAULT_HASHING_MAX_QUEUED: usize = 64;
+pub const DEFAULT_ARGON2_MEMORY_KIB: u32 = 15000;
+pub const DEFAULT_ARGON2_ITERATIONS: u32 = 2;
+pub const DEFAULT_ARGON2_PARALLELISM: u32 = 1;
+pub const DEFAULT_EMAIL_DOMAIN_RULES_RELOAD_SECONDS: u64 = 30;
 
 pub mod prod {
     pub const APP_ADDRESS: &str = "0.0.0.0:3000";
@@ -47,4 +321,18 @@ pub mod prod {
 
 pub mod test {
     pub const APP_ADDRESS: &str = "127.0.0.1:0";
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bans_outlive_every_token() {
+        assert_eq!(longest_token_ttl(60, 60), TOKEN_TTL_SECONDS);
+        assert_eq!(longest_token_ttl(TOKEN_TTL_SECONDS * 6, 60), TOKEN_TTL_SECONDS * 6);
+        assert_eq!(longest_token_ttl(60, TOKEN_TTL_SECONDS * 2), TOKEN_TTL_SECONDS * 2);
+        assert!(*BANNED_TOKEN_TTL_SECONDS >= *IMPERSONATION_TTL_SECONDS);
+        assert!(*BANNED_TOKEN_TTL_SECONDS >= *TOKEN_EXCHANGE_TTL_SECONDS);
+    }
+}
@@ -0,0 +1,46 @@
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use super::{auth::Claims, constants::JWT_SECRET};
+
+type HmacSha256 = Hmac<Sha256>;
+
+// Forms of the admin console carry a token bound to the session they were rendered for.
+// It is derived from the JWT, so nothing has to be stored and a new login invalidates the old forms.
+pub fn generate_csrf_token(claims: &Claims) -> String {
+    hex::encode(session_mac(claims).finalize().into_bytes())
+}
+
+pub fn verify_csrf_token(claims: &Claims, csrf_token: &str) -> bool {
+    match hex::decode(csrf_token) {
+        Ok(csrf_token) => session_mac(claims).verify_slice(&csrf_token).is_ok(),
+        Err(_) => false,
+    }
+}
+
+fn session_mac(claims: &Claims) -> HmacSha256 {
+    let mut mac = HmacSha256::new_from_slice(JWT_SECRET.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(format!("csrf:{}:{}", claims.sub, claims.issued_at().timestamp_micros()).as_bytes());
+    mac
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn claims(sub: &str, iat: usize) -> Claims {
+        Claims { sub: sub.to_string(), exp: iat + 600, iat, iat_us: None, tenant: Default::default(), scope: None, roles: vec![], permissions: vec![], act: None, aud: None, pat: None }
+    }
+
+    #[test]
+    fn test_csrf_token_is_bound_to_the_session() {
+        let session = claims("user", 1000);
+        let csrf_token = generate_csrf_token(&session);
+
+        assert!(verify_csrf_token(&session, &csrf_token));
+        assert!(!verify_csrf_token(&claims("user", 1001), &csrf_token));
+        assert!(!verify_csrf_token(&claims("other user", 1000), &csrf_token));
+        assert!(!verify_csrf_token(&session, ""));
+        assert!(!verify_csrf_token(&session, "not hex"));
+    }
+}
@@ -1,4 +1,5 @@
 pub mod constants;
 pub mod auth;
+pub mod csrf;
 
 pub use constants::*;
\ No newline at end of file
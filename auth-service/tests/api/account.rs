@@ -0,0 +1,199 @@
+use auth_service::routes::{AccountExportResponse, CancelAccountDeletionResponse, DeleteAccountResponse};
+
+use crate::helpers::{get_random_email, signup_admin_and_login, signup_and_login, TestApp};
+
+#[tokio::test]
+async fn should_return_400_if_jwt_cookie_missing() {
+    let mut app = TestApp::new().await;
+
+    let response = app.get_account_export().await;
+    assert_eq!(response.status().as_u16(), 400);
+
+    let response = app.post_account_delete(&serde_json::json!({ "password": "S3cure-Passw0rd!" })).await;
+    assert_eq!(response.status().as_u16(), 400);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_export_account_data() {
+    let mut app = TestApp::new().await;
+
+    let random_email = get_random_email();
+    signup_and_login(&app, &random_email).await;
+
+    let response = app.get_account_export().await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let export = response
+        .json::<AccountExportResponse>()
+        .await
+        .expect("Could not deserialize response body to AccountExportResponse");
+
+    assert_eq!(export.profile.email, random_email);
+    assert_eq!(export.profile.pending_email_change, None);
+    assert!(!export.two_factor_auth.enabled);
+    assert!(export.current_session.issued_at.is_some());
+    assert_eq!(export.organization.slug, "default");
+    assert!(export.roles.is_empty());
+    assert!(export.permissions.is_empty());
+    assert!(!export.account_state.locked);
+    assert!(!export.account_state.password_reset_required);
+    assert!(!export.account_state.approval_pending);
+    assert_eq!(export.account_state.password_history_count, 0);
+    assert_eq!(export.account_state.deletion_scheduled_at, None);
+    assert!(export.personal_access_tokens.is_empty());
+    assert!(export.pending_invitations.is_empty());
+
+    let events: Vec<&str> = export.audit_log.iter().map(|entry| entry.event.as_str()).collect();
+    assert_eq!(events, vec!["signup", "login", "data_exported"]);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_export_roles_tokens_and_invitations() {
+    let mut app = TestApp::new().await;
+
+    let random_email = get_random_email();
+
+    // An admin of another organization invites the address
+    app.add_tenant("acme", None).await;
+    let admin_email = get_random_email();
+    app.post_signup(&serde_json::json!({
+        "tenant": "acme",
+        "email": admin_email,
+        "password": "S3cure-Passw0rd!",
+        "requires2FA": false
+    })).await;
+    app.assign_role(&admin_email, "admin").await;
+    app.post_login(&serde_json::json!({
+        "tenant": "acme",
+        "email": admin_email,
+        "password": "S3cure-Passw0rd!",
+    })).await;
+    let response = app.post_admin_invitation(&serde_json::json!({ "email": random_email, "roles": ["admin"] })).await;
+    assert_eq!(response.status().as_u16(), 201);
+
+    signup_admin_and_login(&app, &random_email).await;
+    let response = app.post_change_password(&serde_json::json!({
+        "currentPassword": "S3cure-Passw0rd!",
+        "newPassword": "N3w-Passw0rd-456",
+    })).await;
+    assert_eq!(response.status().as_u16(), 200);
+    let response = app.post_account_tokens(&serde_json::json!({ "name": "ci", "scopes": ["users:manage"] })).await;
+    assert_eq!(response.status().as_u16(), 201);
+
+    let response = app.get_account_export().await;
+    assert_eq!(response.status().as_u16(), 200);
+    let export = response.json::<AccountExportResponse>().await.unwrap();
+
+    assert_eq!(export.roles, vec!["admin"]);
+    assert!(export.permissions.contains(&"users:manage".to_string()));
+    assert_eq!(export.account_state.password_history_count, 1);
+    assert_eq!(export.personal_access_tokens.len(), 1);
+    assert_eq!(export.personal_access_tokens[0].name, "ci");
+    assert_eq!(export.pending_invitations.len(), 1);
+    assert_eq!(export.pending_invitations[0].organization, "acme");
+    assert_eq!(export.pending_invitations[0].roles, vec!["admin"]);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_422_if_malformed_delete_input() {
+    let mut app = TestApp::new().await;
+
+    signup_and_login(&app, &get_random_email()).await;
+
+    let response = app.post_account_delete(&serde_json::json!({ "": "" })).await;
+    assert_eq!(response.status().as_u16(), 422);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_401_if_incorrect_password() {
+    let mut app = TestApp::new().await;
+
+    signup_and_login(&app, &get_random_email()).await;
+
+    let response = app.post_account_delete(&serde_json::json!({ "password": "wrong_password" })).await;
+    assert_eq!(response.status().as_u16(), 401);
+
+    let response = app.get_account_export().await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_delete_account_and_revoke_tokens() {
+    let mut app = TestApp::new().await;
+
+    let random_email = get_random_email();
+    let token = signup_and_login(&app, &random_email).await;
+
+    let response = app.post_account_delete(&serde_json::json!({ "password": "S3cure-Passw0rd!" })).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    response
+        .json::<DeleteAccountResponse>()
+        .await
+        .expect("Could not deserialize response body to DeleteAccountResponse");
+
+    let response = app.post_verify_token(&serde_json::json!({ "token": token })).await;
+    assert_eq!(response.status().as_u16(), 401);
+
+    let response = app.post_login(&serde_json::json!({
+        "email": random_email,
+        "password": "S3cure-Passw0rd!",
+    })).await;
+    assert_eq!(response.status().as_u16(), 401);
+
+    // The email stays reserved until the account is deleted for good
+    let response = app.post_signup(&serde_json::json!({
+        "email": random_email,
+        "password": "S3cure-Passw0rd!",
+        "requires2FA": false
+    })).await;
+    assert_eq!(response.status().as_u16(), 409);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_cancel_account_deletion_during_grace_period() {
+    let mut app = TestApp::new().await;
+
+    let random_email = get_random_email();
+    signup_and_login(&app, &random_email).await;
+
+    let credentials = |password: &str| serde_json::json!({ "email": random_email, "password": password });
+
+    // Only scheduled deletions can be cancelled
+    let response = app.post_account_cancel_deletion(&credentials("S3cure-Passw0rd!")).await;
+    assert_eq!(response.status().as_u16(), 401);
+
+    let response = app.post_account_delete(&serde_json::json!({ "password": "S3cure-Passw0rd!" })).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app.post_account_cancel_deletion(&credentials("Wr0ng-Passw0rd!")).await;
+    assert_eq!(response.status().as_u16(), 401);
+
+    let response = app.post_account_cancel_deletion(&credentials("S3cure-Passw0rd!")).await;
+    assert_eq!(response.status().as_u16(), 200);
+    response
+        .json::<CancelAccountDeletionResponse>()
+        .await
+        .expect("Could not deserialize response body to CancelAccountDeletionResponse");
+
+    let response = app.post_login(&credentials("S3cure-Passw0rd!")).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app.get_account_export().await;
+    let export = response.json::<AccountExportResponse>().await.unwrap();
+    assert!(export.audit_log.iter().any(|entry| entry.event == "account_deletion_cancelled"));
+
+    app.clean_up().await;
+}
@@ -0,0 +1,149 @@
+use auth_service::routes::AdminUserResponse;
+
+use crate::helpers::{get_random_email, signup_admin_and_login, signup_and_login, TestApp};
+
+// The token of the first form on a rendered page
+fn csrf_token(html: &str) -> String {
+    let marker = r#"name="csrf_token" value=""#;
+    let start = html.find(marker).expect("No csrf token on the page") + marker.len();
+    html[start..].split('"').next().unwrap().to_owned()
+}
+
+#[tokio::test]
+async fn should_redirect_to_login_without_session() {
+    let mut app = TestApp::new().await;
+
+    let response = app.get_admin_console(&[]).await;
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(response.url().path(), "/");
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_403_without_manage_users_permission() {
+    let mut app = TestApp::new().await;
+
+    let email = get_random_email();
+    signup_and_login(&app, &email).await;
+    let user_id = app.get_user_id(&email).await;
+
+    let response = app.get_admin_console(&[]).await;
+    assert_eq!(response.status().as_u16(), 403);
+
+    let response = app.get_admin_console_user(&user_id).await;
+    assert_eq!(response.status().as_u16(), 403);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_search_users() {
+    let mut app = TestApp::new().await;
+
+    signup_and_login(&app, "alice@console.test").await;
+    signup_and_login(&app, "bob@console.test").await;
+    let admin_email = get_random_email();
+    signup_admin_and_login(&app, &admin_email).await;
+
+    let response = app.get_admin_console(&[("search", "alice")]).await;
+    assert_eq!(response.status().as_u16(), 200);
+    let html = response.text().await.unwrap();
+    assert!(html.contains("alice@console.test"));
+    assert!(!html.contains("bob@console.test"));
+
+    let html = app.get_admin_console(&[]).await.text().await.unwrap();
+    assert!(html.contains("bob@console.test"));
+    assert!(html.contains(&admin_email));
+
+    let response = app.get_admin_console(&[("page", &u64::MAX.to_string())]).await;
+    assert_eq!(response.status().as_u16(), 400);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_show_user_sessions_and_audit_history() {
+    let mut app = TestApp::new().await;
+
+    let email = get_random_email();
+    signup_and_login(&app, &email).await;
+    let user_id = app.get_user_id(&email).await;
+    signup_admin_and_login(&app, &get_random_email()).await;
+
+    let response = app.get_admin_console_user(&user_id).await;
+    assert_eq!(response.status().as_u16(), 200);
+    let html = response.text().await.unwrap();
+    assert!(html.contains(&email));
+    assert!(html.contains("Logged in at"));
+    assert!(html.contains("signup"));
+    assert!(!csrf_token(&html).is_empty());
+
+    let response = app.get_admin_console_user(&uuid::Uuid::new_v4().to_string()).await;
+    assert_eq!(response.status().as_u16(), 404);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_reject_actions_without_valid_csrf_token() {
+    let mut app = TestApp::new().await;
+
+    let email = get_random_email();
+    signup_and_login(&app, &email).await;
+    let user_id = app.get_user_id(&email).await;
+    signup_admin_and_login(&app, &get_random_email()).await;
+
+    for csrf_token in ["", "not a token", &"0".repeat(64)] {
+        let response = app.post_admin_console_action(&user_id, "lock", csrf_token).await;
+        assert_eq!(response.status().as_u16(), 403);
+    }
+
+    // A token rendered for an earlier session is not accepted either
+    let html = app.get_admin_console_user(&user_id).await.text().await.unwrap();
+    signup_admin_and_login(&app, &get_random_email()).await;
+
+    let response = app.post_admin_console_action(&user_id, "lock", &csrf_token(&html)).await;
+    assert_eq!(response.status().as_u16(), 403);
+
+    let user = app.get_admin_user(&user_id).await.json::<AdminUserResponse>().await.unwrap();
+    assert!(!user.locked);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_lock_unlock_and_force_password_reset() {
+    let mut app = TestApp::new().await;
+
+    let email = get_random_email();
+    signup_and_login(&app, &email).await;
+    let user_id = app.get_user_id(&email).await;
+    signup_admin_and_login(&app, &get_random_email()).await;
+
+    let html = app.get_admin_console_user(&user_id).await.text().await.unwrap();
+    let token = csrf_token(&html);
+
+    // Actions redirect back to the user's page
+    let response = app.post_admin_console_action(&user_id, "lock", &token).await;
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(response.url().path(), format!("/admin/console/users/{}", user_id));
+    let html = response.text().await.unwrap();
+    assert!(html.contains("Unlock"));
+    assert!(html.contains("No active sessions"));
+
+    let user = app.get_admin_user(&user_id).await.json::<AdminUserResponse>().await.unwrap();
+    assert!(user.locked);
+
+    let response = app.post_admin_console_action(&user_id, "unlock", &token).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app.post_admin_console_action(&user_id, "force-password-reset", &token).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let user = app.get_admin_user(&user_id).await.json::<AdminUserResponse>().await.unwrap();
+    assert!(!user.locked);
+    assert!(user.password_reset_required);
+
+    app.clean_up().await;
+}
@@ -0,0 +1,103 @@
+use auth_service::{domain::UserRoles, routes::VerifyTokenResponse};
+
+use crate::helpers::{get_random_email, login, signup_and_login, TestApp};
+
+#[tokio::test]
+async fn should_return_400_if_jwt_cookie_missing() {
+    let mut app = TestApp::new().await;
+
+    let user_id = uuid::Uuid::new_v4().to_string();
+    let response = app.get_admin_user_roles(&user_id).await;
+    assert_eq!(response.status().as_u16(), 400);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_403_without_manage_roles_permission() {
+    let mut app = TestApp::new().await;
+
+    let email = get_random_email();
+    signup_and_login(&app, &email).await;
+    let user_id = app.get_user_id(&email).await;
+
+    let response = app.get_admin_user_roles(&user_id).await;
+    assert_eq!(response.status().as_u16(), 403);
+
+    let response = app.post_admin_user_roles(&user_id, &serde_json::json!({ "role": "admin" })).await;
+    assert_eq!(response.status().as_u16(), 403);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_assign_and_revoke_roles() {
+    let mut app = TestApp::new().await;
+
+    let admin_email = get_random_email();
+    let user_email = get_random_email();
+    signup_and_login(&app, &user_email).await;
+    signup_and_login(&app, &admin_email).await;
+    let user_id = app.get_user_id(&user_email).await;
+
+    // Tokens issued before the role was assigned do not carry it
+    app.assign_role(&admin_email, "admin").await;
+    let response = app.get_admin_user_roles(&user_id).await;
+    assert_eq!(response.status().as_u16(), 403);
+
+    login(&app, &admin_email).await;
+    let response = app.get_admin_user_roles(&user_id).await;
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(response.json::<UserRoles>().await.unwrap(), UserRoles::default());
+
+    let response = app.post_admin_user_roles(&user_id, &serde_json::json!({ "role": "admin" })).await;
+    assert_eq!(response.status().as_u16(), 200);
+    let user_roles = response.json::<UserRoles>().await.unwrap();
+    assert_eq!(user_roles.roles, vec!["admin"]);
+    assert_eq!(user_roles.permissions, vec!["metrics:read", "roles:manage", "tenant:manage", "users:impersonate", "users:manage"]);
+
+    // The roles and their permissions are carried by the user's next token
+    let token = login(&app, &user_email).await;
+    let response = app.post_verify_token(&serde_json::json!({ "token": token })).await;
+    assert_eq!(response.status().as_u16(), 200);
+    let claims = response.json::<VerifyTokenResponse>().await.unwrap();
+    assert_eq!(claims.roles, vec!["admin"]);
+    assert_eq!(claims.permissions, vec!["metrics:read", "roles:manage", "tenant:manage", "users:impersonate", "users:manage"]);
+
+    // Revoking the role revokes the tokens that carry it
+    login(&app, &admin_email).await;
+    let response = app.delete_admin_user_role(&user_id, "admin").await;
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(response.json::<UserRoles>().await.unwrap(), UserRoles::default());
+
+    let response = app.post_verify_token(&serde_json::json!({ "token": token })).await;
+    assert_eq!(response.status().as_u16(), 401);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_404_for_unknown_user_or_role() {
+    let mut app = TestApp::new().await;
+
+    let admin_email = get_random_email();
+    signup_and_login(&app, &admin_email).await;
+    app.assign_role(&admin_email, "admin").await;
+    login(&app, &admin_email).await;
+    let admin_id = app.get_user_id(&admin_email).await;
+
+    let unknown_user_id = uuid::Uuid::new_v4().to_string();
+    let response = app.post_admin_user_roles(&unknown_user_id, &serde_json::json!({ "role": "admin" })).await;
+    assert_eq!(response.status().as_u16(), 404);
+
+    let response = app.get_admin_user_roles("not-a-user-id").await;
+    assert_eq!(response.status().as_u16(), 404);
+
+    let response = app.post_admin_user_roles(&admin_id, &serde_json::json!({ "role": "unknown" })).await;
+    assert_eq!(response.status().as_u16(), 404);
+
+    let response = app.delete_admin_user_role(&admin_id, "unknown").await;
+    assert_eq!(response.status().as_u16(), 404);
+
+    app.clean_up().await;
+}
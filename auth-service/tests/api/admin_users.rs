@@ -0,0 +1,208 @@
+use auth_service::{routes::{AdminUserResponse, ListUsersResponse}, ErrorResponse};
+
+use crate::helpers::{get_random_email, login, signup_admin_and_login, signup_and_login, TestApp};
+
+#[tokio::test]
+async fn should_return_403_without_manage_users_permission() {
+    let mut app = TestApp::new().await;
+
+    let email = get_random_email();
+    signup_and_login(&app, &email).await;
+    let user_id = app.get_user_id(&email).await;
+
+    let response = app.get_admin_users(&[]).await;
+    assert_eq!(response.status().as_u16(), 403);
+
+    let response = app.post_admin_user_action(&user_id, "lock").await;
+    assert_eq!(response.status().as_u16(), 403);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_list_and_search_users_with_pagination() {
+    let mut app = TestApp::new().await;
+
+    for name in ["carol", "alice", "bob"] {
+        signup_and_login(&app, &format!("{}@customer.test", name)).await;
+    }
+    signup_admin_and_login(&app, &get_random_email()).await;
+
+    let response = app.get_admin_users(&[("search", "CUSTOMER"), ("perPage", "2")]).await;
+    assert_eq!(response.status().as_u16(), 200);
+    let page = response.json::<ListUsersResponse>().await.unwrap();
+    assert_eq!((page.page, page.per_page, page.total), (1, 2, 3));
+    let emails: Vec<&str> = page.users.iter().map(|user| user.email.as_str()).collect();
+    assert_eq!(emails, vec!["alice@customer.test", "bob@customer.test"]);
+
+    let response = app.get_admin_users(&[("search", "customer"), ("perPage", "2"), ("page", "2")]).await;
+    let page = response.json::<ListUsersResponse>().await.unwrap();
+    let emails: Vec<&str> = page.users.iter().map(|user| user.email.as_str()).collect();
+    assert_eq!(emails, vec!["carol@customer.test"]);
+
+    // Wildcards are matched literally
+    let response = app.get_admin_users(&[("search", "%")]).await;
+    assert_eq!(response.json::<ListUsersResponse>().await.unwrap().total, 0);
+
+    let response = app.get_admin_users(&[]).await;
+    assert_eq!(response.json::<ListUsersResponse>().await.unwrap().total, 4);
+
+    // Pages whose offset does not fit the stores are refused instead of wrapping around
+    let response = app.get_admin_users(&[("perPage", "100"), ("page", &u64::MAX.to_string())]).await;
+    assert_eq!(response.status().as_u16(), 400);
+    let response = app.get_admin_users(&[("perPage", "2"), ("page", &(i64::MAX / 2 + 2).to_string())]).await;
+    assert_eq!(response.status().as_u16(), 400);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_get_user() {
+    let mut app = TestApp::new().await;
+
+    let email = get_random_email();
+    signup_and_login(&app, &email).await;
+    let user_id = app.get_user_id(&email).await;
+    signup_admin_and_login(&app, &get_random_email()).await;
+
+    let response = app.get_admin_user(&user_id).await;
+    assert_eq!(response.status().as_u16(), 200);
+    let user = response.json::<AdminUserResponse>().await.unwrap();
+    assert_eq!(user.id, user_id);
+    assert_eq!(user.email, email);
+    assert!(!user.requires_2fa && !user.locked && !user.password_reset_required);
+    assert_eq!(user.deletion_scheduled_at, None);
+
+    let response = app.get_admin_user(&uuid::Uuid::new_v4().to_string()).await;
+    assert_eq!(response.status().as_u16(), 404);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_lock_and_unlock_user() {
+    let mut app = TestApp::new().await;
+
+    let email = get_random_email();
+    let admin_email = get_random_email();
+    let token = signup_and_login(&app, &email).await;
+    let user_id = app.get_user_id(&email).await;
+
+    signup_admin_and_login(&app, &admin_email).await;
+
+    let response = app.post_admin_user_action(&user_id, "lock").await;
+    assert_eq!(response.status().as_u16(), 200);
+    assert!(response.json::<AdminUserResponse>().await.unwrap().locked);
+
+    // Existing sessions end and only the right password reveals the lock
+    let response = app.post_verify_token(&serde_json::json!({ "token": token })).await;
+    assert_eq!(response.status().as_u16(), 401);
+
+    let response = app.post_login(&serde_json::json!({ "email": email, "password": "wrong-Passw0rd!" })).await;
+    assert_eq!(response.status().as_u16(), 401);
+
+    let response = app.post_login(&serde_json::json!({ "email": email, "password": "S3cure-Passw0rd!" })).await;
+    assert_eq!(response.status().as_u16(), 403);
+    assert_eq!(response.json::<ErrorResponse>().await.unwrap().error, "Account is locked");
+
+    let response = app.post_admin_user_action(&user_id, "unlock").await;
+    assert_eq!(response.status().as_u16(), 200);
+    assert!(!response.json::<AdminUserResponse>().await.unwrap().locked);
+
+    let response = app.post_login(&serde_json::json!({ "email": email, "password": "S3cure-Passw0rd!" })).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_force_password_reset() {
+    let mut app = TestApp::new().await;
+
+    let email = get_random_email();
+    signup_and_login(&app, &email).await;
+    let user_id = app.get_user_id(&email).await;
+    signup_admin_and_login(&app, &get_random_email()).await;
+
+    let response = app.post_admin_user_action(&user_id, "force-password-reset").await;
+    assert_eq!(response.status().as_u16(), 200);
+    assert!(response.json::<AdminUserResponse>().await.unwrap().password_reset_required);
+
+    // The next login only allows changing the password, which clears the requirement
+    let response = app.post_login(&serde_json::json!({ "email": email, "password": "S3cure-Passw0rd!" })).await;
+    assert_eq!(response.status().as_u16(), 403);
+
+    let response = app.post_change_password(&serde_json::json!({
+        "currentPassword": "S3cure-Passw0rd!",
+        "newPassword": "An0ther-S3cure-Passw0rd!",
+    })).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app.post_login(&serde_json::json!({ "email": email, "password": "An0ther-S3cure-Passw0rd!" })).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_force_password_reset_when_the_notification_fails() {
+    let mut app = TestApp::new().await;
+
+    let email = get_random_email();
+    signup_and_login(&app, &email).await;
+    let user_id = app.get_user_id(&email).await;
+    signup_admin_and_login(&app, &get_random_email()).await;
+    app.email_client.fail_sending();
+
+    let response = app.post_admin_user_action(&user_id, "force-password-reset").await;
+    assert_eq!(response.status().as_u16(), 200);
+    assert!(response.json::<AdminUserResponse>().await.unwrap().password_reset_required);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_toggle_2fa() {
+    let mut app = TestApp::new().await;
+
+    let email = get_random_email();
+    signup_and_login(&app, &email).await;
+    let user_id = app.get_user_id(&email).await;
+    let admin_email = get_random_email();
+    signup_admin_and_login(&app, &admin_email).await;
+
+    let response = app.post_admin_user_2fa(&user_id, &serde_json::json!({ "enabled": true })).await;
+    assert_eq!(response.status().as_u16(), 200);
+    assert!(response.json::<AdminUserResponse>().await.unwrap().requires_2fa);
+
+    let response = app.post_login(&serde_json::json!({ "email": email, "password": "S3cure-Passw0rd!" })).await;
+    assert_eq!(response.status().as_u16(), 206);
+
+    login(&app, &admin_email).await;
+    let response = app.post_admin_user_2fa(&user_id, &serde_json::json!({ "enabled": false })).await;
+    assert!(!response.json::<AdminUserResponse>().await.unwrap().requires_2fa);
+
+    let response = app.post_admin_user_2fa(&user_id, &serde_json::json!({ "enabled": "yes" })).await;
+    assert_eq!(response.status().as_u16(), 422);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_revoke_sessions() {
+    let mut app = TestApp::new().await;
+
+    let email = get_random_email();
+    let token = signup_and_login(&app, &email).await;
+    let user_id = app.get_user_id(&email).await;
+
+    signup_admin_and_login(&app, &get_random_email()).await;
+
+    let response = app.post_admin_user_action(&user_id, "revoke-sessions").await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app.post_verify_token(&serde_json::json!({ "token": token })).await;
+    assert_eq!(response.status().as_u16(), 401);
+
+    app.clean_up().await;
+}
@@ -0,0 +1,230 @@
+use auth_service::domain::Email;
+use auth_service::domain::data_store::EmailChangeToken;
+
+use crate::helpers::{get_random_email, signup_and_login, TestApp};
+
+#[tokio::test]
+async fn should_return_422_if_malformed_input() {
+    let mut app = TestApp::new().await;
+
+    signup_and_login(&app, &get_random_email()).await;
+
+    let test_cases = [
+        serde_json::json!({
+            "password": "S3cure-Passw0rd!",
+        }),
+        serde_json::json!({
+            "newEmail": get_random_email(),
+        }),
+        serde_json::json!({
+            "": ""
+        })
+    ];
+
+    for test_case in test_cases.iter() {
+        let response = app.post_change_email(&test_case).await;
+        assert_eq!(
+            response.status().as_u16(),
+            422,
+            "Failed for input: {:?}",
+            test_case
+        );
+    }
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_400_if_jwt_cookie_missing() {
+    let mut app = TestApp::new().await;
+
+    let body = serde_json::json!({
+        "password": "S3cure-Passw0rd!",
+        "newEmail": get_random_email(),
+    });
+
+    let response = app.post_change_email(&body).await;
+    assert_eq!(response.status().as_u16(), 400);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_401_if_incorrect_password() {
+    let mut app = TestApp::new().await;
+
+    signup_and_login(&app, &get_random_email()).await;
+
+    let body = serde_json::json!({
+        "password": "wrong_password",
+        "newEmail": get_random_email(),
+    });
+
+    let response = app.post_change_email(&body).await;
+    assert_eq!(response.status().as_u16(), 401);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_409_if_new_email_already_exists() {
+    let mut app = TestApp::new().await;
+
+    let taken_email = get_random_email();
+    signup_and_login(&app, &taken_email).await;
+    signup_and_login(&app, &get_random_email()).await;
+
+    let body = serde_json::json!({
+        "password": "S3cure-Passw0rd!",
+        "newEmail": taken_email,
+    });
+
+    let response = app.post_change_email(&body).await;
+    assert_eq!(response.status().as_u16(), 409);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_400_if_new_email_is_the_current_one() {
+    let mut app = TestApp::new().await;
+
+    let email = get_random_email();
+    signup_and_login(&app, &email).await;
+
+    let body = serde_json::json!({
+        "password": "S3cure-Passw0rd!",
+        "newEmail": email.to_uppercase(),
+    });
+
+    let response = app.post_change_email(&body).await;
+    assert_eq!(response.status().as_u16(), 400);
+    let email = Email::parse(email).unwrap();
+    assert!(app.app_state.email_change_store.read().await.get_request(&email).await.is_err());
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_400_if_new_email_domain_is_disposable() {
+    let mut app = TestApp::new().await;
+
+    signup_and_login(&app, &get_random_email()).await;
+
+    let body = serde_json::json!({
+        "password": "S3cure-Passw0rd!",
+        "newEmail": "someone@guerrillamail.com",
+    });
+
+    let response = app.post_change_email(&body).await;
+    assert_eq!(response.status().as_u16(), 400);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_change_email_after_confirmation() {
+    let mut app = TestApp::new().await;
+
+    let old_email = get_random_email();
+    let new_email = get_random_email();
+    let old_token = signup_and_login(&app, &old_email).await;
+
+    let body = serde_json::json!({
+        "password": "S3cure-Passw0rd!",
+        "newEmail": new_email,
+    });
+
+    let response = app.post_change_email(&body).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let (confirmation_token, cancel_token);
+    {
+        let email_change_store = app.app_state.email_change_store.read().await;
+        let email = Email::parse(old_email.clone()).unwrap();
+        (_, confirmation_token, cancel_token) = email_change_store.get_request(&email).await.unwrap();
+    }
+
+    // The cancel token must not confirm the change
+    let response = app.get_confirm_email_change(&old_email, cancel_token.as_ref()).await;
+    assert_eq!(response.status().as_u16(), 401);
+
+    let response = app.get_confirm_email_change(&old_email, confirmation_token.as_ref()).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    // Tokens are bound to the user id, so existing sessions survive the change
+    let response = app.post_verify_token(&serde_json::json!({ "token": old_token })).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app.post_login(&serde_json::json!({
+        "email": old_email,
+        "password": "S3cure-Passw0rd!",
+    })).await;
+    assert_eq!(response.status().as_u16(), 401);
+
+    let response = app.post_login(&serde_json::json!({
+        "email": new_email,
+        "password": "S3cure-Passw0rd!",
+    })).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    // The link is single-use
+    let response = app.get_confirm_email_change(&old_email, confirmation_token.as_ref()).await;
+    assert_eq!(response.status().as_u16(), 401);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_not_change_email_after_cancellation() {
+    let mut app = TestApp::new().await;
+
+    let old_email = get_random_email();
+    let new_email = get_random_email();
+    signup_and_login(&app, &old_email).await;
+
+    let body = serde_json::json!({
+        "password": "S3cure-Passw0rd!",
+        "newEmail": new_email,
+    });
+
+    let response = app.post_change_email(&body).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let (confirmation_token, cancel_token);
+    {
+        let email_change_store = app.app_state.email_change_store.read().await;
+        let email = Email::parse(old_email.clone()).unwrap();
+        (_, confirmation_token, cancel_token) = email_change_store.get_request(&email).await.unwrap();
+    }
+
+    let response = app.get_cancel_email_change(&old_email, cancel_token.as_ref()).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app.get_confirm_email_change(&old_email, confirmation_token.as_ref()).await;
+    assert_eq!(response.status().as_u16(), 401);
+
+    let response = app.post_login(&serde_json::json!({
+        "email": old_email,
+        "password": "S3cure-Passw0rd!",
+    })).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_401_if_unknown_token() {
+    let mut app = TestApp::new().await;
+
+    let email = get_random_email();
+    let token = EmailChangeToken::default();
+
+    let response = app.get_confirm_email_change(&email, token.as_ref()).await;
+    assert_eq!(response.status().as_u16(), 401);
+
+    let response = app.get_cancel_email_change(&email, "not_a_token").await;
+    assert_eq!(response.status().as_u16(), 401);
+
+    app.clean_up().await;
+}
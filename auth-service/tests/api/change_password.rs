@@ -0,0 +1,255 @@
+use auth_service::{routes::PasswordExpiredResponse, utils::constants::JWT_COOKIE_NAME, ErrorResponse};
+use reqwest::Url;
+
+use crate::helpers::{get_random_email, signup_and_login, TestApp};
+
+#[tokio::test]
+async fn should_return_422_if_malformed_input() {
+    let mut app = TestApp::new().await;
+
+    signup_and_login(&app, &get_random_email()).await;
+
+    let test_cases = [
+        serde_json::json!({
+            "currentPassword": "S3cure-Passw0rd!",
+        }),
+        serde_json::json!({
+            "newPassword": "N3w-Passw0rd-456",
+        }),
+        serde_json::json!({
+            "": ""
+        })
+    ];
+
+    for test_case in test_cases.iter() {
+        let response = app.post_change_password(&test_case).await;
+        assert_eq!(
+            response.status().as_u16(),
+            422,
+            "Failed for input: {:?}",
+            test_case
+        );
+    }
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_400_if_jwt_cookie_missing() {
+    let mut app = TestApp::new().await;
+
+    let body = serde_json::json!({
+        "currentPassword": "S3cure-Passw0rd!",
+        "newPassword": "N3w-Passw0rd-456",
+    });
+
+    let response = app.post_change_password(&body).await;
+    assert_eq!(response.status().as_u16(), 400);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_401_if_invalid_token() {
+    let mut app = TestApp::new().await;
+
+    app.cookie_jar.add_cookie_str(
+        &format!(
+            "{}=invalid; HttpOnly; SameSite=Lax; Secure; Path=/",
+            JWT_COOKIE_NAME
+        ),
+        &Url::parse("http://127.0.0.1").expect("Failed to parse URL"),
+    );
+
+    let body = serde_json::json!({
+        "currentPassword": "S3cure-Passw0rd!",
+        "newPassword": "N3w-Passw0rd-456",
+    });
+
+    let response = app.post_change_password(&body).await;
+    assert_eq!(response.status().as_u16(), 401);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_401_if_incorrect_current_password() {
+    let mut app = TestApp::new().await;
+
+    signup_and_login(&app, &get_random_email()).await;
+
+    let body = serde_json::json!({
+        "currentPassword": "wrong_password",
+        "newPassword": "N3w-Passw0rd-456",
+    });
+
+    let response = app.post_change_password(&body).await;
+    assert_eq!(response.status().as_u16(), 401);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_400_if_invalid_new_password() {
+    let mut app = TestApp::new().await;
+
+    signup_and_login(&app, &get_random_email()).await;
+
+    let body = serde_json::json!({
+        "currentPassword": "S3cure-Passw0rd!",
+        "newPassword": "1234567",
+    });
+
+    let response = app.post_change_password(&body).await;
+    assert_eq!(response.status().as_u16(), 400);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_200_and_revoke_old_sessions() {
+    let mut app = TestApp::new().await;
+
+    let random_email = get_random_email();
+    let old_token = signup_and_login(&app, &random_email).await;
+
+    let body = serde_json::json!({
+        "currentPassword": "S3cure-Passw0rd!",
+        "newPassword": "N3w-Passw0rd-456",
+    });
+
+    let response = app.post_change_password(&body).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let new_token = response.cookies()
+        .find(|cookie| cookie.name() == JWT_COOKIE_NAME)
+        .expect("No auth cookie found")
+        .value()
+        .to_string();
+
+    let response = app.post_verify_token(&serde_json::json!({ "token": old_token })).await;
+    assert_eq!(response.status().as_u16(), 401);
+
+    let response = app.post_verify_token(&serde_json::json!({ "token": new_token })).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app.post_login(&serde_json::json!({
+        "email": random_email,
+        "password": "S3cure-Passw0rd!",
+    })).await;
+    assert_eq!(response.status().as_u16(), 401);
+
+    let response = app.post_login(&serde_json::json!({
+        "email": random_email,
+        "password": "N3w-Passw0rd-456",
+    })).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_200_if_the_notification_fails() {
+    let mut app = TestApp::new().await;
+
+    let random_email = get_random_email();
+    signup_and_login(&app, &random_email).await;
+    app.email_client.fail_sending();
+
+    let response = app.post_change_password(&serde_json::json!({
+        "currentPassword": "S3cure-Passw0rd!",
+        "newPassword": "N3w-Passw0rd-456",
+    })).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app.post_login(&serde_json::json!({
+        "email": random_email,
+        "password": "N3w-Passw0rd-456",
+    })).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_400_if_password_was_recently_used() {
+    let mut app = TestApp::new().await;
+
+    signup_and_login(&app, &get_random_email()).await;
+
+    let response = app.post_change_password(&serde_json::json!({
+        "currentPassword": "S3cure-Passw0rd!",
+        "newPassword": "N3w-Passw0rd-456",
+    })).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app.post_change_password(&serde_json::json!({
+        "currentPassword": "N3w-Passw0rd-456",
+        "newPassword": "S3cure-Passw0rd!",
+    })).await;
+    assert_eq!(response.status().as_u16(), 400);
+
+    let rules: Vec<String> = response
+        .json::<ErrorResponse>()
+        .await
+        .expect("Could not deserialize response body to ErrorResponse")
+        .details
+        .expect("Response should list the failed rules")
+        .into_iter()
+        .map(|detail| detail.rule)
+        .collect();
+    assert_eq!(rules, vec!["recently_used".to_owned()]);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_only_allow_password_change_if_password_expired() {
+    let mut app = TestApp::new().await;
+
+    let random_email = get_random_email();
+    signup_and_login(&app, &random_email).await;
+    app.expire_password(&random_email).await;
+
+    let response = app.post_login(&serde_json::json!({
+        "email": random_email,
+        "password": "S3cure-Passw0rd!",
+    })).await;
+    assert_eq!(response.status().as_u16(), 403);
+
+    let restricted_token = response.cookies()
+        .find(|cookie| cookie.name() == JWT_COOKIE_NAME)
+        .expect("No auth cookie found")
+        .value()
+        .to_string();
+
+    let json_body = response
+        .json::<PasswordExpiredResponse>()
+        .await
+        .expect("Could not deserialize response body to PasswordExpiredResponse");
+    assert_eq!(json_body.message, "Password expired, change required".to_owned());
+
+    // The restricted token is not accepted anywhere else
+    let response = app.post_verify_token(&serde_json::json!({ "token": restricted_token })).await;
+    assert_eq!(response.status().as_u16(), 401);
+
+    let response = app.get_account_export().await;
+    assert_eq!(response.status().as_u16(), 401);
+
+    let response = app.post_change_password(&serde_json::json!({
+        "currentPassword": "S3cure-Passw0rd!",
+        "newPassword": "N3w-Passw0rd-456",
+    })).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app.get_account_export().await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app.post_login(&serde_json::json!({
+        "email": random_email,
+        "password": "N3w-Passw0rd-456",
+    })).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    app.clean_up().await;
+}
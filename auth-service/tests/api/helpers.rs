@@ -1,5 +1,5 @@
 use std::str::FromStr;
-use std::sync::{Arc, Mutex};
+use std::sync::{atomic::{AtomicBool, Ordering}, Arc, Mutex};
 
 use auth_service::{app_state::AppState, get_postgres_pool, get_redis_client, services::data_store::{postgres_user_store::PostgresUserStore,
                                                                                                     hashset_token_store::HashsetBannedTokenStore}, utils::test, Application};
@@ -8,34 +8,92 @@ use sqlx::{Connection, Executor, PgConnection, PgPool};
 use sqlx::postgres::{PgConnectOptions, PgPoolOptions};
 use tokio::sync::RwLock;
 use uuid::Uuid;
-use auth_service::domain::MockEmailClient;
+use auth_service::domain::{Email, EmailClient, Password, PasswordPepper, Tenant, TenantId, TenantSettings, TenantSlug, TokenExchangeClient, User, UserId};
 use auth_service::services::data_store::hashmap_two_fa_code_store::HashmapTwoFACodeStore;
-use auth_service::services::data_store::redis_banned_token_store::RedisBannedTokenStore;
-use auth_service::services::data_store::redis_two_fa_code_store::RedisTwoFACodeStore;
-use auth_service::utils::{DATABASE_URL, REDIS_HOST_NAME};
+use auth_service::services::data_store::redis_banned_token_store::{RedisBannedTokenStore, BANNED_USER_KEY_PREFIX};
+use auth_service::services::data_store::redis_two_fa_code_store::{RedisTwoFACodeStore, DISABLE_2FA_CODE_PREFIX};
+use auth_service::services::data_store::redis_email_change_store::RedisEmailChangeStore;
+use auth_service::services::data_store::postgres_audit_log_store::PostgresAuditLogStore;
+use auth_service::services::data_store::postgres_role_store::PostgresRoleStore;
+use auth_service::services::data_store::postgres_tenant_store::PostgresTenantStore;
+use auth_service::services::data_store::postgres_invitation_store::PostgresInvitationStore;
+use auth_service::services::data_store::postgres_personal_access_token_store::PostgresPersonalAccessTokenStore;
+use auth_service::services::email_domain_filter::FileEmailDomainFilter;
+use auth_service::services::password_policy::StrengthPasswordPolicy;
+use auth_service::services::hashing_pool::HashingPool;
+use auth_service::services::password_hasher::Argon2PasswordHasher;
+use auth_service::app_state::{HashingPoolType, PasswordHasherType};
+use auth_service::utils::{ARGON2_PARAMS, DATABASE_URL, JWT_COOKIE_NAME, PASSWORD_HISTORY_LENGTH, PASSWORD_MAX_LENGTH, PASSWORD_MIN_LENGTH, PASSWORD_MIN_STRENGTH, REDIS_HOST_NAME};
+
+const TEST_PASSWORD_MAX_AGE_DAYS: i64 = 90;
+const TEST_HASHING_MAX_CONCURRENCY: usize = 2;
+const TEST_HASHING_MAX_QUEUED: usize = 16;
+pub const TEST_CLIENT_ID: &str = "orders";
+pub const TEST_CLIENT_SECRET: &str = "orders-s3cret";
+pub const TEST_CLIENT_AUDIENCE: &str = "billing";
 
 pub struct TestApp {
     pub address: String,
     pub cookie_jar: Arc<Jar>,
     pub http_client: reqwest::Client,
     pub app_state: AppState,
+    pub email_client: RecordingEmailClient,
     pub db_name: String,
     pub clean_up_called: bool,
 }
 
 impl TestApp {
     pub async fn new() -> Self {
+        Self::build(false, TEST_HASHING_MAX_CONCURRENCY, TEST_HASHING_MAX_QUEUED).await
+    }
+
+    pub async fn with_enumeration_safe_signup() -> Self {
+        Self::build(true, TEST_HASHING_MAX_CONCURRENCY, TEST_HASHING_MAX_QUEUED).await
+    }
+
+    pub async fn with_hashing_pool(max_concurrency: usize, max_queued: usize) -> Self {
+        Self::build(false, max_concurrency, max_queued).await
+    }
+
+    async fn build(enumeration_safe_signup: bool, hashing_max_concurrency: usize, hashing_max_queued: usize) -> Self {
         let db_name = Uuid::new_v4().to_string();
         let pg_pool = configure_postgresql(&db_name).await;
         let redis_connection = Arc::new(RwLock::new(configure_redis()));
 
 
-        let user_store  = Arc::new(RwLock::new(PostgresUserStore::new(pg_pool)));
+        let hashing_pool = Arc::new(HashingPool::new(hashing_max_concurrency, hashing_max_queued));
+        let user_store  = Arc::new(RwLock::new(PostgresUserStore::new(pg_pool.clone(), test_password_hasher(hashing_pool.clone()))));
+        let audit_log_store  = Arc::new(RwLock::new(PostgresAuditLogStore::new(pg_pool.clone())));
+        let role_store  = Arc::new(RwLock::new(PostgresRoleStore::new(pg_pool.clone())));
+        let tenant_store  = Arc::new(RwLock::new(PostgresTenantStore::new(pg_pool.clone())));
+        let invitation_store  = Arc::new(RwLock::new(PostgresInvitationStore::new(pg_pool.clone())));
+        let personal_access_token_store  = Arc::new(RwLock::new(PostgresPersonalAccessTokenStore::new(pg_pool)));
         let banned_token_store  = Arc::new(RwLock::new(RedisBannedTokenStore::new(redis_connection.clone())));
         let two_fa_code_store  = Arc::new(RwLock::new(RedisTwoFACodeStore::new(redis_connection.clone())));
-        let email_client = Arc::new(RwLock::new(MockEmailClient));
-
-        let app_state = AppState::new(user_store, banned_token_store, two_fa_code_store, email_client);
+        let disable_2fa_code_store  = Arc::new(RwLock::new(RedisTwoFACodeStore::new(redis_connection.clone()).with_key_prefix(DISABLE_2FA_CODE_PREFIX)));
+        let email_change_store  = Arc::new(RwLock::new(RedisEmailChangeStore::new(redis_connection.clone())));
+        let recording_email_client = RecordingEmailClient::default();
+        let email_client = Arc::new(RwLock::new(recording_email_client.clone()));
+
+        // Expiry is disabled by default, the tests enable it to cover expired passwords
+        let password_policy = Arc::new(StrengthPasswordPolicy::new(
+            *PASSWORD_MIN_LENGTH,
+            *PASSWORD_MAX_LENGTH,
+            *PASSWORD_MIN_STRENGTH,
+            *PASSWORD_HISTORY_LENGTH,
+            TEST_PASSWORD_MAX_AGE_DAYS,
+            None,
+        ));
+
+        let app_state = AppState::new(user_store, banned_token_store, two_fa_code_store, email_change_store, audit_log_store, email_client, password_policy, hashing_pool)
+            .with_enumeration_safe_signup(enumeration_safe_signup)
+            .with_disable_2fa_code_store(disable_2fa_code_store)
+            .with_role_store(role_store)
+            .with_tenant_store(tenant_store)
+            .with_invitation_store(invitation_store)
+            .with_personal_access_token_store(personal_access_token_store)
+            .with_email_domain_filter(Arc::new(FileEmailDomainFilter::new(true)))
+            .with_token_exchange_clients(vec![test_token_exchange_client()]);
 
         let app = Application::build(app_state.clone(), test::APP_ADDRESS)
             .await
@@ -63,6 +121,7 @@ impl TestApp {
             cookie_jar,
             http_client,
             app_state,
+            email_client: recording_email_client,
             db_name,
             clean_up_called
         }
@@ -73,6 +132,135 @@ impl TestApp {
         self.clean_up_called = true;
     }
 
+    // Seconds until the marker banning the user's tokens expires
+    pub fn banned_user_ttl(&self, user_id: &str) -> i64 {
+        redis::cmd("TTL")
+            .arg(format!("{}{}", BANNED_USER_KEY_PREFIX, user_id))
+            .query(&mut configure_redis())
+            .expect("Failed to read the ban marker")
+    }
+
+    // Move the user's last password change far enough into the past for the password to be expired
+    pub async fn expire_password(&self, email: &str) {
+        let db_conn_string = format!("{}/{}", DATABASE_URL.as_str(), self.db_name);
+        let mut connection = PgConnection::connect(&db_conn_string)
+            .await
+            .expect("Failed to connect to Postgres");
+
+        sqlx::query("UPDATE users SET password_changed_at = NOW() - make_interval(days => $1) WHERE email = $2")
+            .bind(TEST_PASSWORD_MAX_AGE_DAYS as i32)
+            .bind(email)
+            .execute(&mut connection)
+            .await
+            .expect("Failed to expire password");
+    }
+
+    pub async fn expire_invitations(&self) {
+        let db_conn_string = format!("{}/{}", DATABASE_URL.as_str(), self.db_name);
+        let mut connection = PgConnection::connect(&db_conn_string)
+            .await
+            .expect("Failed to connect to Postgres");
+
+        sqlx::query("UPDATE invitations SET expires_at = NOW()")
+            .execute(&mut connection)
+            .await
+            .expect("Failed to expire invitations");
+    }
+
+    pub async fn expire_personal_access_tokens(&self) {
+        let db_conn_string = format!("{}/{}", DATABASE_URL.as_str(), self.db_name);
+        let mut connection = PgConnection::connect(&db_conn_string)
+            .await
+            .expect("Failed to connect to Postgres");
+
+        sqlx::query("UPDATE personal_access_tokens SET expires_at = NOW()")
+            .execute(&mut connection)
+            .await
+            .expect("Failed to expire personal access tokens");
+    }
+
+    // Insert a user the way the import command does, keeping the given hash
+    pub async fn import_user(&self, email: &str, password_hash: &str) {
+        let db_conn_string = format!("{}/{}", DATABASE_URL.as_str(), self.db_name);
+        let pg_pool = get_postgres_pool(&db_conn_string)
+            .await
+            .expect("Failed to create Postgres connection pool!");
+
+        let user = User::new(
+            Email::parse(email.to_string()).expect("Invalid email"),
+            Password::parse(password_hash.to_string()).expect("Invalid password hash"),
+            false,
+        );
+        PostgresUserStore::new(pg_pool, test_password_hasher(self.app_state.hashing_pool.clone()))
+            .import_user(user)
+            .await
+            .expect("Failed to import user");
+    }
+
+    pub async fn get_password_hash(&self, email: &str) -> String {
+        let db_conn_string = format!("{}/{}", DATABASE_URL.as_str(), self.db_name);
+        let mut connection = PgConnection::connect(&db_conn_string)
+            .await
+            .expect("Failed to connect to Postgres");
+
+        sqlx::query_scalar("SELECT password_hash FROM users WHERE email = $1")
+            .bind(email)
+            .fetch_one(&mut connection)
+            .await
+            .expect("Failed to get password hash")
+    }
+
+    // Roles are assigned directly in the store, the way the `assign_role` command does
+    pub async fn assign_role(&self, email: &str, role: &str) {
+        let user_id = self.get_user_id(email).await;
+        self.app_state.role_store.write().await
+            .assign_role(&UserId::parse(user_id).expect("Invalid user id"), role)
+            .await
+            .expect("Failed to assign role");
+    }
+
+    // Organizations are created directly in the store, there is no API to create them
+    pub async fn add_tenant(&self, slug: &str, host: Option<&str>) -> TenantId {
+        let tenant = Tenant::new(
+            TenantSlug::parse(slug.to_string()).expect("Invalid tenant slug"),
+            slug.to_string(),
+            host.map(str::to_string),
+        );
+        let tenant_id = tenant.id;
+        self.app_state.tenant_store.write().await
+            .add_tenant(tenant)
+            .await
+            .expect("Failed to add tenant");
+        tenant_id
+    }
+
+    pub async fn update_tenant_settings(&self, tenant_id: &TenantId, settings: serde_json::Value) {
+        let settings = serde_json::from_value::<TenantSettings>(settings).expect("Invalid tenant settings");
+        self.app_state.tenant_store.write().await
+            .update_settings(tenant_id, settings.parse().expect("Invalid tenant settings"))
+            .await
+            .expect("Failed to update tenant settings");
+    }
+
+    // The token of the latest invitation link emailed to the address, only its hash is stored
+    pub fn get_invitation_token(&self, email: &str) -> String {
+        let marker = "?invite=";
+        let content = self.email_client.last_email_containing(email, marker).expect("No invitation found");
+        let start = content.find(marker).unwrap() + marker.len();
+
+        content[start..].split_whitespace().next().unwrap().to_owned()
+    }
+
+    pub async fn get_user_id(&self, email: &str) -> String {
+        let email = Email::parse(email.to_string()).expect("Invalid email");
+        self.app_state.user_store.read().await
+            .get_user_by_email(&email)
+            .await
+            .expect("Failed to get user")
+            .get_id()
+            .to_string()
+    }
+
     pub async fn get_root(&self) -> reqwest::Response {
         self.http_client
             .get(&format!("{}/", &self.address))
@@ -81,6 +269,14 @@ impl TestApp {
             .expect("Failed to execute request.")
     }
 
+    pub async fn get_metrics(&self) -> reqwest::Response {
+        self.http_client
+            .get(&format!("{}/metrics", &self.address))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
     pub async fn post_signup<Body>(&self, body: &Body) -> reqwest::Response
     where
         Body: serde::Serialize
@@ -105,6 +301,33 @@ impl TestApp {
             .expect("Failed to execute request.")
     }
 
+    // Sent with another Host header, the way a browser on an organization's login domain would
+    pub async fn post_login_with_host<Body>(&self, host: &str, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.http_client
+            .post(format!("{}/login", &self.address))
+            .header(reqwest::header::HOST, host)
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_signup_with_host<Body>(&self, host: &str, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.http_client
+            .post(format!("{}/signup", &self.address))
+            .header(reqwest::header::HOST, host)
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
     pub async fn post_verify_2fa<Body>(&self, body: &Body) -> reqwest::Response
     where
         Body: serde::Serialize,
@@ -125,6 +348,257 @@ impl TestApp {
             .expect("Failed to execute request.")
     }
 
+    pub async fn post_change_password<Body>(&self, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.http_client
+            .post(format!("{}/change-password", &self.address))
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_change_email<Body>(&self, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.http_client
+            .post(format!("{}/change-email", &self.address))
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn get_confirm_email_change(&self, email: &str, token: &str) -> reqwest::Response {
+        self.http_client
+            .get(format!("{}/confirm-email-change", &self.address))
+            .query(&[("email", email), ("token", token)])
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn get_cancel_email_change(&self, email: &str, token: &str) -> reqwest::Response {
+        self.http_client
+            .get(format!("{}/cancel-email-change", &self.address))
+            .query(&[("email", email), ("token", token)])
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn get_account_export(&self) -> reqwest::Response {
+        self.http_client
+            .get(format!("{}/account/export", &self.address))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_account_delete<Body>(&self, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.http_client
+            .post(format!("{}/account/delete", &self.address))
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_account_cancel_deletion<Body>(&self, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.http_client
+            .post(format!("{}/account/cancel-deletion", &self.address))
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_enable_2fa<Body>(&self, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.http_client
+            .post(format!("{}/enable-2fa", &self.address))
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_disable_2fa<Body>(&self, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.http_client
+            .post(format!("{}/disable-2fa", &self.address))
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn get_admin_users(&self, query: &[(&str, &str)]) -> reqwest::Response {
+        self.http_client
+            .get(format!("{}/admin/users", &self.address))
+            .query(query)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn get_admin_user(&self, user_id: &str) -> reqwest::Response {
+        self.http_client
+            .get(format!("{}/admin/users/{}", &self.address, user_id))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    // Actions without a body: force-password-reset, lock, unlock, revoke-sessions, approve and reject
+    pub async fn post_admin_user_action(&self, user_id: &str, action: &str) -> reqwest::Response {
+        self.http_client
+            .post(format!("{}/admin/users/{}/{}", &self.address, user_id, action))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn get_admin_console(&self, query: &[(&str, &str)]) -> reqwest::Response {
+        self.http_client
+            .get(format!("{}/admin/console", &self.address))
+            .query(query)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn get_admin_console_user(&self, user_id: &str) -> reqwest::Response {
+        self.http_client
+            .get(format!("{}/admin/console/users/{}", &self.address, user_id))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    // Console forms: lock, unlock and force-password-reset
+    pub async fn post_admin_console_action(&self, user_id: &str, action: &str, csrf_token: &str) -> reqwest::Response {
+        self.http_client
+            .post(format!("{}/admin/console/users/{}/{}", &self.address, user_id, action))
+            .form(&[("csrf_token", csrf_token)])
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_admin_user_2fa<Body>(&self, user_id: &str, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.http_client
+            .post(format!("{}/admin/users/{}/2fa", &self.address, user_id))
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn get_admin_user_roles(&self, user_id: &str) -> reqwest::Response {
+        self.http_client
+            .get(format!("{}/admin/users/{}/roles", &self.address, user_id))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_admin_user_roles<Body>(&self, user_id: &str, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.http_client
+            .post(format!("{}/admin/users/{}/roles", &self.address, user_id))
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn delete_admin_user_role(&self, user_id: &str, role: &str) -> reqwest::Response {
+        self.http_client
+            .delete(format!("{}/admin/users/{}/roles/{}", &self.address, user_id, role))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_admin_invitation<Body>(&self, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.http_client
+            .post(format!("{}/admin/invitations", &self.address))
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn get_admin_invitations(&self) -> reqwest::Response {
+        self.http_client
+            .get(format!("{}/admin/invitations", &self.address))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn delete_admin_invitation(&self, invitation_id: &str) -> reqwest::Response {
+        self.http_client
+            .delete(format!("{}/admin/invitations/{}", &self.address, invitation_id))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_accept_invite<Body>(&self, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.http_client
+            .post(format!("{}/accept-invite", &self.address))
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn get_admin_tenant(&self) -> reqwest::Response {
+        self.http_client
+            .get(format!("{}/admin/tenant", &self.address))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn put_admin_tenant_settings<Body>(&self, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.http_client
+            .put(format!("{}/admin/tenant/settings", &self.address))
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
     pub async fn post_verify_token<Body>(&self, body: &Body) -> reqwest::Response
     where
         Body: serde::Serialize,
@@ -136,6 +610,71 @@ impl TestApp {
             .await
             .expect("Failed to execute request.")
     }
+
+    // Token endpoint requests are form encoded, the client authenticating with HTTP Basic when `client` is given
+    pub async fn post_token(&self, client: Option<(&str, &str)>, form: &[(&str, &str)]) -> reqwest::Response {
+        let request = self.http_client.post(format!("{}/token", &self.address)).form(form);
+        let request = match client {
+            Some((client_id, client_secret)) => request.basic_auth(client_id, Some(client_secret)),
+            None => request,
+        };
+
+        request
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn get_account_tokens(&self) -> reqwest::Response {
+        self.http_client
+            .get(format!("{}/account/tokens", &self.address))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_account_tokens<Body>(&self, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        self.http_client
+            .post(format!("{}/account/tokens", &self.address))
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn delete_account_token(&self, token_id: &str) -> reqwest::Response {
+        self.http_client
+            .delete(format!("{}/account/tokens/{}", &self.address, token_id))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    // Sent the way scripts do, without the cookies of the logged in user
+    pub async fn get_with_bearer_token(&self, path: &str, token: &str) -> reqwest::Response {
+        reqwest::Client::new()
+            .get(format!("{}{}", &self.address, path))
+            .bearer_auth(token)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
+
+    pub async fn post_with_bearer_token<Body>(&self, path: &str, token: &str, body: &Body) -> reqwest::Response
+    where
+        Body: serde::Serialize,
+    {
+        reqwest::Client::new()
+            .post(format!("{}{}", &self.address, path))
+            .bearer_auth(token)
+            .json(body)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+    }
 }
 
 impl Drop for TestApp {
@@ -146,6 +685,12 @@ impl Drop for TestApp {
     }
 }
 
+// Hashes are always peppered in tests so the pepper lookup is exercised
+fn test_password_hasher(hashing_pool: HashingPoolType) -> PasswordHasherType {
+    let peppers = vec![PasswordPepper::new("test".to_string(), "test-pepper".to_string()).expect("Valid test pepper")];
+    Arc::new(Argon2PasswordHasher::new(ARGON2_PARAMS.clone(), peppers, hashing_pool))
+}
+
 async fn configure_postgresql(db_name: &str) -> PgPool {
     let postgresql_conn_url = DATABASE_URL.to_owned();
 
@@ -229,6 +774,83 @@ fn configure_redis() -> redis::Connection {
         .expect("Failed to get Redis connection")
 }
 
+fn test_token_exchange_client() -> TokenExchangeClient {
+    TokenExchangeClient::new(TEST_CLIENT_ID.to_owned(), TEST_CLIENT_SECRET.to_owned(), vec![TEST_CLIENT_AUDIENCE.to_owned()])
+        .expect("Test client is valid")
+}
+
 pub fn get_random_email() -> String {
     format!("{}@example.com", Uuid::new_v4())
 }
+
+// Create a user without 2FA, log it in and return the issued JWT
+pub async fn signup_and_login(app: &TestApp, email: &str) -> String {
+    let signup_body = serde_json::json!({
+        "email": email,
+        "password": "S3cure-Passw0rd!",
+        "requires2FA": false
+    });
+
+    app.post_signup(&signup_body).await;
+
+    login(app, email).await
+}
+
+// Create a user with the admin role and log it in, its token grants every admin permission
+pub async fn signup_admin_and_login(app: &TestApp, email: &str) -> String {
+    signup_and_login(app, email).await;
+    app.assign_role(email, "admin").await;
+    login(app, email).await
+}
+
+// Log in a user created by `signup_and_login` and return the issued JWT
+pub async fn login(app: &TestApp, email: &str) -> String {
+    let login_body = serde_json::json!({
+        "email": email,
+        "password": "S3cure-Passw0rd!",
+    });
+
+    let login_response = app.post_login(&login_body).await;
+
+    let token = login_response.cookies()
+        .find(|cookie| cookie.name() == JWT_COOKIE_NAME)
+        .expect("No auth cookie found")
+        .value()
+        .to_string();
+
+    token
+}
+
+// Keeps every email instead of sending it, so tests can follow the links they contain
+#[derive(Clone, Default)]
+pub struct RecordingEmailClient {
+    sent: Arc<Mutex<Vec<(String, String)>>>,
+    failing: Arc<AtomicBool>,
+}
+
+impl RecordingEmailClient {
+    // Every email sent afterwards fails, as if the provider were down
+    pub fn fail_sending(&self) {
+        self.failing.store(true, Ordering::SeqCst);
+    }
+
+    pub fn last_email_containing(&self, recipient: &str, text: &str) -> Option<String> {
+        self.sent.lock().unwrap()
+            .iter()
+            .rev()
+            .find(|(to, content)| to == recipient && content.contains(text))
+            .map(|(_, content)| content.clone())
+    }
+}
+
+#[async_trait::async_trait]
+impl EmailClient for RecordingEmailClient {
+    async fn send_email(&self, recipient: &Email, _subject: &str, content: &str) -> Result<(), String> {
+        if self.failing.load(Ordering::SeqCst) {
+            return Err("The email provider is unavailable".to_owned());
+        }
+
+        self.sent.lock().unwrap().push((recipient.as_ref().to_owned(), content.to_owned()));
+        Ok(())
+    }
+}
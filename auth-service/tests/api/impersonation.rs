@@ -0,0 +1,171 @@
+use auth_service::{
+    domain::UserId,
+    routes::{ImpersonationResponse, VerifyTokenResponse},
+    utils::constants::{IMPERSONATION_TTL_SECONDS, JWT_COOKIE_NAME, TOKEN_EXCHANGE_TTL_SECONDS},
+    ErrorResponse,
+};
+
+use crate::helpers::{get_random_email, login, signup_admin_and_login, signup_and_login, TestApp};
+
+fn auth_token(response: &reqwest::Response) -> String {
+    response.cookies()
+        .find(|cookie| cookie.name() == JWT_COOKIE_NAME)
+        .expect("No auth cookie found")
+        .value()
+        .to_owned()
+}
+
+#[tokio::test]
+async fn should_impersonate_user_with_actor_claim() {
+    let mut app = TestApp::new().await;
+
+    let email = get_random_email();
+    signup_and_login(&app, &email).await;
+    app.assign_role(&email, "admin").await;
+    let user_id = app.get_user_id(&email).await;
+
+    let admin_email = get_random_email();
+    signup_admin_and_login(&app, &admin_email).await;
+    let admin_id = app.get_user_id(&admin_email).await;
+
+    let response = app.post_admin_user_action(&user_id, "impersonate").await;
+    assert_eq!(response.status().as_u16(), 200);
+    let token = auth_token(&response);
+    let impersonation = response.json::<ImpersonationResponse>().await.unwrap();
+    assert_eq!(impersonation.user_id, user_id);
+    assert_eq!(impersonation.email, email);
+
+    // Other services see the user, their roles and the admin acting for them
+    let response = app.post_verify_token(&serde_json::json!({ "token": token })).await;
+    assert_eq!(response.status().as_u16(), 200);
+    let verified = response.json::<VerifyTokenResponse>().await.unwrap();
+    assert_eq!(verified.roles, vec!["admin"]);
+    assert!(verified.permissions.is_empty());
+    assert_eq!(verified.act.unwrap().sub, admin_id);
+
+    // The permissions of the user are not granted
+    let response = app.get_admin_users(&[]).await;
+    assert_eq!(response.status().as_u16(), 403);
+
+    let response = app.post_logout().await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    login(&app, &admin_email).await;
+    let audit_log = app.app_state.audit_log_store.read().await
+        .get_entries(&UserId::parse(user_id).unwrap())
+        .await
+        .unwrap();
+    let entry = audit_log.last().unwrap();
+    assert_eq!(entry.event, "impersonated");
+    assert_eq!(entry.actor_id.unwrap().to_string(), admin_id);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_refuse_sensitive_operations_while_impersonating() {
+    let mut app = TestApp::new().await;
+
+    let email = get_random_email();
+    signup_and_login(&app, &email).await;
+    let user_id = app.get_user_id(&email).await;
+    signup_admin_and_login(&app, &get_random_email()).await;
+
+    let response = app.post_admin_user_action(&user_id, "impersonate").await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app.post_change_password(&serde_json::json!({
+        "currentPassword": "S3cure-Passw0rd!",
+        "newPassword": "An0ther-S3cure-Passw0rd!",
+    })).await;
+    assert_eq!(response.status().as_u16(), 403);
+    assert_eq!(response.json::<ErrorResponse>().await.unwrap().error, "Not allowed while impersonating a user");
+
+    let response = app.post_change_email(&serde_json::json!({
+        "password": "S3cure-Passw0rd!",
+        "newEmail": get_random_email(),
+    })).await;
+    assert_eq!(response.status().as_u16(), 403);
+
+    let response = app.get_account_export().await;
+    assert_eq!(response.status().as_u16(), 403);
+
+    let response = app.post_account_delete(&serde_json::json!({ "password": "S3cure-Passw0rd!" })).await;
+    assert_eq!(response.status().as_u16(), 403);
+
+    let response = app.post_enable_2fa(&serde_json::json!({ "password": "S3cure-Passw0rd!" })).await;
+    assert_eq!(response.status().as_u16(), 403);
+
+    // Nor can it start another impersonation
+    let response = app.post_admin_user_action(&user_id, "impersonate").await;
+    assert_eq!(response.status().as_u16(), 403);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_keep_sessions_revoked_until_impersonation_tokens_expire() {
+    let mut app = TestApp::new().await;
+
+    let email = get_random_email();
+    signup_and_login(&app, &email).await;
+    let user_id = app.get_user_id(&email).await;
+    let admin_email = get_random_email();
+    signup_admin_and_login(&app, &admin_email).await;
+
+    let response = app.post_admin_user_action(&user_id, "impersonate").await;
+    let token = auth_token(&response);
+    login(&app, &admin_email).await;
+
+    let response = app.post_admin_user_action(&user_id, "revoke-sessions").await;
+    assert_eq!(response.status().as_u16(), 200);
+    let response = app.post_verify_token(&serde_json::json!({ "token": token })).await;
+    assert_eq!(response.status().as_u16(), 401);
+
+    // The ban would lift before the impersonation token expires otherwise
+    assert!(app.banned_user_ttl(&user_id) > *IMPERSONATION_TTL_SECONDS - 5);
+    assert!(app.banned_user_ttl(&user_id) > *TOKEN_EXCHANGE_TTL_SECONDS - 5);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_only_let_admins_impersonate_members_of_their_organization() {
+    let mut app = TestApp::new().await;
+
+    let email = get_random_email();
+    signup_and_login(&app, &email).await;
+    let user_id = app.get_user_id(&email).await;
+
+    let response = app.post_admin_user_action(&user_id, "impersonate").await;
+    assert_eq!(response.status().as_u16(), 403);
+
+    app.add_tenant("acme", None).await;
+    let other_email = get_random_email();
+    app.post_signup(&serde_json::json!({
+        "tenant": "acme",
+        "email": other_email,
+        "password": "S3cure-Passw0rd!",
+        "requires2FA": false
+    })).await;
+
+    let admin_email = get_random_email();
+    signup_admin_and_login(&app, &admin_email).await;
+
+    let response = app.post_admin_user_action(&app.get_user_id(&other_email).await, "impersonate").await;
+    assert_eq!(response.status().as_u16(), 404);
+
+    let response = app.post_admin_user_action(&app.get_user_id(&admin_email).await, "impersonate").await;
+    assert_eq!(response.status().as_u16(), 403);
+
+    // Revoking the user's sessions ends the impersonation too
+    let response = app.post_admin_user_action(&user_id, "impersonate").await;
+    assert_eq!(response.status().as_u16(), 200);
+    let token = auth_token(&response);
+
+    app.app_state.banned_token_store.write().await.ban_user_tokens(&user_id).await.unwrap();
+    let response = app.post_verify_token(&serde_json::json!({ "token": token })).await;
+    assert_eq!(response.status().as_u16(), 401);
+
+    app.clean_up().await;
+}
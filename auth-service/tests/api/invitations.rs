@@ -0,0 +1,253 @@
+use auth_service::{
+    domain::{InvitationStatus, TenantId, UserRoles},
+    routes::{AcceptInvitationResponse, InvitationResponse, ListInvitationsResponse},
+    utils::constants::JWT_COOKIE_NAME,
+};
+
+use crate::helpers::{get_random_email, signup_admin_and_login, signup_and_login, TestApp};
+
+// Sign up an admin of the organization and log them in to it
+async fn signup_tenant_admin_and_login(app: &TestApp, tenant: &str, email: &str) {
+    app.post_signup(&serde_json::json!({
+        "tenant": tenant,
+        "email": email,
+        "password": "S3cure-Passw0rd!",
+        "requires2FA": false
+    })).await;
+    app.assign_role(email, "admin").await;
+    login_to(app, tenant, email).await;
+}
+
+async fn login_to(app: &TestApp, tenant: &str, email: &str) -> reqwest::Response {
+    app.post_login(&serde_json::json!({
+        "tenant": tenant,
+        "email": email,
+        "password": "S3cure-Passw0rd!",
+    })).await
+}
+
+fn accept_body(token: &str, password: &str) -> serde_json::Value {
+    serde_json::json!({ "token": token, "password": password })
+}
+
+#[tokio::test]
+async fn should_create_account_from_invitation() {
+    let mut app = TestApp::new().await;
+
+    signup_admin_and_login(&app, &get_random_email()).await;
+    let email = get_random_email();
+
+    let response = app.post_admin_invitation(&serde_json::json!({ "email": email, "roles": ["admin"] })).await;
+    assert_eq!(response.status().as_u16(), 201);
+    let invitation = response.json::<InvitationResponse>().await.unwrap();
+    assert_eq!(invitation.email, email);
+    assert_eq!(invitation.roles, vec!["admin"]);
+    assert_eq!(invitation.status, InvitationStatus::Pending);
+
+    let token = app.get_invitation_token(&email);
+
+    let response = app.post_accept_invite(&accept_body(&token, "weak")).await;
+    assert_eq!(response.status().as_u16(), 400);
+
+    let response = app.post_accept_invite(&accept_body(&token, "S3cure-Passw0rd!")).await;
+    assert_eq!(response.status().as_u16(), 201);
+    let accepted = response.json::<AcceptInvitationResponse>().await.unwrap();
+    assert_eq!(accepted.tenant, "default");
+
+    // Links are single-use
+    let response = app.post_accept_invite(&accept_body(&token, "S3cure-Passw0rd!")).await;
+    assert_eq!(response.status().as_u16(), 400);
+
+    let response = login_to(&app, "default", &email).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let user_id = app.get_user_id(&email).await;
+    let user_roles = app.get_admin_user_roles(&user_id).await.json::<UserRoles>().await.unwrap();
+    assert_eq!(user_roles.roles, vec!["admin"]);
+
+    let invitations = app.get_admin_invitations().await.json::<ListInvitationsResponse>().await.unwrap();
+    assert_eq!(invitations.invitations.len(), 1);
+    assert_eq!(invitations.invitations[0].status, InvitationStatus::Accepted);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_move_existing_account_to_the_organization() {
+    let mut app = TestApp::new().await;
+
+    app.add_tenant("acme", None).await;
+    let email = get_random_email();
+    signup_admin_and_login(&app, &email).await;
+    let old_token = login_to(&app, "default", &email).await
+        .cookies()
+        .find(|cookie| cookie.name() == JWT_COOKIE_NAME)
+        .unwrap()
+        .value()
+        .to_owned();
+
+    signup_tenant_admin_and_login(&app, "acme", &get_random_email()).await;
+    let response = app.post_admin_invitation(&serde_json::json!({ "email": email })).await;
+    assert_eq!(response.status().as_u16(), 201);
+    let token = app.get_invitation_token(&email);
+
+    // Moving an account takes its password
+    let response = app.post_accept_invite(&accept_body(&token, "Wr0ng-Passw0rd!")).await;
+    assert_eq!(response.status().as_u16(), 401);
+
+    let response = app.post_accept_invite(&accept_body(&token, "S3cure-Passw0rd!")).await;
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(response.json::<AcceptInvitationResponse>().await.unwrap().tenant, "acme");
+
+    // Its sessions end and the roles of the former organization are dropped
+    let response = app.post_verify_token(&serde_json::json!({ "token": old_token })).await;
+    assert_eq!(response.status().as_u16(), 401);
+
+    let response = login_to(&app, "default", &email).await;
+    assert_eq!(response.status().as_u16(), 401);
+    let response = login_to(&app, "acme", &email).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app.get_admin_users(&[]).await;
+    assert_eq!(response.status().as_u16(), 403);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_keep_invitation_usable_when_accepting_fails() {
+    let mut app = TestApp::new().await;
+
+    // An account scheduled for deletion is hidden, but still holds its email
+    let email = get_random_email();
+    signup_and_login(&app, &email).await;
+    let response = app.post_account_delete(&serde_json::json!({ "password": "S3cure-Passw0rd!" })).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    signup_admin_and_login(&app, &get_random_email()).await;
+    let response = app.post_admin_invitation(&serde_json::json!({ "email": email })).await;
+    assert_eq!(response.status().as_u16(), 201);
+    let token = app.get_invitation_token(&email);
+
+    let response = app.post_accept_invite(&accept_body(&token, "S3cure-Passw0rd!")).await;
+    assert_eq!(response.status().as_u16(), 409);
+
+    let invitations = app.get_admin_invitations().await.json::<ListInvitationsResponse>().await.unwrap();
+    assert_eq!(invitations.invitations[0].status, InvitationStatus::Pending);
+
+    let response = app.post_account_cancel_deletion(&serde_json::json!({ "email": email, "password": "S3cure-Passw0rd!" })).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app.post_accept_invite(&accept_body(&token, "S3cure-Passw0rd!")).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_revoke_pending_invitations() {
+    let mut app = TestApp::new().await;
+
+    signup_admin_and_login(&app, &get_random_email()).await;
+    let email = get_random_email();
+
+    let first = app.post_admin_invitation(&serde_json::json!({ "email": email })).await
+        .json::<InvitationResponse>().await.unwrap();
+    let first_token = app.get_invitation_token(&email);
+
+    // A new invitation for the address replaces the pending one
+    let second = app.post_admin_invitation(&serde_json::json!({ "email": email })).await
+        .json::<InvitationResponse>().await.unwrap();
+    let response = app.post_accept_invite(&accept_body(&first_token, "S3cure-Passw0rd!")).await;
+    assert_eq!(response.status().as_u16(), 400);
+
+    let response = app.delete_admin_invitation(&second.id).await;
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(response.json::<InvitationResponse>().await.unwrap().status, InvitationStatus::Revoked);
+
+    let response = app.delete_admin_invitation(&second.id).await;
+    assert_eq!(response.status().as_u16(), 404);
+    let response = app.delete_admin_invitation(&first.id).await;
+    assert_eq!(response.status().as_u16(), 404);
+
+    let token = app.get_invitation_token(&email);
+    let response = app.post_accept_invite(&accept_body(&token, "S3cure-Passw0rd!")).await;
+    assert_eq!(response.status().as_u16(), 400);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_not_accept_expired_invitations() {
+    let mut app = TestApp::new().await;
+
+    signup_admin_and_login(&app, &get_random_email()).await;
+    let email = get_random_email();
+    let invitation = app.post_admin_invitation(&serde_json::json!({ "email": email })).await
+        .json::<InvitationResponse>().await.unwrap();
+    let token = app.get_invitation_token(&email);
+
+    app.expire_invitations().await;
+
+    let response = app.post_accept_invite(&accept_body(&token, "S3cure-Passw0rd!")).await;
+    assert_eq!(response.status().as_u16(), 400);
+
+    let response = app.delete_admin_invitation(&invitation.id).await;
+    assert_eq!(response.status().as_u16(), 404);
+
+    let invitations = app.get_admin_invitations().await.json::<ListInvitationsResponse>().await.unwrap();
+    assert_eq!(invitations.invitations[0].status, InvitationStatus::Expired);
+
+    let response = app.post_accept_invite(&accept_body(&uuid::Uuid::new_v4().to_string(), "S3cure-Passw0rd!")).await;
+    assert_eq!(response.status().as_u16(), 400);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_validate_invitations() {
+    let mut app = TestApp::new().await;
+
+    let member_email = get_random_email();
+    signup_and_login(&app, &member_email).await;
+
+    let response = app.post_admin_invitation(&serde_json::json!({ "email": get_random_email() })).await;
+    assert_eq!(response.status().as_u16(), 403);
+
+    signup_admin_and_login(&app, &get_random_email()).await;
+
+    let response = app.post_admin_invitation(&serde_json::json!({ "email": member_email })).await;
+    assert_eq!(response.status().as_u16(), 409);
+
+    let response = app.post_admin_invitation(&serde_json::json!({ "email": "not an email" })).await;
+    assert_eq!(response.status().as_u16(), 400);
+
+    let response = app.post_admin_invitation(&serde_json::json!({ "email": get_random_email(), "roles": ["unknown"] })).await;
+    assert_eq!(response.status().as_u16(), 404);
+
+    app.update_tenant_settings(&TenantId::default(), serde_json::json!({ "allowedEmailDomains": ["acme.test"] })).await;
+    let response = app.post_admin_invitation(&serde_json::json!({ "email": "alice@example.com" })).await;
+    assert_eq!(response.status().as_u16(), 400);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_scope_invitations_to_the_organization() {
+    let mut app = TestApp::new().await;
+
+    app.add_tenant("acme", None).await;
+    signup_tenant_admin_and_login(&app, "acme", &get_random_email()).await;
+    let invitation = app.post_admin_invitation(&serde_json::json!({ "email": get_random_email() })).await
+        .json::<InvitationResponse>().await.unwrap();
+
+    signup_admin_and_login(&app, &get_random_email()).await;
+
+    let invitations = app.get_admin_invitations().await.json::<ListInvitationsResponse>().await.unwrap();
+    assert!(invitations.invitations.is_empty());
+
+    let response = app.delete_admin_invitation(&invitation.id).await;
+    assert_eq!(response.status().as_u16(), 404);
+
+    app.clean_up().await;
+}
@@ -12,7 +12,7 @@ async fn should_return_422_if_malformed_credentials() {
 
     let test_cases = [
         serde_json::json!({
-            "password": "password123",            
+            "password": "S3cure-Passw0rd!",            
         }),
         serde_json::json!({
             "email": random_email,                    
@@ -44,7 +44,7 @@ async fn should_return_400_if_invalid_input() {
 
     let data_signup = serde_json::json!({
         "email": random_email,
-        "password": "12345678",
+        "password": "S3cure-Passw0rd!",
         "requires2FA": true         
     });
 
@@ -57,7 +57,7 @@ async fn should_return_400_if_invalid_input() {
         }),
         serde_json::json!({
             "email": "wrong_mail.com",
-            "password": "12345678"               
+            "password": "S3cure-Passw0rd!"               
         }),
     ];
     
@@ -84,7 +84,7 @@ async fn should_return_401_if_incorrect_credentials() {
 
     let data_signup = serde_json::json!({
         "email": random_email,
-        "password": "12345678",
+        "password": "S3cure-Passw0rd!",
         "requires2FA": true         
     });
 
@@ -97,7 +97,7 @@ async fn should_return_401_if_incorrect_credentials() {
         }),
         serde_json::json!({
             "email": "anoter@mail.com",
-            "password": "12345678"
+            "password": "S3cure-Passw0rd!"
         }),
     ];
     
@@ -123,7 +123,7 @@ async fn should_return_200_if_valid_credentials_and_2fa_disabled() {
 
     let signup_body = serde_json::json!({
         "email": random_email,
-        "password": "password123",
+        "password": "S3cure-Passw0rd!",
         "requires2FA": false
     });
 
@@ -133,7 +133,7 @@ async fn should_return_200_if_valid_credentials_and_2fa_disabled() {
 
     let login_body = serde_json::json!({
         "email": random_email,
-        "password": "password123",
+        "password": "S3cure-Passw0rd!",
     });
 
     let response = app.post_login(&login_body).await;
@@ -150,6 +150,37 @@ async fn should_return_200_if_valid_credentials_and_2fa_disabled() {
     app.clean_up().await;
 }
 
+#[tokio::test]
+async fn should_upgrade_imported_legacy_hash_on_login() {
+    let mut app = TestApp::new().await;
+
+    let random_email = get_random_email();
+    let legacy_hash = bcrypt::hash("S3cure-Passw0rd!", 4).expect("Failed to hash password");
+    app.import_user(&random_email, &legacy_hash).await;
+
+    let login_body = serde_json::json!({
+        "email": random_email,
+        "password": "wrong-Passw0rd!",
+    });
+    let response = app.post_login(&login_body).await;
+    assert_eq!(response.status().as_u16(), 401);
+    assert_eq!(app.get_password_hash(&random_email).await, legacy_hash);
+
+    let login_body = serde_json::json!({
+        "email": random_email,
+        "password": "S3cure-Passw0rd!",
+    });
+    let response = app.post_login(&login_body).await;
+    assert_eq!(response.status().as_u16(), 200);
+    assert!(app.get_password_hash(&random_email).await.starts_with("$argon2id$"));
+
+    // The upgraded hash keeps working
+    let response = app.post_login(&login_body).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    app.clean_up().await;
+}
+
 #[tokio::test]
 async fn should_return_206_if_valid_credentials_and_2fa_enabled() {
     let mut app = TestApp::new().await;
@@ -158,7 +189,7 @@ async fn should_return_206_if_valid_credentials_and_2fa_enabled() {
 
     let signup_body = serde_json::json!({
         "email": &random_email,
-        "password": "password123",
+        "password": "S3cure-Passw0rd!",
         "requires2FA": true
     });
 
@@ -166,12 +197,14 @@ async fn should_return_206_if_valid_credentials_and_2fa_enabled() {
 
     let login_body = serde_json::json!({
         "email": &random_email,
-        "password": "password123",
+        "password": "S3cure-Passw0rd!",
     });
 
     let login_response = app.post_login(&login_body).await;
 
     assert_eq!(login_response.status().as_u16(), 206);
+    // No token until the code is verified
+    assert!(login_response.cookies().all(|cookie| cookie.name() != JWT_COOKIE_NAME));
 
     let json_body = login_response
         .json::<TwoFactorAuthResponse>()
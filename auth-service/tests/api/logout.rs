@@ -11,7 +11,7 @@ async fn should_return_200_if_valid_jwt_cookie() {
 
     let signup_body = serde_json::json!({
         "email": random_email,
-        "password": "password123",
+        "password": "S3cure-Passw0rd!",
         "requires2FA": false
     });
 
@@ -19,7 +19,7 @@ async fn should_return_200_if_valid_jwt_cookie() {
 
     let login_body = serde_json::json!({
         "email": random_email,
-        "password": "password123",
+        "password": "S3cure-Passw0rd!",
     });
 
     let login_response = app.post_login(&login_body).await;
@@ -53,7 +53,7 @@ async fn should_return_400_if_logout_called_twice_in_a_row() {
 
     let signup_body = serde_json::json!({
         "email": random_email,
-        "password": "password123",
+        "password": "S3cure-Passw0rd!",
         "requires2FA": false
     });
 
@@ -61,7 +61,7 @@ async fn should_return_400_if_logout_called_twice_in_a_row() {
 
     let login_body = serde_json::json!({
         "email": random_email,
-        "password": "password123",
+        "password": "S3cure-Passw0rd!",
     });
 
     app.post_login(&login_body).await;
@@ -1,7 +1,21 @@
+mod account;
+mod admin_console;
+mod admin_roles;
+mod admin_users;
+mod change_email;
+mod change_password;
 mod helpers;
+mod impersonation;
+mod invitations;
 mod login;
 mod logout;
+mod metrics;
+mod personal_access_tokens;
 mod root;
 mod signup;
+mod signup_modes;
+mod tenant;
+mod toggle_2fa;
+mod token_exchange;
 mod verify_2fa;
 mod verify_token;
\ No newline at end of file
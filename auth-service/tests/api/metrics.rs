@@ -0,0 +1,85 @@
+use auth_service::routes::MetricsResponse;
+
+use crate::helpers::{get_random_email, signup_admin_and_login, signup_and_login, TestApp};
+
+#[tokio::test]
+async fn should_report_password_hashing_metrics() {
+    let mut app = TestApp::new().await;
+
+    signup_admin_and_login(&app, &get_random_email()).await;
+
+    let response = app.get_metrics().await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let metrics = response
+        .json::<MetricsResponse>()
+        .await
+        .expect("Could not deserialize response body to MetricsResponse")
+        .hashing;
+
+    // One hash for the signup and one verification for each of the two logins
+    assert_eq!(metrics.completed, 3);
+    assert_eq!((metrics.queued, metrics.running, metrics.rejected), (0, 0, 0));
+    assert!(metrics.average_latency_ms > 0.0);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_400_if_jwt_cookie_missing() {
+    let mut app = TestApp::new().await;
+
+    let response = app.get_metrics().await;
+    assert_eq!(response.status().as_u16(), 400);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_403_without_metrics_permission() {
+    let mut app = TestApp::new().await;
+
+    signup_and_login(&app, &get_random_email()).await;
+
+    let response = app.get_metrics().await;
+    assert_eq!(response.status().as_u16(), 403);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_503_when_hashing_pool_is_saturated() {
+    // A single hash at a time and no queue, so concurrent logins have to be turned away
+    let mut app = TestApp::with_hashing_pool(1, 0).await;
+
+    let random_email = get_random_email();
+    let signup_body = serde_json::json!({
+        "email": random_email,
+        "password": "S3cure-Passw0rd!",
+        "requires2FA": false
+    });
+    let response = app.post_signup(&signup_body).await;
+    assert_eq!(response.status().as_u16(), 201);
+
+    let login_body = serde_json::json!({
+        "email": random_email,
+        "password": "S3cure-Passw0rd!",
+    });
+    let responses = tokio::join!(
+        app.post_login(&login_body),
+        app.post_login(&login_body),
+        app.post_login(&login_body),
+        app.post_login(&login_body),
+    );
+    let responses = [responses.0, responses.1, responses.2, responses.3];
+
+    let statuses: Vec<u16> = responses.iter().map(|response| response.status().as_u16()).collect();
+    assert!(statuses.iter().all(|status| *status == 200 || *status == 503), "Unexpected statuses {:?}", statuses);
+    assert!(statuses.contains(&200));
+    assert!(statuses.contains(&503));
+
+    let rejected = responses.iter().find(|response| response.status().as_u16() == 503).unwrap();
+    assert_eq!(rejected.headers().get("retry-after").unwrap(), "1");
+
+    app.clean_up().await;
+}
@@ -0,0 +1,182 @@
+use auth_service::{
+    domain::PersonalAccessTokenStatus,
+    routes::{
+        CreatePersonalAccessTokenResponse, ListPersonalAccessTokensResponse, PersonalAccessTokenResponse,
+        VerifyTokenResponse,
+    },
+};
+
+use crate::helpers::{get_random_email, signup_admin_and_login, signup_and_login, TestApp};
+
+#[tokio::test]
+async fn should_create_token_shown_only_once() {
+    let mut app = TestApp::new().await;
+
+    signup_admin_and_login(&app, &get_random_email()).await;
+
+    let response = app.post_account_tokens(&serde_json::json!({
+        "name": "deploy script",
+        "scopes": ["users:manage"],
+        "expiresInDays": 7,
+    })).await;
+    assert_eq!(response.status().as_u16(), 201);
+    let created = response.json::<CreatePersonalAccessTokenResponse>().await.unwrap();
+    assert!(created.token.starts_with(&created.personal_access_token.prefix));
+    assert_eq!(created.personal_access_token.name, "deploy script");
+    assert_eq!(created.personal_access_token.scopes, vec!["users:manage"]);
+    assert!(created.personal_access_token.last_used_at.is_none());
+
+    // Only the prefix can be listed afterwards
+    let response = app.get_account_tokens().await;
+    assert_eq!(response.status().as_u16(), 200);
+    let body = response.text().await.unwrap();
+    assert!(!body.contains(&created.token));
+    let listed = serde_json::from_str::<ListPersonalAccessTokensResponse>(&body).unwrap();
+    assert_eq!(listed.tokens.len(), 1);
+    assert_eq!(listed.tokens[0].id, created.personal_access_token.id);
+    assert_eq!(listed.tokens[0].prefix, created.personal_access_token.prefix);
+    assert_eq!(listed.tokens[0].status, PersonalAccessTokenStatus::Active);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_accept_token_as_bearer_with_its_scopes_only() {
+    let mut app = TestApp::new().await;
+
+    signup_admin_and_login(&app, &get_random_email()).await;
+
+    let response = app.post_account_tokens(&serde_json::json!({ "name": "ci", "scopes": ["users:manage"] })).await;
+    let token = response.json::<CreatePersonalAccessTokenResponse>().await.unwrap().token;
+
+    // By services verifying it, in the body or as a bearer token
+    let response = app.post_verify_token(&serde_json::json!({ "token": token })).await;
+    assert_eq!(response.status().as_u16(), 200);
+    let verified = response.json::<VerifyTokenResponse>().await.unwrap();
+    assert!(verified.roles.is_empty());
+    assert_eq!(verified.permissions, vec!["users:manage"]);
+    assert!(verified.act.is_none());
+
+    let response = app.post_with_bearer_token("/verify-token", &token, &serde_json::json!({})).await;
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(response.json::<VerifyTokenResponse>().await.unwrap().permissions, vec!["users:manage"]);
+
+    // And by the routes requiring a permission, within its scopes
+    assert_eq!(app.get_with_bearer_token("/admin/users", &token).await.status().as_u16(), 200);
+    assert_eq!(app.get_with_bearer_token("/admin/tenant", &token).await.status().as_u16(), 403);
+
+    // But not by the account routes, whatever its scopes
+    assert_eq!(app.get_with_bearer_token("/account/export", &token).await.status().as_u16(), 403);
+
+    let response = app.get_account_tokens().await;
+    let listed = response.json::<ListPersonalAccessTokensResponse>().await.unwrap();
+    assert!(listed.tokens[0].last_used_at.is_some());
+
+    // A token cannot be used to create others
+    let response = app.post_with_bearer_token("/account/tokens", &token, &serde_json::json!({ "name": "another" })).await;
+    assert_eq!(response.status().as_u16(), 403);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_reject_revoked_expired_and_unknown_tokens() {
+    let mut app = TestApp::new().await;
+
+    signup_and_login(&app, &get_random_email()).await;
+
+    let response = app.post_account_tokens(&serde_json::json!({ "name": "revoked" })).await;
+    let revoked = response.json::<CreatePersonalAccessTokenResponse>().await.unwrap();
+    let response = app.post_account_tokens(&serde_json::json!({ "name": "expired" })).await;
+    let expired = response.json::<CreatePersonalAccessTokenResponse>().await.unwrap();
+
+    let response = app.delete_account_token(&revoked.personal_access_token.id).await;
+    assert_eq!(response.status().as_u16(), 200);
+    let body = response.json::<PersonalAccessTokenResponse>().await.unwrap();
+    assert_eq!(body.status, PersonalAccessTokenStatus::Revoked);
+    assert_eq!(app.delete_account_token(&revoked.personal_access_token.id).await.status().as_u16(), 404);
+    assert_eq!(app.delete_account_token("not-an-id").await.status().as_u16(), 404);
+
+    app.expire_personal_access_tokens().await;
+
+    let unknown = format!("{}x", expired.token);
+    for token in [&revoked.token, &expired.token, &unknown] {
+        let response = app.post_verify_token(&serde_json::json!({ "token": token })).await;
+        assert_eq!(response.status().as_u16(), 401);
+        let response = app.get_with_bearer_token("/account/export", token).await;
+        assert_eq!(response.status().as_u16(), 401);
+    }
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_revoke_tokens_with_the_sessions() {
+    let mut app = TestApp::new().await;
+
+    signup_and_login(&app, &get_random_email()).await;
+    let response = app.post_account_tokens(&serde_json::json!({ "name": "ci" })).await;
+    let created = response.json::<CreatePersonalAccessTokenResponse>().await.unwrap();
+
+    let response = app.post_change_password(&serde_json::json!({
+        "currentPassword": "S3cure-Passw0rd!",
+        "newPassword": "N3w-Passw0rd-456",
+    })).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app.post_verify_token(&serde_json::json!({ "token": created.token })).await;
+    assert_eq!(response.status().as_u16(), 401);
+    let response = app.get_account_tokens().await;
+    let listed = response.json::<ListPersonalAccessTokensResponse>().await.unwrap();
+    assert_eq!(listed.tokens[0].status, PersonalAccessTokenStatus::Revoked);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_only_revoke_own_tokens() {
+    let mut app = TestApp::new().await;
+
+    signup_and_login(&app, &get_random_email()).await;
+    let response = app.post_account_tokens(&serde_json::json!({ "name": "mine" })).await;
+    let created = response.json::<CreatePersonalAccessTokenResponse>().await.unwrap();
+
+    // Another user logs in on the same client
+    signup_and_login(&app, &get_random_email()).await;
+    let response = app.delete_account_token(&created.personal_access_token.id).await;
+    assert_eq!(response.status().as_u16(), 404);
+    let response = app.get_account_tokens().await;
+    assert!(response.json::<ListPersonalAccessTokensResponse>().await.unwrap().tokens.is_empty());
+
+    let response = app.post_verify_token(&serde_json::json!({ "token": created.token })).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_refuse_invalid_tokens_and_missing_permissions() {
+    let mut app = TestApp::new().await;
+
+    assert_eq!(app.post_account_tokens(&serde_json::json!({ "name": "ci" })).await.status().as_u16(), 400);
+    assert_eq!(app.get_account_tokens().await.status().as_u16(), 400);
+
+    signup_and_login(&app, &get_random_email()).await;
+
+    let test_cases = [
+        serde_json::json!({ "name": "" }),
+        serde_json::json!({ "name": "x".repeat(101) }),
+        serde_json::json!({ "name": "ci", "expiresInDays": 0 }),
+        serde_json::json!({ "name": "ci", "expiresInDays": 366 }),
+    ];
+    for test_case in test_cases {
+        let response = app.post_account_tokens(&test_case).await;
+        assert_eq!(response.status().as_u16(), 400, "Failed for input: {:?}", test_case);
+    }
+
+    // Only permissions of the user can be granted
+    let response = app.post_account_tokens(&serde_json::json!({ "name": "ci", "scopes": ["users:manage"] })).await;
+    assert_eq!(response.status().as_u16(), 403);
+
+    app.clean_up().await;
+}
@@ -12,12 +12,12 @@ async fn should_return_422_if_malformed_input() {
 
     let test_cases = [
         serde_json::json!({
-            "password": "password123",
+            "password": "S3cure-Passw0rd!",
             "requires2FA": true
         }),
         serde_json::json!({
             "email": random_email,
-            "password": "password123"         
+            "password": "S3cure-Passw0rd!"         
         }),
         serde_json::json!({
             "123": "324"         
@@ -45,7 +45,7 @@ async fn should_return_201_if_valid_input() {
 
     let data = serde_json::json!({
         "email": random_email,
-        "password": "12345678",
+        "password": "S3cure-Passw0rd!",
         "requires2FA": true         
     });
 
@@ -77,18 +77,18 @@ async fn should_return_400_if_invalid_input() {
     let test_cases = [       
         serde_json::json!({
             "email": "",
-            "password": "password123",
+            "password": "S3cure-Passw0rd!",
             "requires2FA": true
         }),
         serde_json::json!({
             "email": "invalidmail.com",
-            "password": "password123",
+            "password": "S3cure-Passw0rd!",
             "requires2FA": true         
         }),
         serde_json::json!({
             "email": random_email,
-            "password": "1234567",
-            "requires2FA": true         
+            "password": "",
+            "requires2FA": true
         })
     ];
 
@@ -123,7 +123,7 @@ async fn should_return_409_if_email_already_exists() {
     let random_email = get_random_email();
     let payload = serde_json::json!({
         "email": random_email,
-        "password": "password123",
+        "password": "S3cure-Passw0rd!",
         "requires2FA": true
     });
 
@@ -143,4 +143,129 @@ async fn should_return_409_if_email_already_exists() {
     );
 
     app.clean_up().await;
-}
\ No newline at end of file
+}
+
+#[tokio::test]
+async fn should_not_reveal_existing_email_in_enumeration_safe_mode() {
+    let mut app = TestApp::with_enumeration_safe_signup().await;
+
+    let random_email = get_random_email();
+    let payload = serde_json::json!({
+        "email": random_email,
+        "password": "S3cure-Passw0rd!",
+        "requires2FA": false
+    });
+
+    let response = app.post_signup(&payload).await;
+    assert_eq!(response.status().as_u16(), 201);
+    let first_body = response.json::<SignupResponse>().await.expect("Could not deserialize response body to SignupResponse");
+
+    let response = app.post_signup(&payload).await;
+    assert_eq!(response.status().as_u16(), 201);
+    let second_body = response.json::<SignupResponse>().await.expect("Could not deserialize response body to SignupResponse");
+
+    assert_eq!(first_body, second_body);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_409_if_email_collides_after_normalization() {
+    let mut app = TestApp::new().await;
+
+    let random_email = get_random_email();
+
+    let response = app.post_signup(&serde_json::json!({
+        "email": random_email,
+        "password": "S3cure-Passw0rd!",
+        "requires2FA": false
+    })).await;
+    assert_eq!(response.status().as_u16(), 201);
+
+    let response = app.post_signup(&serde_json::json!({
+        "email": random_email.to_uppercase(),
+        "password": "S3cure-Passw0rd!",
+        "requires2FA": false
+    })).await;
+    assert_eq!(response.status().as_u16(), 409);
+
+    // The same account can be logged into with any casing of the address
+    let response = app.post_login(&serde_json::json!({
+        "email": random_email.to_uppercase(),
+        "password": "S3cure-Passw0rd!",
+    })).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_400_with_failed_rules_if_weak_password() {
+    let mut app = TestApp::new().await;
+
+    let response = app.post_signup(&serde_json::json!({
+        "email": "jane.doe@example.com",
+        "password": "jane.doe12345678",
+        "requires2FA": false
+    })).await;
+    assert_eq!(response.status().as_u16(), 400);
+
+    let error_response = response
+        .json::<ErrorResponse>()
+        .await
+        .expect("Could not deserialize response body to ErrorResponse");
+
+    assert_eq!(error_response.error, "Password does not meet the requirements".to_owned());
+
+    let rules: Vec<String> = error_response.details
+        .expect("Response should list the failed rules")
+        .into_iter()
+        .map(|detail| detail.rule)
+        .collect();
+    assert!(rules.contains(&"contains_email".to_owned()));
+    assert!(rules.contains(&"too_weak".to_owned()));
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_400_with_failed_rules_if_short_password() {
+    let mut app = TestApp::new().await;
+
+    let response = app.post_signup(&serde_json::json!({
+        "email": get_random_email(),
+        "password": "Zq8#vL2",
+        "requires2FA": false
+    })).await;
+    assert_eq!(response.status().as_u16(), 400);
+
+    let rules: Vec<String> = response
+        .json::<ErrorResponse>()
+        .await
+        .expect("Could not deserialize response body to ErrorResponse")
+        .details
+        .expect("Response should list the failed rules")
+        .into_iter()
+        .map(|detail| detail.rule)
+        .collect();
+    assert!(rules.contains(&"too_short".to_owned()));
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_400_if_email_domain_is_disposable() {
+    let mut app = TestApp::new().await;
+
+    for email in ["someone@mailinator.com", "someone@private.mailinator.com", "someone@YOPMAIL.com"] {
+        let response = app.post_signup(&serde_json::json!({
+            "email": email,
+            "password": "S3cure-Passw0rd!",
+            "requires2FA": false
+        })).await;
+        assert_eq!(response.status().as_u16(), 400, "Failed for email: {}", email);
+        assert_eq!(response.json::<ErrorResponse>().await.unwrap().error, "Email domain is not accepted");
+    }
+
+    app.clean_up().await;
+}
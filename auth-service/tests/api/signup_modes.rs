@@ -0,0 +1,168 @@
+use auth_service::{
+    domain::TenantId,
+    routes::{AdminUserResponse, ListUsersResponse, SignupResponse},
+    ErrorResponse,
+};
+
+use crate::helpers::{get_random_email, signup_admin_and_login, TestApp};
+
+fn signup_body(email: &str) -> serde_json::Value {
+    serde_json::json!({
+        "email": email,
+        "password": "S3cure-Passw0rd!",
+        "requires2FA": false
+    })
+}
+
+fn login_body(email: &str) -> serde_json::Value {
+    serde_json::json!({
+        "email": email,
+        "password": "S3cure-Passw0rd!",
+    })
+}
+
+#[tokio::test]
+async fn should_refuse_signup_when_disabled_or_invite_only() {
+    let mut app = TestApp::new().await;
+
+    app.update_tenant_settings(&TenantId::default(), serde_json::json!({ "signupMode": "disabled" })).await;
+    let response = app.post_signup(&signup_body(&get_random_email())).await;
+    assert_eq!(response.status().as_u16(), 403);
+    assert_eq!(response.json::<ErrorResponse>().await.unwrap().error, "Signup is disabled for the organization");
+
+    app.update_tenant_settings(&TenantId::default(), serde_json::json!({ "signupMode": "invite_only" })).await;
+    let response = app.post_signup(&signup_body(&get_random_email())).await;
+    assert_eq!(response.status().as_u16(), 403);
+    assert_eq!(response.json::<ErrorResponse>().await.unwrap().error, "Signup to the organization takes an invitation");
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_only_let_signup_domains_sign_up_when_domain_restricted() {
+    let mut app = TestApp::new().await;
+
+    app.update_tenant_settings(&TenantId::default(), serde_json::json!({
+        "signupMode": "domain_restricted",
+        "signupEmailDomains": ["acme.test"]
+    })).await;
+
+    let response = app.post_signup(&signup_body("alice@example.com")).await;
+    assert_eq!(response.status().as_u16(), 400);
+
+    let email = format!("{}@acme.test", uuid::Uuid::new_v4());
+    let response = app.post_signup(&signup_body(&email)).await;
+    assert_eq!(response.status().as_u16(), 201);
+
+    let response = app.post_login(&login_body(&email)).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_keep_pending_accounts_from_logging_in_until_approved() {
+    let mut app = TestApp::new().await;
+
+    signup_admin_and_login(&app, &get_random_email()).await;
+    app.update_tenant_settings(&TenantId::default(), serde_json::json!({ "signupMode": "pending_approval" })).await;
+
+    let email = get_random_email();
+    let response = app.post_signup(&signup_body(&email)).await;
+    assert_eq!(response.status().as_u16(), 201);
+    assert!(response.json::<SignupResponse>().await.unwrap().message.contains("approve"));
+
+    // Only someone with the password learns the account is pending
+    let response = app.post_login(&serde_json::json!({ "email": email, "password": "Wr0ng-Passw0rd!" })).await;
+    assert_eq!(response.status().as_u16(), 401);
+    let response = app.post_login(&login_body(&email)).await;
+    assert_eq!(response.status().as_u16(), 403);
+    assert_eq!(response.json::<ErrorResponse>().await.unwrap().error, "Account is pending approval");
+
+    let users = app.get_admin_users(&[("pending", "true")]).await.json::<ListUsersResponse>().await.unwrap();
+    assert_eq!(users.total, 1);
+    assert_eq!(users.users[0].email, email);
+    assert!(users.users[0].approval_pending);
+
+    let response = app.post_admin_user_action(&users.users[0].id, "approve").await;
+    assert_eq!(response.status().as_u16(), 200);
+    assert!(!response.json::<AdminUserResponse>().await.unwrap().approval_pending);
+
+    // Approved accounts are no longer pending
+    let response = app.post_admin_user_action(&users.users[0].id, "approve").await;
+    assert_eq!(response.status().as_u16(), 404);
+    let response = app.post_admin_user_action(&users.users[0].id, "reject").await;
+    assert_eq!(response.status().as_u16(), 404);
+
+    let response = app.post_login(&login_body(&email)).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_delete_rejected_accounts() {
+    let mut app = TestApp::new().await;
+
+    signup_admin_and_login(&app, &get_random_email()).await;
+    app.update_tenant_settings(&TenantId::default(), serde_json::json!({ "signupMode": "pending_approval" })).await;
+
+    let email = get_random_email();
+    app.post_signup(&signup_body(&email)).await;
+    let user_id = app.get_user_id(&email).await;
+
+    let response = app.post_admin_user_action(&user_id, "reject").await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app.get_admin_user(&user_id).await;
+    assert_eq!(response.status().as_u16(), 404);
+    let response = app.post_login(&login_body(&email)).await;
+    assert_eq!(response.status().as_u16(), 401);
+
+    // The address can sign up again
+    let response = app.post_signup(&signup_body(&email)).await;
+    assert_eq!(response.status().as_u16(), 201);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_approve_and_reject_when_the_notification_fails() {
+    let mut app = TestApp::new().await;
+
+    signup_admin_and_login(&app, &get_random_email()).await;
+    app.update_tenant_settings(&TenantId::default(), serde_json::json!({ "signupMode": "pending_approval" })).await;
+
+    let (approved, rejected) = (get_random_email(), get_random_email());
+    app.post_signup(&signup_body(&approved)).await;
+    app.post_signup(&signup_body(&rejected)).await;
+    app.email_client.fail_sending();
+
+    let response = app.post_admin_user_action(&app.get_user_id(&approved).await, "approve").await;
+    assert_eq!(response.status().as_u16(), 200);
+    let response = app.post_admin_user_action(&app.get_user_id(&rejected).await, "reject").await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app.post_login(&login_body(&approved)).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_reject_invalid_signup_settings() {
+    let mut app = TestApp::new().await;
+
+    signup_admin_and_login(&app, &get_random_email()).await;
+
+    let response = app.put_admin_tenant_settings(&serde_json::json!({ "signupMode": "domain_restricted" })).await;
+    assert_eq!(response.status().as_u16(), 400);
+
+    let response = app.put_admin_tenant_settings(&serde_json::json!({ "signupMode": "closed" })).await;
+    assert_eq!(response.status().as_u16(), 422);
+
+    let response = app.put_admin_tenant_settings(&serde_json::json!({ "signupMode": "invite_only" })).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    app.clean_up().await;
+}
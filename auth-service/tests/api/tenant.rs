@@ -0,0 +1,230 @@
+use auth_service::routes::{ListUsersResponse, SignupResponse, TenantResponse, VerifyTokenResponse};
+use auth_service::utils::JWT_COOKIE_NAME;
+
+use crate::helpers::{get_random_email, signup_admin_and_login, signup_and_login, TestApp};
+
+fn signup_body(tenant: &str, email: &str) -> serde_json::Value {
+    serde_json::json!({
+        "tenant": tenant,
+        "email": email,
+        "password": "S3cure-Passw0rd!",
+        "requires2FA": false
+    })
+}
+
+fn login_body(tenant: &str, email: &str) -> serde_json::Value {
+    serde_json::json!({
+        "tenant": tenant,
+        "email": email,
+        "password": "S3cure-Passw0rd!",
+    })
+}
+
+#[tokio::test]
+async fn should_login_to_the_organization_of_the_slug() {
+    let mut app = TestApp::new().await;
+
+    let tenant_id = app.add_tenant("acme", None).await;
+    let email = get_random_email();
+
+    let response = app.post_signup(&signup_body("Acme", &email)).await;
+    assert_eq!(response.status().as_u16(), 201);
+
+    // Members of an organization are unknown to the others
+    let response = app.post_login(&login_body("", &email)).await;
+    assert_eq!(response.status().as_u16(), 401);
+    let response = app.post_login(&login_body("default", &email)).await;
+    assert_eq!(response.status().as_u16(), 401);
+
+    let response = app.post_login(&login_body("acme", &email)).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let token = response.cookies().next().unwrap().value().to_string();
+    let response = app.post_verify_token(&serde_json::json!({ "token": token })).await;
+    let claims = response.json::<VerifyTokenResponse>().await.unwrap();
+    assert_eq!(claims.tenant, tenant_id.to_string());
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_not_reveal_members_of_another_organization_on_signup() {
+    let mut app = TestApp::new().await;
+
+    app.add_tenant("acme", None).await;
+    let email = get_random_email();
+
+    let response = app.post_signup(&signup_body("acme", &email)).await;
+    assert_eq!(response.status().as_u16(), 201);
+    let first_body = response.json::<SignupResponse>().await.unwrap();
+
+    // The email is taken, but the other organization gets the answer of a new account
+    let response = app.post_signup(&signup_body("default", &email)).await;
+    assert_eq!(response.status().as_u16(), 201);
+    assert_eq!(response.json::<SignupResponse>().await.unwrap(), first_body);
+
+    let response = app.post_login(&login_body("default", &email)).await;
+    assert_eq!(response.status().as_u16(), 401);
+    let response = app.post_login(&login_body("acme", &email)).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    // Within the organization the email is still reported as taken
+    let response = app.post_signup(&signup_body("acme", &email)).await;
+    assert_eq!(response.status().as_u16(), 409);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_404_for_unknown_organization() {
+    let mut app = TestApp::new().await;
+
+    let email = get_random_email();
+
+    for tenant in ["unknown", "not a slug"] {
+        let response = app.post_signup(&signup_body(tenant, &email)).await;
+        assert_eq!(response.status().as_u16(), 404);
+
+        let response = app.post_login(&login_body(tenant, &email)).await;
+        assert_eq!(response.status().as_u16(), 404);
+    }
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_derive_organization_from_host() {
+    let mut app = TestApp::new().await;
+
+    app.add_tenant("acme", Some("login.acme.test")).await;
+    let email = get_random_email();
+
+    let response = app.post_signup_with_host("login.acme.test:3000", &signup_body("", &email)).await;
+    assert_eq!(response.status().as_u16(), 201);
+
+    let response = app.post_login_with_host("LOGIN.acme.test", &login_body("", &email)).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    // A slug takes precedence over the host, unknown hosts fall back to the default organization
+    let response = app.post_login_with_host("login.acme.test", &login_body("default", &email)).await;
+    assert_eq!(response.status().as_u16(), 401);
+    let response = app.post_login_with_host("login.other.test", &login_body("", &email)).await;
+    assert_eq!(response.status().as_u16(), 401);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_only_allow_email_domains_of_the_organization() {
+    let mut app = TestApp::new().await;
+
+    let tenant_id = app.add_tenant("acme", None).await;
+    app.update_tenant_settings(&tenant_id, serde_json::json!({ "allowedEmailDomains": ["Acme.test"] })).await;
+
+    let response = app.post_signup(&signup_body("acme", "alice@example.com")).await;
+    assert_eq!(response.status().as_u16(), 400);
+
+    let response = app.post_signup(&signup_body("acme", "alice@acme.test")).await;
+    assert_eq!(response.status().as_u16(), 201);
+
+    // Other organizations are not restricted
+    let response = app.post_signup(&signup_body("default", "bob@example.com")).await;
+    assert_eq!(response.status().as_u16(), 201);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_require_2fa_when_the_organization_does() {
+    let mut app = TestApp::new().await;
+
+    let tenant_id = app.add_tenant("acme", None).await;
+    let email = get_random_email();
+    app.post_signup(&signup_body("acme", &email)).await;
+    let response = app.post_login(&login_body("acme", &email)).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    app.update_tenant_settings(&tenant_id, serde_json::json!({ "requires2FA": true })).await;
+
+    let response = app.post_disable_2fa(&serde_json::json!({ "password": "S3cure-Passw0rd!" })).await;
+    assert_eq!(response.status().as_u16(), 403);
+
+    let response = app.post_login(&login_body("acme", &email)).await;
+    assert_eq!(response.status().as_u16(), 206);
+    assert!(response.cookies().all(|cookie| cookie.name() != JWT_COOKIE_NAME));
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_apply_the_password_policy_of_the_organization() {
+    let mut app = TestApp::new().await;
+
+    let tenant_id = app.add_tenant("acme", None).await;
+    app.update_tenant_settings(&tenant_id, serde_json::json!({ "passwordPolicy": { "minLength": 20 } })).await;
+
+    let response = app.post_signup(&signup_body("acme", &get_random_email())).await;
+    assert_eq!(response.status().as_u16(), 400);
+
+    let response = app.post_signup(&signup_body("default", &get_random_email())).await;
+    assert_eq!(response.status().as_u16(), 201);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_scope_admin_access_to_the_organization() {
+    let mut app = TestApp::new().await;
+
+    app.add_tenant("acme", None).await;
+    let member_email = get_random_email();
+    app.post_signup(&signup_body("acme", &member_email)).await;
+    let member_id = app.get_user_id(&member_email).await;
+
+    let admin_email = get_random_email();
+    signup_admin_and_login(&app, &admin_email).await;
+
+    let response = app.get_admin_user(&member_id).await;
+    assert_eq!(response.status().as_u16(), 404);
+
+    let response = app.post_admin_user_action(&member_id, "lock").await;
+    assert_eq!(response.status().as_u16(), 404);
+
+    let users = app.get_admin_users(&[]).await.json::<ListUsersResponse>().await.unwrap();
+    assert_eq!(users.total, 1);
+    assert_eq!(users.users[0].email, admin_email);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_get_and_update_organization_settings() {
+    let mut app = TestApp::new().await;
+
+    signup_and_login(&app, &get_random_email()).await;
+    let response = app.get_admin_tenant().await;
+    assert_eq!(response.status().as_u16(), 403);
+
+    signup_admin_and_login(&app, &get_random_email()).await;
+
+    let tenant = app.get_admin_tenant().await.json::<TenantResponse>().await.unwrap();
+    assert_eq!(tenant.slug, "default");
+    assert!(!tenant.settings.requires_2fa);
+
+    let response = app.put_admin_tenant_settings(&serde_json::json!({ "allowedEmailDomains": [" "] })).await;
+    assert_eq!(response.status().as_u16(), 400);
+
+    let response = app.put_admin_tenant_settings(&serde_json::json!({
+        "requires2FA": true,
+        "allowedEmailDomains": ["Example.com", "example.com"],
+        "passwordPolicy": { "minLength": 14 }
+    })).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let tenant = app.get_admin_tenant().await.json::<TenantResponse>().await.unwrap();
+    assert!(tenant.settings.requires_2fa);
+    assert_eq!(tenant.settings.allowed_email_domains, vec!["example.com"]);
+    assert_eq!(tenant.settings.password_policy.min_length, Some(14));
+
+    app.clean_up().await;
+}
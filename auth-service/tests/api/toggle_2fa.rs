@@ -0,0 +1,147 @@
+use auth_service::domain::Email;
+use auth_service::routes::TwoFactorAuthResponse;
+
+use crate::helpers::{get_random_email, signup_and_login, TestApp};
+
+#[tokio::test]
+async fn should_return_400_if_jwt_cookie_missing() {
+    let mut app = TestApp::new().await;
+
+    let body = serde_json::json!({ "password": "S3cure-Passw0rd!" });
+
+    let response = app.post_enable_2fa(&body).await;
+    assert_eq!(response.status().as_u16(), 400);
+
+    let response = app.post_disable_2fa(&body).await;
+    assert_eq!(response.status().as_u16(), 400);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_return_401_if_incorrect_password() {
+    let mut app = TestApp::new().await;
+
+    signup_and_login(&app, &get_random_email()).await;
+
+    let body = serde_json::json!({ "password": "wrong_password" });
+
+    let response = app.post_enable_2fa(&body).await;
+    assert_eq!(response.status().as_u16(), 401);
+
+    let response = app.post_disable_2fa(&body).await;
+    assert_eq!(response.status().as_u16(), 401);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_enable_2fa() {
+    let mut app = TestApp::new().await;
+
+    let random_email = get_random_email();
+    signup_and_login(&app, &random_email).await;
+
+    let response = app.post_enable_2fa(&serde_json::json!({ "password": "S3cure-Passw0rd!" })).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app.post_login(&serde_json::json!({
+        "email": random_email,
+        "password": "S3cure-Passw0rd!",
+    })).await;
+    assert_eq!(response.status().as_u16(), 206);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_disable_2fa_with_valid_code() {
+    let mut app = TestApp::new().await;
+
+    let random_email = get_random_email();
+    signup_and_login(&app, &random_email).await;
+
+    let response = app.post_enable_2fa(&serde_json::json!({ "password": "S3cure-Passw0rd!" })).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app.post_disable_2fa(&serde_json::json!({ "password": "S3cure-Passw0rd!" })).await;
+    assert_eq!(response.status().as_u16(), 206);
+
+    let json_body = response
+        .json::<TwoFactorAuthResponse>()
+        .await
+        .expect("Could not deserialize response body to TwoFactorAuthResponse");
+
+    let two_fa_code;
+    {
+        let two_fa_code_store = app.app_state.disable_2fa_code_store.read().await;
+        let email = Email::parse(random_email.clone()).unwrap();
+        (_, two_fa_code) = two_fa_code_store.get_code(&email).await.unwrap();
+    }
+
+    let response = app.post_disable_2fa(&serde_json::json!({
+        "password": "S3cure-Passw0rd!",
+        "loginAttemptId": json_body.login_attempt_id,
+        "2FACode": "000000",
+    })).await;
+    assert_eq!(response.status().as_u16(), 401);
+
+    let response = app.post_disable_2fa(&serde_json::json!({
+        "password": "S3cure-Passw0rd!",
+        "loginAttemptId": json_body.login_attempt_id,
+        "2FACode": two_fa_code,
+    })).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app.post_login(&serde_json::json!({
+        "email": random_email,
+        "password": "S3cure-Passw0rd!",
+    })).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_keep_disable_code_apart_from_login_code() {
+    let mut app = TestApp::new().await;
+
+    let random_email = get_random_email();
+    let email = Email::parse(random_email.clone()).unwrap();
+    signup_and_login(&app, &random_email).await;
+
+    let response = app.post_enable_2fa(&serde_json::json!({ "password": "S3cure-Passw0rd!" })).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    let response = app.post_login(&serde_json::json!({
+        "email": random_email,
+        "password": "S3cure-Passw0rd!",
+    })).await;
+    assert_eq!(response.status().as_u16(), 206);
+    let login_code = app.app_state.two_fa_code_store.read().await.get_code(&email).await.unwrap();
+
+    let response = app.post_disable_2fa(&serde_json::json!({ "password": "S3cure-Passw0rd!" })).await;
+    assert_eq!(response.status().as_u16(), 206);
+    let (disable_attempt_id, disable_code) = app.app_state.disable_2fa_code_store.read().await.get_code(&email).await.unwrap();
+
+    // Requesting the disable code leaves the pending login alone
+    assert_eq!(app.app_state.two_fa_code_store.read().await.get_code(&email).await.unwrap(), login_code);
+
+    // And it is no second factor for logging in
+    let response = app.post_verify_2fa(&serde_json::json!({
+        "email": random_email,
+        "loginAttemptId": disable_attempt_id.as_ref(),
+        "2FACode": disable_code.as_ref(),
+    })).await;
+    assert_eq!(response.status().as_u16(), 401);
+
+    let (login_attempt_id, two_fa_code) = login_code;
+    let response = app.post_verify_2fa(&serde_json::json!({
+        "email": random_email,
+        "loginAttemptId": login_attempt_id.as_ref(),
+        "2FACode": two_fa_code.as_ref(),
+    })).await;
+    assert_eq!(response.status().as_u16(), 200);
+
+    app.clean_up().await;
+}
@@ -0,0 +1,166 @@
+use auth_service::{
+    routes::{TokenExchangeResponse, VerifyTokenResponse},
+    ErrorResponse,
+};
+
+use crate::helpers::{
+    get_random_email, signup_admin_and_login, signup_and_login, TestApp, TEST_CLIENT_AUDIENCE, TEST_CLIENT_ID, TEST_CLIENT_SECRET,
+};
+
+const GRANT_TYPE: &str = "urn:ietf:params:oauth:grant-type:token-exchange";
+const ACCESS_TOKEN_TYPE: &str = "urn:ietf:params:oauth:token-type:access_token";
+const CLIENT: Option<(&str, &str)> = Some((TEST_CLIENT_ID, TEST_CLIENT_SECRET));
+
+fn exchange_form<'a>(subject_token: &'a str, scope: &'a str) -> Vec<(&'a str, &'a str)> {
+    vec![
+        ("grant_type", GRANT_TYPE),
+        ("subject_token", subject_token),
+        ("subject_token_type", ACCESS_TOKEN_TYPE),
+        ("audience", TEST_CLIENT_AUDIENCE),
+        ("scope", scope),
+    ]
+}
+
+async fn oauth_error(response: reqwest::Response) -> String {
+    response.json::<ErrorResponse>().await.unwrap().error
+}
+
+#[tokio::test]
+async fn should_exchange_user_token_for_down_scoped_audience_token() {
+    let mut app = TestApp::new().await;
+
+    let email = get_random_email();
+    let subject_token = signup_admin_and_login(&app, &email).await;
+    let user_id = app.get_user_id(&email).await;
+
+    let response = app.post_token(CLIENT, &exchange_form(&subject_token, "users:manage")).await;
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(response.headers().get("cache-control").unwrap(), "no-store");
+    let exchanged = response.json::<TokenExchangeResponse>().await.unwrap();
+    assert_eq!(exchanged.issued_token_type, ACCESS_TOKEN_TYPE);
+    assert_eq!(exchanged.token_type, "Bearer");
+    assert_eq!(exchanged.scope, "users:manage");
+    assert!(exchanged.expires_in > 0 && exchanged.expires_in <= 300);
+
+    // The audience sees the user with the requested permissions only, and the client acting for them
+    let response = app.post_verify_token(&serde_json::json!({
+        "token": exchanged.access_token,
+        "audience": TEST_CLIENT_AUDIENCE,
+    })).await;
+    assert_eq!(response.status().as_u16(), 200);
+    let verified = response.json::<VerifyTokenResponse>().await.unwrap();
+    assert!(verified.roles.is_empty());
+    assert_eq!(verified.permissions, vec!["users:manage"]);
+    assert_eq!(verified.act.unwrap().sub, TEST_CLIENT_ID);
+
+    // Nobody else accepts it
+    let response = app.post_verify_token(&serde_json::json!({ "token": exchanged.access_token })).await;
+    assert_eq!(response.status().as_u16(), 401);
+    let response = app.post_verify_token(&serde_json::json!({ "token": exchanged.access_token, "audience": "shipping" })).await;
+    assert_eq!(response.status().as_u16(), 401);
+
+    // Nor can it be exchanged again
+    let response = app.post_token(CLIENT, &exchange_form(&exchanged.access_token, "")).await;
+    assert_eq!(response.status().as_u16(), 400);
+    assert_eq!(oauth_error(response).await, "invalid_grant");
+
+    // Revoking the sessions of the user revokes the exchanged tokens too
+    app.app_state.banned_token_store.write().await.ban_user_tokens(&user_id).await.unwrap();
+    let response = app.post_verify_token(&serde_json::json!({
+        "token": exchanged.access_token,
+        "audience": TEST_CLIENT_AUDIENCE,
+    })).await;
+    assert_eq!(response.status().as_u16(), 401);
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_authenticate_the_client() {
+    let mut app = TestApp::new().await;
+
+    let subject_token = signup_and_login(&app, &get_random_email()).await;
+
+    let response = app.post_token(Some((TEST_CLIENT_ID, "wrong-secret")), &exchange_form(&subject_token, "")).await;
+    assert_eq!(response.status().as_u16(), 401);
+    assert_eq!(response.headers().get("www-authenticate").unwrap(), "Basic");
+    assert_eq!(oauth_error(response).await, "invalid_client");
+
+    let response = app.post_token(None, &exchange_form(&subject_token, "")).await;
+    assert_eq!(response.status().as_u16(), 401);
+
+    // Credentials in the form are accepted as well, but not along with HTTP Basic
+    let mut form = exchange_form(&subject_token, "");
+    form.extend([("client_id", TEST_CLIENT_ID), ("client_secret", TEST_CLIENT_SECRET)]);
+    let response = app.post_token(None, &form).await;
+    assert_eq!(response.status().as_u16(), 200);
+    assert_eq!(response.json::<TokenExchangeResponse>().await.unwrap().scope, "");
+
+    let response = app.post_token(CLIENT, &form).await;
+    assert_eq!(response.status().as_u16(), 400);
+    assert_eq!(oauth_error(response).await, "invalid_request");
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_reject_invalid_token_exchange_requests() {
+    let mut app = TestApp::new().await;
+
+    let subject_token = signup_and_login(&app, &get_random_email()).await;
+
+    let mut form = exchange_form(&subject_token, "");
+    form[0] = ("grant_type", "password");
+    let response = app.post_token(CLIENT, &form).await;
+    assert_eq!(response.status().as_u16(), 400);
+    assert_eq!(oauth_error(response).await, "unsupported_grant_type");
+
+    let mut form = exchange_form(&subject_token, "");
+    form.retain(|(name, _)| *name != "audience");
+    let response = app.post_token(CLIENT, &form).await;
+    assert_eq!(response.status().as_u16(), 400);
+    assert_eq!(oauth_error(response).await, "invalid_request");
+
+    let mut form = exchange_form(&subject_token, "");
+    form[3] = ("audience", "shipping");
+    let response = app.post_token(CLIENT, &form).await;
+    assert_eq!(response.status().as_u16(), 400);
+    assert_eq!(oauth_error(response).await, "invalid_target");
+
+    // Scopes can only be narrowed
+    let response = app.post_token(CLIENT, &exchange_form(&subject_token, "users:manage")).await;
+    assert_eq!(response.status().as_u16(), 400);
+    assert_eq!(oauth_error(response).await, "invalid_scope");
+
+    let response = app.post_token(CLIENT, &exchange_form("invalid", "")).await;
+    assert_eq!(response.status().as_u16(), 400);
+    let error = response.json::<ErrorResponse>().await.unwrap();
+    assert_eq!(error.error, "invalid_grant");
+    assert_eq!(error.error_description.unwrap(), "Subject token is invalid or cannot be exchanged");
+
+    app.clean_up().await;
+}
+
+#[tokio::test]
+async fn should_not_exchange_impersonation_tokens() {
+    let mut app = TestApp::new().await;
+
+    let email = get_random_email();
+    signup_and_login(&app, &email).await;
+    let user_id = app.get_user_id(&email).await;
+    signup_admin_and_login(&app, &get_random_email()).await;
+
+    let response = app.post_admin_user_action(&user_id, "impersonate").await;
+    assert_eq!(response.status().as_u16(), 200);
+    let subject_token = response.cookies()
+        .find(|cookie| cookie.name() == "jwt")
+        .unwrap()
+        .value()
+        .to_owned();
+
+    let response = app.post_token(CLIENT, &exchange_form(&subject_token, "")).await;
+    assert_eq!(response.status().as_u16(), 400);
+    assert_eq!(oauth_error(response).await, "invalid_grant");
+
+    app.clean_up().await;
+}
@@ -1,4 +1,4 @@
-use auth_service::utils::constants::JWT_COOKIE_NAME;
+use auth_service::{routes::VerifyTokenResponse, utils::constants::JWT_COOKIE_NAME};
 use crate::helpers::{get_random_email, TestApp};
 
 
@@ -10,7 +10,7 @@ async fn should_return_200_valid_token() {
 
     let signup_body = serde_json::json!({
         "email": random_email,
-        "password": "password123",
+        "password": "S3cure-Passw0rd!",
         "requires2FA": false
     });
 
@@ -18,7 +18,7 @@ async fn should_return_200_valid_token() {
 
     let login_body = serde_json::json!({
         "email": random_email,
-        "password": "password123",
+        "password": "S3cure-Passw0rd!",
     });
 
     let login_response = app.post_login(&login_body).await;
@@ -36,6 +36,11 @@ async fn should_return_200_valid_token() {
     let response = app.post_verify_token(&token_body).await;
     assert_eq!(response.status().as_u16(), 200);
 
+    // Users without roles get empty roles and permissions
+    let claims = response.json::<VerifyTokenResponse>().await.unwrap();
+    assert!(claims.roles.is_empty());
+    assert!(claims.permissions.is_empty());
+
     app.clean_up().await;
 }
 
@@ -61,7 +66,7 @@ async fn should_return_401_if_banned_token() {
 
     let signup_body = serde_json::json!({
         "email": random_email,
-        "password": "password123",
+        "password": "S3cure-Passw0rd!",
         "requires2FA": false
     });
 
@@ -69,7 +74,7 @@ async fn should_return_401_if_banned_token() {
 
     let login_body = serde_json::json!({
         "email": random_email,
-        "password": "password123",
+        "password": "S3cure-Passw0rd!",
     });
 
     let login_response = app.post_login(&login_body).await;   